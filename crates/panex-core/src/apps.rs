@@ -0,0 +1,346 @@
+//! Applications the desktop has registered for a file, from the XDG
+//! `.desktop` files and `mimeapps.list` — what a file manager's "Open with"
+//! lists. Read on demand, when the list is asked for; there is nothing to
+//! find on systems without them.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{shared_openers, Opener, RunIn};
+
+/// What the desktop offers for all of `paths`: the defaults set for their
+/// MIME type first, then the rest by name.
+pub fn system_openers(paths: &[String]) -> Vec<Opener> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    let data_dirs = search_dirs(
+        "XDG_DATA_HOME",
+        &home.join(".local/share"),
+        "XDG_DATA_DIRS",
+        "/usr/local/share:/usr/share",
+    );
+    let config_dirs = search_dirs(
+        "XDG_CONFIG_HOME",
+        &home.join(".config"),
+        "XDG_CONFIG_DIRS",
+        "/etc/xdg",
+    );
+    let registry = Registry::read(&data_dirs, &config_dirs);
+    shared_openers(paths.iter().map(|p| registry.openers_for(p)))
+}
+
+/// What "Open with" lists for `paths`: the `configured` openers, then the
+/// desktop's that run something else.
+pub fn open_with_choices(configured: Vec<Opener>, paths: &[String]) -> Vec<Opener> {
+    let mut choices = configured;
+    for opener in system_openers(paths) {
+        if !choices.iter().any(|o| o.command == opener.command) {
+            choices.push(opener);
+        }
+    }
+    choices
+}
+
+/// Run `opener` on `paths` where it says, or where `default` says when it
+/// does not. `RunIn::Current` needs the caller's terminal, which only the
+/// caller can hand over; asked for here, it is a new terminal instead.
+pub fn launch(opener: &Opener, paths: &[String], default: RunIn) -> Result<(), String> {
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+    let runs: Vec<&[&str]> = if opener.single {
+        paths.chunks(1).collect()
+    } else {
+        vec![&paths]
+    };
+    for run in runs {
+        match opener.run.unwrap_or(default) {
+            RunIn::Terminal | RunIn::Current => {
+                crate::open_in_terminal_with_command(&opener.command, run)?
+            }
+            RunIn::Detached => crate::spawn_detached(&opener.command, run)?,
+        }
+    }
+    Ok(())
+}
+
+/// The user's directory, then the system's, as the spec orders them.
+fn search_dirs(home_var: &str, home_default: &Path, var: &str, default: &str) -> Vec<PathBuf> {
+    let home = std::env::var_os(home_var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .unwrap_or_else(|| home_default.to_path_buf());
+    let rest = std::env::var(var)
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| default.to_string());
+    std::iter::once(home)
+        .chain(rest.split(':').filter(|d| !d.is_empty()).map(PathBuf::from))
+        .collect()
+}
+
+struct App {
+    name: String,
+    exec: String,
+    terminal: bool,
+    mime_types: Vec<String>,
+}
+
+#[derive(Default)]
+struct Registry {
+    /// By desktop file ID, e.g. `org.gnome.eog.desktop`.
+    apps: HashMap<String, App>,
+    defaults: HashMap<String, Vec<String>>,
+    added: HashMap<String, Vec<String>>,
+    removed: HashMap<String, HashSet<String>>,
+}
+
+impl Registry {
+    /// The first directory to have an ID wins, and the first list to set a
+    /// type's default: the user's over the system's.
+    fn read(data_dirs: &[PathBuf], config_dirs: &[PathBuf]) -> Self {
+        let mut registry = Registry::default();
+        for dir in data_dirs {
+            let applications = dir.join("applications");
+            registry.scan(&applications, &applications);
+        }
+        let lists = config_dirs.iter().map(|d| d.join("mimeapps.list")).chain(
+            data_dirs
+                .iter()
+                .map(|d| d.join("applications/mimeapps.list")),
+        );
+        for list in lists {
+            if let Ok(text) = fs::read_to_string(&list) {
+                registry.read_mimeapps(&text);
+            }
+        }
+        registry
+    }
+
+    fn scan(&mut self, root: &Path, dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // A linked folder is not followed: one pointing back up would
+            // have the scan going round for ever.
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                self.scan(root, &path);
+                continue;
+            }
+            if path.extension().is_none_or(|e| e != "desktop") {
+                continue;
+            }
+            // A file in a subfolder has the folders in its ID, joined by
+            // dashes: `kde/okular.desktop` is `kde-okular.desktop`.
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let id = relative.to_string_lossy().replace('/', "-");
+            if self.apps.contains_key(&id) {
+                continue;
+            }
+            if let Some(app) = fs::read_to_string(&path)
+                .ok()
+                .and_then(|t| parse_desktop(&t))
+            {
+                self.apps.insert(id, app);
+            }
+        }
+    }
+
+    fn read_mimeapps(&mut self, text: &str) {
+        let mut section = "";
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('[') {
+                section = line;
+                continue;
+            }
+            let Some((mime, ids)) = line.split_once('=') else {
+                continue;
+            };
+            let ids = ids
+                .split(';')
+                .filter(|id| !id.is_empty())
+                .map(str::to_string);
+            let mime = mime.trim().to_string();
+            match section {
+                "[Default Applications]" => {
+                    self.defaults.entry(mime).or_insert_with(|| ids.collect());
+                }
+                "[Added Associations]" => self.added.entry(mime).or_default().extend(ids),
+                "[Removed Associations]" => self.removed.entry(mime).or_default().extend(ids),
+                _ => {}
+            }
+        }
+    }
+
+    fn openers_for(&self, path: &str) -> Vec<Opener> {
+        let mimes: Vec<String> = mime_guess::from_path(path)
+            .iter()
+            .map(|m| m.essence_str().to_string())
+            .collect();
+        let listed = |table: &HashMap<String, Vec<String>>| -> Vec<String> {
+            mimes
+                .iter()
+                .filter_map(|m| table.get(m))
+                .flatten()
+                .cloned()
+                .collect()
+        };
+        let removed: HashSet<&String> = mimes
+            .iter()
+            .filter_map(|m| self.removed.get(m))
+            .flatten()
+            .collect();
+        let mut by_name: Vec<(&String, &App)> = self
+            .apps
+            .iter()
+            .filter(|(_, app)| app.mime_types.iter().any(|t| mimes.contains(t)))
+            .collect();
+        by_name.sort_by_key(|(_, app)| app.name.to_lowercase());
+
+        let ids = listed(&self.defaults)
+            .into_iter()
+            .chain(listed(&self.added))
+            .chain(by_name.into_iter().map(|(id, _)| id.clone()));
+        let mut seen = HashSet::new();
+        ids.filter(|id| !removed.contains(id) && seen.insert(id.clone()))
+            .filter_map(|id| self.apps.get(&id))
+            .map(App::opener)
+            .collect()
+    }
+}
+
+impl App {
+    /// The `Exec` line with its field codes taken out: the paths are
+    /// appended instead. One that only takes `%f` or `%u` gets a file a run.
+    /// The rest is kept as written, quoted spaces and all.
+    fn opener(&self) -> Opener {
+        let mut single = false;
+        let mut command = String::new();
+        let mut chars = self.exec.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                command.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('%') => command.push('%'),
+                Some('f' | 'u') => single = true,
+                _ => {}
+            }
+        }
+        if self.exec.contains("%F") || self.exec.contains("%U") {
+            single = false;
+        }
+        Opener {
+            command: command.trim().to_string(),
+            name: Some(self.name.clone()),
+            run: Some(if self.terminal {
+                RunIn::Terminal
+            } else {
+                RunIn::Detached
+            }),
+            single,
+        }
+    }
+}
+
+/// The `[Desktop Entry]` group of an application that can open files, or
+/// None for anything else: links, hidden entries, programs without `Exec`.
+fn parse_desktop(text: &str) -> Option<App> {
+    let mut in_entry = false;
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+        // `Name[de]=` and the like are translations; the plain key is kept.
+        if let Some((key, value)) = line.split_once('=') {
+            fields.entry(key.trim()).or_insert(value.trim());
+        }
+    }
+    let yes = |key: &str| fields.get(key) == Some(&"true");
+    if fields.get("Type") != Some(&"Application") || yes("Hidden") || yes("NoDisplay") {
+        return None;
+    }
+    let mime_types: Vec<String> = fields
+        .get("MimeType")?
+        .split(';')
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect();
+    Some(App {
+        name: fields.get("Name")?.to_string(),
+        exec: fields.get("Exec")?.to_string(),
+        terminal: yes("Terminal"),
+        mime_types,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn desktop_files_are_offered_for_their_types_defaults_first() {
+        let tmp = TempDir::new("apps");
+        let root = &tmp.0;
+        let applications = root.join("data/applications");
+        fs::create_dir_all(applications.join("kde")).unwrap();
+        let entry = |name: &str, exec: &str, extra: &str| {
+            format!(
+                "[Desktop Entry]\nType=Application\nName={name}\nName[de]=Anders\nExec={exec}\nMimeType=image/png;{extra}\n"
+            )
+        };
+        fs::write(
+            applications.join("viewer.desktop"),
+            entry("Viewer", "view %F", ""),
+        )
+        .unwrap();
+        fs::write(
+            applications.join("kde/paint.desktop"),
+            entry("Paint", "paint --title \"A  B\" --new %u", ""),
+        )
+        .unwrap();
+        fs::write(
+            applications.join("hidden.desktop"),
+            entry("Hidden", "hide %f", "") + "NoDisplay=true\n",
+        )
+        .unwrap();
+        fs::write(
+            applications.join("gone.desktop"),
+            entry("Gone", "gone %f", ""),
+        )
+        .unwrap();
+        fs::write(
+            applications.join("text.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Text\nExec=text\nMimeType=text/plain;\n",
+        )
+        .unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&applications, applications.join("kde/loop")).unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::write(
+            root.join("config/mimeapps.list"),
+            "[Default Applications]\nimage/png=kde-paint.desktop;\n[Removed Associations]\nimage/png=gone.desktop;\n",
+        )
+        .unwrap();
+
+        let registry = Registry::read(&[root.join("data")], &[root.join("config")]);
+        let openers = registry.openers_for("/tmp/photo.PNG");
+        let names: Vec<&str> = openers.iter().map(Opener::label).collect();
+        assert_eq!(names, ["Paint", "Viewer"]);
+        assert_eq!(openers[0].command, "paint --title \"A  B\" --new");
+        assert!(openers[0].single, "%u takes one file");
+        assert!(!openers[1].single);
+        assert_eq!(openers[1].run, Some(RunIn::Detached));
+    }
+}
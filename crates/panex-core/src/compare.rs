@@ -0,0 +1,470 @@
+//! Comparing two directories side by side, and bringing them into line.
+//!
+//! One level deep, as the panes are: a folder present on both sides is the
+//! same folder here, whatever is inside it. Descending is what navigating
+//! both panes into it is for.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// How one name differs between the two directories. Names that match on
+/// every compared property are left out of a comparison altogether.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum Difference {
+    OnlyLeft,
+    OnlyRight,
+    LeftNewer,
+    RightNewer,
+    /// Same modification time but different size or content, or a file on
+    /// one side facing a folder on the other. Nothing says which one wins.
+    Different,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DirDiff {
+    pub name: String,
+    pub is_dir: bool,
+    pub difference: Difference,
+}
+
+/// What a name looks like on one side — enough to compare, and no more.
+struct Side {
+    is_dir: bool,
+    len: u64,
+    modified: u64,
+}
+
+fn list(dir: &str) -> Result<BTreeMap<String, Side>, String> {
+    let path = Path::new(dir);
+    if !path.is_dir() {
+        return Err(format!("Not a directory: {}", dir));
+    }
+    let mut sides = BTreeMap::new();
+    let read_dir = fs::read_dir(path).map_err(|e| format!("Failed to read directory: {}", e))?;
+    for entry in read_dir {
+        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
+        let meta = entry
+            .metadata()
+            .map_err(|e| format!("Failed to read metadata: {}", e))?;
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        sides.insert(
+            entry.file_name().to_string_lossy().to_string(),
+            Side {
+                is_dir: meta.is_dir(),
+                // The apparent length, not the blocks `FileEntry::size` counts:
+                // two identical files can sit in different numbers of blocks.
+                len: meta.len(),
+                modified,
+            },
+        );
+    }
+    Ok(sides)
+}
+
+/// Compare the entries of `left` and `right` by name, size and modification
+/// time, and — with `by_content` — by the bytes of files that otherwise look
+/// alike. Returns only the names that differ, sorted by name.
+pub fn compare_directories(
+    left: &str,
+    right: &str,
+    by_content: bool,
+) -> Result<Vec<DirDiff>, String> {
+    let left_entries = list(left)?;
+    let mut right_entries = list(right)?;
+    let mut diffs = Vec::new();
+
+    for (name, l) in left_entries {
+        let Some(r) = right_entries.remove(&name) else {
+            diffs.push(DirDiff {
+                name,
+                is_dir: l.is_dir,
+                difference: Difference::OnlyLeft,
+            });
+            continue;
+        };
+        let difference = if l.is_dir != r.is_dir {
+            Some(Difference::Different)
+        } else if l.is_dir {
+            None
+        } else {
+            compare_files(
+                &Path::new(left).join(&name),
+                &Path::new(right).join(&name),
+                &l,
+                &r,
+                by_content,
+            )
+        };
+        if let Some(difference) = difference {
+            diffs.push(DirDiff {
+                name,
+                is_dir: l.is_dir && r.is_dir,
+                difference,
+            });
+        }
+    }
+
+    for (name, r) in right_entries {
+        diffs.push(DirDiff {
+            name,
+            is_dir: r.is_dir,
+            difference: Difference::OnlyRight,
+        });
+    }
+
+    diffs.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(diffs)
+}
+
+fn compare_files(
+    left: &Path,
+    right: &Path,
+    l: &Side,
+    r: &Side,
+    by_content: bool,
+) -> Option<Difference> {
+    // Identical bytes are the same file whatever the clock says — a copy that
+    // did not keep its timestamp is still a copy.
+    if by_content && l.len == r.len && same_content(left, right) {
+        return None;
+    }
+    if l.modified > r.modified {
+        Some(Difference::LeftNewer)
+    } else if r.modified > l.modified {
+        Some(Difference::RightNewer)
+    } else if l.len != r.len || by_content {
+        // Equal times: under `by_content` reaching here means the bytes
+        // already differed, and without it the size is all there is to go on.
+        Some(Difference::Different)
+    } else {
+        None
+    }
+}
+
+/// Compared a block at a time rather than hashed: both files are local, and a
+/// hash would read every byte of both just the same before saying anything,
+/// where this stops at the first block that differs.
+fn same_content(left: &Path, right: &Path) -> bool {
+    let (Ok(mut a), Ok(mut b)) = (fs::File::open(left), fs::File::open(right)) else {
+        return false;
+    };
+    let mut buf_a = [0u8; 64 * 1024];
+    let mut buf_b = [0u8; 64 * 1024];
+    loop {
+        let Ok(n) = read_full(&mut a, &mut buf_a) else {
+            return false;
+        };
+        let Ok(m) = read_full(&mut b, &mut buf_b) else {
+            return false;
+        };
+        if n != m || buf_a[..n] != buf_b[..m] {
+            return false;
+        }
+        if n == 0 {
+            return true;
+        }
+    }
+}
+
+/// `read` may return short of a full buffer mid-file, which would misalign
+/// the two blocks being compared. This fills the buffer unless the file ends.
+fn read_full(file: &mut fs::File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum SyncDirection {
+    LeftToRight,
+    RightToLeft,
+    Both,
+}
+
+impl SyncDirection {
+    pub fn cycle(self) -> Self {
+        match self {
+            SyncDirection::LeftToRight => SyncDirection::RightToLeft,
+            SyncDirection::RightToLeft => SyncDirection::Both,
+            SyncDirection::Both => SyncDirection::LeftToRight,
+        }
+    }
+}
+
+/// One copy the sync will make: `source` into `dest_dir`, replacing whatever
+/// is there under the same name.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SyncStep {
+    pub name: String,
+    pub source: String,
+    pub dest_dir: String,
+    /// True when the copy goes left to right.
+    pub rightward: bool,
+}
+
+/// Everything a sync would do, worked out before any of it is done, so the
+/// whole of it can be read before anything is written.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct SyncPlan {
+    pub steps: Vec<SyncStep>,
+    /// Names left alone, with the reason.
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Turn a comparison into the copies that would resolve it.
+///
+/// One way never overwrites a newer file on the receiving side — that would
+/// be throwing away the more recent work — and lists it as skipped instead.
+/// Both ways copies each newer file across, and skips a `Different` name,
+/// where neither side is newer and there is nothing to choose by.
+pub fn plan_sync(left: &str, right: &str, diffs: &[DirDiff], direction: SyncDirection) -> SyncPlan {
+    let mut plan = SyncPlan::default();
+    let step = |d: &DirDiff, rightward: bool| {
+        let (from, to) = if rightward {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        SyncStep {
+            name: d.name.clone(),
+            source: Path::new(from).join(&d.name).to_string_lossy().to_string(),
+            dest_dir: to.to_string(),
+            rightward,
+        }
+    };
+
+    for d in diffs {
+        use Difference::*;
+        use SyncDirection::*;
+        // A file facing a folder cannot be copied over in either direction
+        // without deleting one of them, and sync does not delete.
+        let mismatched = d.difference == Different && is_mismatched(left, right, &d.name);
+        let rightward = match (direction, d.difference) {
+            _ if mismatched => {
+                plan.skipped.push((
+                    d.name.clone(),
+                    "a file on one side, a folder on the other".to_string(),
+                ));
+                continue;
+            }
+            (LeftToRight, OnlyLeft | LeftNewer | Different) => true,
+            (RightToLeft, OnlyRight | RightNewer | Different) => false,
+            (Both, OnlyLeft | LeftNewer) => true,
+            (Both, OnlyRight | RightNewer) => false,
+            (Both, Different) => {
+                plan.skipped.push((
+                    d.name.clone(),
+                    "differs with neither side newer".to_string(),
+                ));
+                continue;
+            }
+            (LeftToRight, RightNewer) | (RightToLeft, LeftNewer) => {
+                plan.skipped
+                    .push((d.name.clone(), "newer on the receiving side".to_string()));
+                continue;
+            }
+            // Only on the receiving side: one way does not delete, so there
+            // is nothing to do and nothing worth reporting.
+            (LeftToRight, OnlyRight) | (RightToLeft, OnlyLeft) => continue,
+        };
+        plan.steps.push(step(d, rightward));
+    }
+    plan
+}
+
+fn is_mismatched(left: &str, right: &str, name: &str) -> bool {
+    Path::new(left).join(name).is_dir() != Path::new(right).join(name).is_dir()
+}
+
+/// Carry out a plan through the same copy the clipboard uses. Keeps going past
+/// a failed step, so one unreadable file does not strand the rest, and returns
+/// how many steps succeeded alongside what went wrong with the others.
+pub fn apply_sync(plan: &SyncPlan) -> (usize, Vec<String>) {
+    let mut done = 0;
+    let mut errors = Vec::new();
+    for step in &plan.steps {
+        match crate::copy_entry(&step.source, &step.dest_dir) {
+            Ok(dest) => {
+                // A plain copy stamps the file with the time of copying, which
+                // the next comparison would read as the copy being newer.
+                keep_modified(&step.source, &dest);
+                done += 1;
+            }
+            Err(e) => errors.push(format!("{}: {}", step.name, e)),
+        }
+    }
+    (done, errors)
+}
+
+fn keep_modified(source: &str, dest: &str) {
+    let Ok(modified) = fs::metadata(source).and_then(|m| m.modified()) else {
+        return;
+    };
+    if Path::new(dest).is_file() {
+        if let Ok(file) = fs::File::options().write(true).open(dest) {
+            let _ = file.set_modified(modified);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::time::{Duration, SystemTime};
+
+    /// Two sibling directories under the system temp dir, removed on drop.
+    struct Pair(TempDir);
+
+    impl Pair {
+        fn new(tag: &str) -> Self {
+            let tmp = TempDir::new(&format!("compare-{}", tag));
+            fs::create_dir_all(tmp.0.join("left")).unwrap();
+            fs::create_dir_all(tmp.0.join("right")).unwrap();
+            Self(tmp)
+        }
+
+        fn root(&self) -> &Path {
+            &self.0 .0
+        }
+
+        fn left(&self) -> String {
+            self.root().join("left").to_string_lossy().to_string()
+        }
+
+        fn right(&self) -> String {
+            self.root().join("right").to_string_lossy().to_string()
+        }
+
+        /// Write a file and pin its modification time, so tests do not depend
+        /// on how fast two writes land.
+        fn write(&self, side: &str, name: &str, content: &str, secs: u64) {
+            let path = self.root().join(side).join(name);
+            fs::write(&path, content).unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+                .unwrap();
+        }
+    }
+
+    fn kinds(diffs: &[DirDiff]) -> Vec<(&str, Difference)> {
+        diffs
+            .iter()
+            .map(|d| (d.name.as_str(), d.difference))
+            .collect()
+    }
+
+    #[test]
+    fn reports_each_kind_of_difference_and_leaves_matches_out() {
+        let pair = Pair::new("kinds");
+        pair.write("left", "same.txt", "x", 1000);
+        pair.write("right", "same.txt", "x", 1000);
+        pair.write("left", "mine.txt", "x", 1000);
+        pair.write("right", "theirs.txt", "x", 1000);
+        pair.write("left", "fresh.txt", "new", 2000);
+        pair.write("right", "fresh.txt", "old", 1000);
+        pair.write("left", "sized.txt", "short", 1000);
+        pair.write("right", "sized.txt", "longer", 1000);
+
+        let diffs = compare_directories(&pair.left(), &pair.right(), false).unwrap();
+
+        assert_eq!(
+            kinds(&diffs),
+            vec![
+                ("fresh.txt", Difference::LeftNewer),
+                ("mine.txt", Difference::OnlyLeft),
+                ("sized.txt", Difference::Different),
+                ("theirs.txt", Difference::OnlyRight),
+            ]
+        );
+    }
+
+    /// Same size, same time, different bytes: only a content comparison can
+    /// tell, and only it should say so.
+    #[test]
+    fn content_comparison_catches_what_size_and_time_miss() {
+        let pair = Pair::new("content");
+        pair.write("left", "a.txt", "abc", 1000);
+        pair.write("right", "a.txt", "xyz", 1000);
+
+        assert!(compare_directories(&pair.left(), &pair.right(), false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            kinds(&compare_directories(&pair.left(), &pair.right(), true).unwrap()),
+            vec![("a.txt", Difference::Different)]
+        );
+    }
+
+    /// A copy that lost its timestamp is still the same file.
+    #[test]
+    fn identical_content_outranks_a_differing_clock() {
+        let pair = Pair::new("clock");
+        pair.write("left", "a.txt", "abc", 1000);
+        pair.write("right", "a.txt", "abc", 5000);
+
+        assert!(compare_directories(&pair.left(), &pair.right(), true)
+            .unwrap()
+            .is_empty());
+    }
+
+    /// One way does not clobber newer work on the receiving side.
+    #[test]
+    fn one_way_skips_a_newer_target() {
+        let pair = Pair::new("oneway");
+        pair.write("left", "a.txt", "old", 1000);
+        pair.write("right", "a.txt", "new", 2000);
+        pair.write("left", "b.txt", "b", 1000);
+
+        let diffs = compare_directories(&pair.left(), &pair.right(), false).unwrap();
+        let plan = plan_sync(
+            &pair.left(),
+            &pair.right(),
+            &diffs,
+            SyncDirection::LeftToRight,
+        );
+
+        assert_eq!(plan.steps.len(), 1);
+        assert_eq!(plan.steps[0].name, "b.txt");
+        assert_eq!(plan.skipped[0].0, "a.txt");
+    }
+
+    /// After a two-way sync there is nothing left to compare — which needs the
+    /// copies to have kept their timestamps.
+    #[test]
+    fn a_two_way_sync_leaves_nothing_to_compare() {
+        let pair = Pair::new("twoway");
+        pair.write("left", "a.txt", "new", 2000);
+        pair.write("right", "a.txt", "old", 1000);
+        pair.write("left", "l.txt", "l", 1000);
+        pair.write("right", "r.txt", "r", 1000);
+        fs::create_dir(pair.root().join("right").join("dir")).unwrap();
+        pair.write("right", "dir/inner.txt", "i", 1000);
+
+        let diffs = compare_directories(&pair.left(), &pair.right(), false).unwrap();
+        let plan = plan_sync(&pair.left(), &pair.right(), &diffs, SyncDirection::Both);
+        let (done, errors) = apply_sync(&plan);
+
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(done, 4);
+        let after = compare_directories(&pair.left(), &pair.right(), false).unwrap();
+        assert!(after.is_empty(), "still differs: {after:?}");
+        assert_eq!(
+            fs::read_to_string(pair.root().join("right").join("a.txt")).unwrap(),
+            "new"
+        );
+    }
+}
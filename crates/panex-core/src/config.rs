@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Set once, at start-up, by `--config`.
+static CONFIG_FILE: OnceLock<PathBuf> = OnceLock::new();
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct PanexConfig {
@@ -9,40 +13,569 @@ pub struct PanexConfig {
     pub favorites: FavoritesConfig,
     #[serde(default)]
     pub open: OpenConfig,
+    #[serde(default)]
+    pub diff: DiffConfig,
+    /// Named layouts, e.g. `[layouts.deploy]`. Sorted by name, which is the
+    /// order a picker lists them in.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub layouts: BTreeMap<String, LayoutPreset>,
+    /// Key chords to action names for the TUI, e.g. `gg = "top"`. Laid over
+    /// the built-in bindings; `"none"` removes one.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub keys: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "ThemeConfig::is_empty")]
+    pub theme: ThemeConfig,
+    #[serde(default, skip_serializing_if = "FilesConfig::is_empty")]
+    pub files: FilesConfig,
+    #[serde(default, skip_serializing_if = "SizesConfig::is_default")]
+    pub sizes: SizesConfig,
+    #[serde(default, skip_serializing_if = "GitConfig::is_default")]
+    pub git: GitConfig,
 }
 
+/// The favorite folders, in the order they are listed — each group
+/// together, the ungrouped first.
+///
+/// ```toml
+/// [favorites]
+/// paths = [
+///   "~/dev",
+///   { path = "~/notes", label = "Notes", group = "writing", key = "n" },
+/// ]
+/// ```
+///
+/// A favorite with nothing but its path is written as a bare path, which is
+/// all favorites used to be, so a config from before labels reads the same.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct FavoritesConfig {
-    #[serde(default)]
-    pub paths: Vec<String>,
+    #[serde(
+        default,
+        rename = "paths",
+        serialize_with = "write_favorites",
+        deserialize_with = "read_favorites"
+    )]
+    pub entries: Vec<Favorite>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Favorite {
+    pub path: String,
+    /// Shown instead of the path.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Goes there with `'` and the key in the TUI, as a vim mark does.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<char>,
+}
+
+impl Favorite {
+    pub fn new(path: &str) -> Self {
+        Favorite {
+            path: path.to_string(),
+            ..Favorite::default()
+        }
+    }
+
+    /// The label, or the path when there is none.
+    pub fn name(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.path)
+    }
+}
+
+/// A favorite as the config holds it: a bare path, or a table.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum FavoriteEntry {
+    Path(String),
+    Full(Favorite),
+}
+
+fn read_favorites<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Vec<Favorite>, D::Error> {
+    let entries = Vec::<FavoriteEntry>::deserialize(d)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| match entry {
+            FavoriteEntry::Path(path) => Favorite::new(&path),
+            FavoriteEntry::Full(favorite) => favorite,
+        })
+        .collect())
+}
+
+fn write_favorites<S: serde::Serializer>(favorites: &[Favorite], s: S) -> Result<S::Ok, S::Error> {
+    let entries: Vec<FavoriteEntry> = favorites
+        .iter()
+        .map(|f| match f {
+            Favorite {
+                path,
+                label: None,
+                group: None,
+                key: None,
+            } => FavoriteEntry::Path(path.clone()),
+            f => FavoriteEntry::Full(f.clone()),
+        })
+        .collect();
+    entries.serialize(s)
+}
+
+impl FavoritesConfig {
+    /// Bring each group's favorites together where the group first comes,
+    /// keeping their order within it; the ungrouped go first.
+    fn regroup(&mut self) {
+        let mut groups: Vec<Option<String>> = vec![None];
+        for favorite in &self.entries {
+            if !groups.contains(&favorite.group) {
+                groups.push(favorite.group.clone());
+            }
+        }
+        self.entries
+            .sort_by_key(|f| groups.iter().position(|g| *g == f.group));
+    }
+
+    fn position(&self, path: &str) -> Option<usize> {
+        let normalized = normalize_path(path);
+        self.entries.iter().position(|f| normalize_path(&f.path) == normalized)
+    }
+
+    /// Put `favorite` in place of the one with its path, or add it. A key
+    /// takes a folder to one place, so another favorite holding the same
+    /// key gives it up.
+    fn update(&mut self, mut favorite: Favorite) {
+        favorite.label = favorite.label.filter(|l| !l.trim().is_empty());
+        favorite.group = favorite.group.filter(|g| !g.trim().is_empty());
+        if let Some(key) = favorite.key {
+            for other in &mut self.entries {
+                if other.key == Some(key) {
+                    other.key = None;
+                }
+            }
+        }
+        match self.position(&favorite.path) {
+            Some(at) => {
+                favorite.path = self.entries[at].path.clone();
+                self.entries[at] = favorite;
+            }
+            None => {
+                favorite.path = normalize_path(&favorite.path);
+                self.entries.push(favorite);
+            }
+        }
+        self.regroup();
+    }
+
+    /// Move the favorite at `index` one place up or down within its group,
+    /// returning where it went. At the edge of its group it stays: it
+    /// changes group by being given another.
+    fn shift(&mut self, index: usize, up: bool) -> Option<usize> {
+        let to = if up { index.checked_sub(1)? } else { index + 1 };
+        if to >= self.entries.len() || self.entries[to].group != self.entries[index].group {
+            return None;
+        }
+        self.entries.swap(index, to);
+        Some(to)
+    }
+
+    pub fn get(&self, path: &str) -> Option<&Favorite> {
+        self.position(path).map(|at| &self.entries[at])
+    }
+
+    /// The favorite bound to `key`.
+    pub fn for_key(&self, key: char) -> Option<&Favorite> {
+        self.entries.iter().find(|f| f.key == Some(key))
+    }
 }
 
+/// What files open with, on the desktop and in the TUI, e.g.
+///
+/// ```toml
+/// [open.tui]
+/// ".md" = "nvim"
+/// ".png" = ["feh", { name = "GIMP", command = "gimp", run = "detached" }]
+/// "image/*" = { command = "feh", run = "detached" }
+/// "*.test.ts" = { command = "npx vitest run", run = "current" }
+/// ```
+///
+/// A key is an extension (`.md`, `.tar.gz`), a MIME type (`image/png`,
+/// `image/*`) or a pattern matched against the name (`Makefile`,
+/// `*.test.ts`). Its value is one opener or a list, the first being what
+/// a file opens with and the rest offered by "Open with". A bare command is
+/// all an opener used to be, so a config from before lists reads the same.
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct OpenConfig {
+    #[serde(default, serialize_with = "write_openers", deserialize_with = "read_openers")]
+    pub gui: BTreeMap<String, Vec<Opener>>,
+    #[serde(default, serialize_with = "write_openers", deserialize_with = "read_openers")]
+    pub tui: BTreeMap<String, Vec<Opener>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Opener {
+    /// Run by the shell with the files' paths appended, so `"nvim -R"`
+    /// works as written.
+    pub command: String,
+    /// Shown instead of the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Unset, a new terminal in the TUI, and on its own on the desktop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<RunIn>,
+    /// Takes one file, so several are opened one run each.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub single: bool,
+}
+
+impl Opener {
+    pub fn new(command: &str) -> Self {
+        Opener {
+            command: command.to_string(),
+            ..Opener::default()
+        }
+    }
+
+    /// The name, or the command when there is none.
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.command)
+    }
+}
+
+/// Where an opener's program runs.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RunIn {
+    /// A new terminal window or tab.
+    Terminal,
+    /// The TUI's own terminal, which it hands over until the program exits.
+    Current,
+    /// In the background, with no terminal: for graphical programs.
+    Detached,
+}
+
+/// An opener as the config holds it: a bare command, or a table.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum OpenerEntry {
+    Command(String),
+    Full(Opener),
+}
+
+impl From<OpenerEntry> for Opener {
+    fn from(entry: OpenerEntry) -> Self {
+        match entry {
+            OpenerEntry::Command(command) => Opener::new(&command),
+            OpenerEntry::Full(opener) => opener,
+        }
+    }
+}
+
+impl From<&Opener> for OpenerEntry {
+    fn from(opener: &Opener) -> Self {
+        if *opener == Opener::new(&opener.command) {
+            OpenerEntry::Command(opener.command.clone())
+        } else {
+            OpenerEntry::Full(opener.clone())
+        }
+    }
+}
+
+/// One opener, or several.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum OpenerList {
+    One(OpenerEntry),
+    Many(Vec<OpenerEntry>),
+}
+
+fn read_openers<'de, D: serde::Deserializer<'de>>(
+    d: D,
+) -> Result<BTreeMap<String, Vec<Opener>>, D::Error> {
+    let lists = BTreeMap::<String, OpenerList>::deserialize(d)?;
+    Ok(lists
+        .into_iter()
+        .map(|(key, list)| {
+            let openers = match list {
+                OpenerList::One(entry) => vec![entry.into()],
+                OpenerList::Many(entries) => entries.into_iter().map(Opener::from).collect(),
+            };
+            (key, openers)
+        })
+        .collect())
+}
+
+fn write_openers<S: serde::Serializer>(
+    openers: &BTreeMap<String, Vec<Opener>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    let lists: BTreeMap<&String, OpenerList> = openers
+        .iter()
+        .map(|(key, list)| {
+            let list = match list.as_slice() {
+                [one] => OpenerList::One(one.into()),
+                many => OpenerList::Many(many.iter().map(OpenerEntry::from).collect()),
+            };
+            (key, list)
+        })
+        .collect();
+    lists.serialize(s)
+}
+
+/// The openers in `table` for `path`, the most particular first: patterns
+/// naming the file, then extensions, longest first, then MIME types, with
+/// `image/*` after `image/png`. One command is offered once.
+pub fn openers_for(table: &BTreeMap<String, Vec<Opener>>, path: &str) -> Vec<Opener> {
+    let name = Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let mimes: Vec<String> = mime_guess::from_path(path)
+        .iter()
+        .map(|m| m.essence_str().to_string())
+        .collect();
+    let mut matched: Vec<(u8, std::cmp::Reverse<usize>, &Vec<Opener>)> = Vec::new();
+    for (key, openers) in table {
+        let lower = key.to_lowercase();
+        let rank = if let Some((kind, sub)) = lower.split_once('/') {
+            if mimes.contains(&lower) {
+                Some(2)
+            } else if sub == "*" && mimes.iter().any(|m| m.split('/').next() == Some(kind)) {
+                Some(3)
+            } else {
+                None
+            }
+        } else if lower.starts_with('.') && !lower.contains(['*', '?', '[']) {
+            (name.ends_with(&lower) && name != lower).then_some(1)
+        } else {
+            glob::Pattern::new(&lower)
+                .is_ok_and(|p| p.matches(&name))
+                .then_some(0)
+        };
+        if let Some(rank) = rank {
+            matched.push((rank, std::cmp::Reverse(key.len()), openers));
+        }
+    }
+    matched.sort_by_key(|(rank, length, _)| (*rank, *length));
+    let mut found: Vec<Opener> = Vec::new();
+    for opener in matched.into_iter().flat_map(|(_, _, openers)| openers) {
+        if !found.iter().any(|o| o.command == opener.command) {
+            found.push(opener.clone());
+        }
+    }
+    found
+}
+
+/// The openers every one of `paths` has, in the order the first has them.
+pub fn shared_openers(per_path: impl IntoIterator<Item = Vec<Opener>>) -> Vec<Opener> {
+    let mut lists = per_path.into_iter();
+    let mut shared = lists.next().unwrap_or_default();
+    for list in lists {
+        shared.retain(|o| list.iter().any(|other| other.command == o.command));
+    }
+    shared
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DiffConfig {
+    /// An external diff command (e.g. `"nvim -d"`), run in a terminal with the
+    /// two files appended. Unset, the TUI shows the diff itself.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+}
+
+/// The TUI's colours: a built-in theme by name, with single roles
+/// overridden, e.g.
+///
+/// ```toml
+/// [theme]
+/// name = "light"
+/// accent = "#b8860b"
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ThemeConfig {
+    /// "dark" (the default), "light", "retro" or "high-contrast".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// How many colours the terminal has: "auto" (the default), "truecolor",
+    /// "256" or "16".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colors: Option<String>,
+    /// Every other key: a role and its colour, checked by the TUI.
+    #[serde(flatten)]
+    pub roles: BTreeMap<String, String>,
+}
+
+impl ThemeConfig {
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.colors.is_none() && self.roles.is_empty()
+    }
+}
+
+/// How the TUI's file list shows each entry, e.g.
+///
+/// ```toml
+/// [files]
+/// icons = false
+///
+/// [files.colors]
+/// executable = "bold green"
+/// "*.rs" = "#dea584"
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FilesConfig {
+    /// Nerd Font icons before the names. Unset, they are shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icons: Option<bool>,
+    /// Whether `$LS_COLORS` is read. Unset, it is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ls_colors: Option<bool>,
+    /// A type (`dir`, `executable`, `archive`, …) or a `*.ext` pattern, and
+    /// its style; checked by the TUI.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, String>,
+    /// The columns, left to right, each with an optional width, e.g.
+    /// `["name", "size:10", "modified"]`. Empty is the built-in set.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    /// "human" (the default) or "exact".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_format: Option<String>,
+    /// "absolute" (the default), "relative" or "iso".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date_format: Option<String>,
+}
+
+impl FilesConfig {
+    pub fn is_empty(&self) -> bool {
+        self.icons.is_none()
+            && self.ls_colors.is_none()
+            && self.colors.is_empty()
+            && self.columns.is_empty()
+            && self.size_format.is_none()
+            && self.date_format.is_none()
+    }
+}
+
+/// How folder sizes are measured.
+///
+/// ```toml
+/// [sizes]
+/// follow_symlinks = true
+/// cross_filesystems = true
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct SizesConfig {
+    /// Count what a symlink points to rather than the link. Off by default.
     #[serde(default)]
-    pub gui: HashMap<String, String>,
+    pub follow_symlinks: bool,
+    /// Go on into filesystems mounted below the folder. Off by default.
+    #[serde(default)]
+    pub cross_filesystems: bool,
+}
+
+impl SizesConfig {
+    pub fn is_default(&self) -> bool {
+        *self == SizesConfig::default()
+    }
+
+    pub fn options(&self) -> crate::SizeOptions {
+        crate::SizeOptions {
+            follow_symlinks: self.follow_symlinks,
+            cross_filesystems: self.cross_filesystems,
+        }
+    }
+}
+
+/// Git status in the file list.
+///
+/// ```toml
+/// [git]
+/// enabled = false       # never run git
+/// hide_ignored = true   # start with ignored files hidden
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GitConfig {
+    /// On unless set to false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
     #[serde(default)]
-    pub tui: HashMap<String, String>,
+    pub hide_ignored: bool,
+}
+
+impl GitConfig {
+    pub fn is_default(&self) -> bool {
+        *self == GitConfig::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled != Some(false)
+    }
+}
+
+/// A layout as written in the config: a folder, or a split of several.
+///
+/// ```toml
+/// [layouts.deploy]
+/// split = "vertical"
+/// panes = [
+///   "~/deploy/staging",
+///   { split = "horizontal", panes = ["~/deploy/prod", "~/deploy/logs"] },
+/// ]
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum LayoutPreset {
+    Path(String),
+    Split {
+        split: PresetSplit,
+        /// The first pane's share, 0 to 1. Unset, every pane gets the same.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ratio: Option<f64>,
+        panes: Vec<LayoutPreset>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetSplit {
+    /// Side by side, as `|` splits.
+    Vertical,
+    /// One above the other, as `_` splits.
+    Horizontal,
 }
 
 impl PanexConfig {
-    /// Returns ~/.panex/config.toml
+    /// Returns ~/.panex/config.toml, or the file given to `use_config_file`.
     pub fn config_path() -> Result<PathBuf, String> {
+        if let Some(path) = CONFIG_FILE.get() {
+            return Ok(path.clone());
+        }
         let home = dirs::home_dir().ok_or("Could not determine home directory")?;
         Ok(home.join(".panex").join("config.toml"))
     }
 
+    /// Read and save `path` instead of ~/.panex/config.toml from now on.
+    /// Only the first call counts: the config is one per process.
+    pub fn use_config_file(path: PathBuf) {
+        let _ = CONFIG_FILE.set(path);
+    }
+
     /// Load config from disk, returning defaults if file doesn't exist.
     pub fn load() -> Self {
-        let path = match Self::config_path() {
-            Ok(p) => p,
-            Err(_) => return Self::default(),
-        };
-        let content = match fs::read_to_string(&path) {
-            Ok(c) => c,
-            Err(_) => return Self::default(),
-        };
-        toml::from_str(&content).unwrap_or_default()
+        Self::config_path()
+            .and_then(|path| Self::read(&path))
+            .unwrap_or_default()
+    }
+
+    /// Read one config file, saying what is wrong with it rather than
+    /// falling back to the defaults.
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Failed to read config: {}", e))?;
+        let mut config: PanexConfig =
+            toml::from_str(&content).map_err(|e| format!("Failed to parse config: {}", e))?;
+        // A hand-edited list may have a group in two places; it is shown,
+        // and written back, in one.
+        config.favorites.regroup();
+        Ok(config)
     }
 
     /// Save config to disk, creating ~/.panex/ if needed.
@@ -58,14 +591,13 @@ impl PanexConfig {
     }
 
     pub fn is_favorite(&self, path: &str) -> bool {
-        let normalized = normalize_path(path);
-        self.favorites.paths.iter().any(|p| normalize_path(p) == normalized)
+        self.favorites.position(path).is_some()
     }
 
     pub fn add_favorite(&mut self, path: &str) -> Result<(), String> {
         let normalized = normalize_path(path);
         if !self.is_favorite(&normalized) {
-            self.favorites.paths.push(normalized);
+            self.favorites.update(Favorite::new(&normalized));
             self.save()?;
         }
         Ok(())
@@ -73,7 +605,9 @@ impl PanexConfig {
 
     pub fn remove_favorite(&mut self, path: &str) -> Result<(), String> {
         let normalized = normalize_path(path);
-        self.favorites.paths.retain(|p| normalize_path(p) != normalized);
+        self.favorites
+            .entries
+            .retain(|f| normalize_path(&f.path) != normalized);
         self.save()
     }
 
@@ -87,24 +621,29 @@ impl PanexConfig {
         }
     }
 
-    /// Get the custom app for a file extension in GUI mode.
-    pub fn get_gui_app(&self, ext: &str) -> Option<&String> {
-        let key = if ext.starts_with('.') {
-            ext.to_string()
-        } else {
-            format!(".{}", ext)
-        };
-        self.open.gui.get(&key)
+    pub fn update_favorite(&mut self, favorite: Favorite) -> Result<(), String> {
+        self.favorites.update(favorite);
+        self.save()
     }
 
-    /// Get the custom command for a file extension in TUI mode.
-    pub fn get_tui_app(&self, ext: &str) -> Option<&String> {
-        let key = if ext.starts_with('.') {
-            ext.to_string()
-        } else {
-            format!(".{}", ext)
-        };
-        self.open.tui.get(&key)
+    /// Move the favorite at `index` up or down, returning where it is now.
+    pub fn move_favorite(&mut self, index: usize, up: bool) -> Result<usize, String> {
+        match self.favorites.shift(index, up) {
+            Some(to) => self.save().map(|()| to),
+            None => Ok(index),
+        }
+    }
+
+    /// What the desktop app offers for all of `paths`, the first being what
+    /// they open with.
+    pub fn gui_openers(&self, paths: &[String]) -> Vec<Opener> {
+        shared_openers(paths.iter().map(|p| openers_for(&self.open.gui, p)))
+    }
+
+    /// What the TUI offers for all of `paths`, the first being what they
+    /// open with.
+    pub fn tui_openers(&self, paths: &[String]) -> Vec<Opener> {
+        shared_openers(paths.iter().map(|p| openers_for(&self.open.tui, p)))
     }
 }
 
@@ -121,3 +660,156 @@ fn normalize_path(path: &str) -> String {
     };
     expanded.trim_end_matches('/').to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layouts_read_from_toml() {
+        let config: PanexConfig = toml::from_str(
+            r#"
+            [layouts.deploy]
+            split = "vertical"
+            ratio = 0.3
+            panes = [
+              "~/staging",
+              { split = "horizontal", panes = ["~/prod", "/var/log"] },
+            ]
+
+            [layouts.single]
+            split = "horizontal"
+            panes = ["/tmp"]
+            "#,
+        )
+        .unwrap();
+        let names: Vec<&String> = config.layouts.keys().collect();
+        assert_eq!(names, ["deploy", "single"]);
+        let LayoutPreset::Split { split, ratio, panes } = &config.layouts["deploy"] else {
+            panic!("deploy should be a split");
+        };
+        assert_eq!((*split, *ratio), (PresetSplit::Vertical, Some(0.3)));
+        assert_eq!(panes[0], LayoutPreset::Path("~/staging".to_string()));
+        assert!(matches!(&panes[1], LayoutPreset::Split { panes, .. } if panes.len() == 2));
+    }
+
+    #[test]
+    fn favorites_read_as_bare_paths_or_tables() {
+        let mut config: PanexConfig = toml::from_str(
+            r#"
+            [favorites]
+            paths = [
+              { path = "/srv/b", group = "work", key = "b" },
+              "/home/me/dev",
+              { path = "/srv/a", label = "Logs", group = "work" },
+              { path = "/notes", group = "writing" },
+            ]
+            "#,
+        )
+        .unwrap();
+        config.favorites.regroup();
+        let names: Vec<&str> = config.favorites.entries.iter().map(Favorite::name).collect();
+        assert_eq!(names, ["/home/me/dev", "/srv/b", "Logs", "/notes"]);
+        assert_eq!(config.favorites.for_key('b').unwrap().path, "/srv/b");
+
+        // Plain favorites go back out as they came in.
+        let text = toml::to_string_pretty(&config).unwrap();
+        assert!(text.contains(r#""/home/me/dev""#), "{}", text);
+        let again: PanexConfig = toml::from_str(&text).unwrap();
+        assert_eq!(again.favorites.entries, config.favorites.entries);
+    }
+
+    #[test]
+    fn favorites_move_within_their_group_and_keys_stay_unique() {
+        let mut favorites = FavoritesConfig::default();
+        for path in ["/a", "/b", "/c"] {
+            favorites.update(Favorite::new(path));
+        }
+        favorites.update(Favorite {
+            group: Some("g".to_string()),
+            key: Some('1'),
+            ..Favorite::new("/a")
+        });
+        let paths = |f: &FavoritesConfig| f.entries.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&favorites), ["/b", "/c", "/a"]);
+        assert_eq!(favorites.shift(1, false), None, "/a is in another group");
+        assert_eq!(favorites.shift(1, true), Some(0));
+        assert_eq!(paths(&favorites), ["/c", "/b", "/a"]);
+
+        favorites.update(Favorite {
+            key: Some('1'),
+            ..Favorite::new("/b/")
+        });
+        assert_eq!(favorites.for_key('1').unwrap().path, "/b");
+        assert_eq!(favorites.entries.len(), 3);
+    }
+
+    /// What `add_favorite` does before it saves.
+    #[test]
+    fn a_new_favorite_goes_with_the_ungrouped_ones() {
+        let mut favorites = FavoritesConfig::default();
+        favorites.update(Favorite::new("/a"));
+        favorites.update(Favorite {
+            group: Some("g".to_string()),
+            ..Favorite::new("/b")
+        });
+
+        favorites.update(Favorite::new("/c"));
+        let paths: Vec<&str> = favorites.entries.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["/a", "/c", "/b"]);
+        assert_eq!(favorites.shift(1, true), Some(0));
+    }
+
+    #[test]
+    fn openers_are_found_by_pattern_then_extension_then_type() {
+        let config: PanexConfig = toml::from_str(
+            r#"
+            [open.tui]
+            ".md" = "nvim"
+            ".gz" = "zless"
+            ".tar.gz" = ["tar tzf", { command = "file-roller", run = "detached" }]
+            "text/*" = { command = "less", run = "current" }
+            "*.test.md" = "mdtest"
+            "#,
+        )
+        .unwrap();
+        let commands = |paths: &[&str]| {
+            let paths: Vec<String> = paths.iter().map(|p| p.to_string()).collect();
+            config
+                .tui_openers(&paths)
+                .into_iter()
+                .map(|o| o.command)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(commands(&["/x/README.MD"]), ["nvim", "less"]);
+        assert_eq!(commands(&["/x/a.test.md"]), ["mdtest", "nvim", "less"]);
+        assert_eq!(commands(&["/x/a.tar.gz"]), ["tar tzf", "file-roller", "zless"]);
+        assert_eq!(commands(&["/x/a.md", "/x/b.txt"]), ["less"]);
+
+        // Written back as it was read: a bare command stays one.
+        let written = toml::to_string(&config).unwrap();
+        assert!(written.contains(r#"".md" = "nvim""#), "{}", written);
+        let again: PanexConfig = toml::from_str(&written).unwrap();
+        assert_eq!(again.open.tui[".tar.gz"][1].run, Some(RunIn::Detached));
+    }
+
+    /// The config is written back whenever a favorite is toggled, so the
+    /// free-form roles have to come through the round trip as they went in.
+    #[test]
+    fn theme_roles_survive_a_save() {
+        let config: PanexConfig = toml::from_str(
+            r##"
+            [theme]
+            name = "light"
+            accent = "#b8860b"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.theme.name.as_deref(), Some("light"));
+        assert_eq!(config.theme.roles["accent"], "#b8860b");
+
+        let again: PanexConfig = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(again.theme.roles, config.theme.roles);
+        assert!(PanexConfig::default().theme.is_empty());
+    }
+}
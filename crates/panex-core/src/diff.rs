@@ -0,0 +1,334 @@
+//! Line-by-line differences between two text files.
+//!
+//! Myers' algorithm, the one `diff` and git use by default, so a change reads
+//! here the way it would anywhere else.
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Past this size a file is refused rather than read: the whole of both files
+/// is held in memory, and a diff that long is not one anybody scrolls through.
+const MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Past this many edits the search for the shortest script stops and the
+/// remainder is shown as removed-then-added. The search costs memory in the
+/// square of the edits, and two files this far apart have little in common
+/// worth lining up anyway.
+const MAX_EDITS: usize = 4000;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum LineChange {
+    Same,
+    Removed,
+    Added,
+}
+
+/// One line of a unified diff. Line numbers are 1-based, and a side is `None`
+/// where the line is not on it.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct DiffLine {
+    pub change: LineChange,
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    pub text: String,
+}
+
+/// Diff two files on disk. Binary files are refused: a line diff of them is
+/// noise, and `compare` already says whether they differ.
+pub fn diff_files(left: &str, right: &str) -> Result<Vec<DiffLine>, String> {
+    let a = read_text(left)?;
+    let b = read_text(right)?;
+    Ok(diff_lines(&a, &b))
+}
+
+fn read_text(path: &str) -> Result<String, String> {
+    let meta = fs::metadata(path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+    if meta.is_dir() {
+        return Err(format!("Not a file: {}", path));
+    }
+    if meta.len() > MAX_FILE_SIZE {
+        return Err(format!("Too large to diff: {}", path));
+    }
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    as_text(bytes, path)
+}
+
+/// `bytes` as text, unless they are too many or look binary.
+pub(crate) fn as_text(bytes: Vec<u8>, path: &str) -> Result<String, String> {
+    if bytes.len() as u64 > MAX_FILE_SIZE {
+        return Err(format!("Too large to diff: {}", path));
+    }
+    // The same test git uses: a NUL byte near the start means binary.
+    if bytes.iter().take(8000).any(|&b| b == 0) {
+        let name = Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+        return Err(format!("Not a text file: {}", name));
+    }
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Diff two texts line by line. Line endings are not part of a line, so a
+/// file saved with CRLF differs from its LF twin only where the text does.
+pub fn diff_lines(left: &str, right: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = left.lines().map(|l| l.trim_end_matches('\r')).collect();
+    let b: Vec<&str> = right.lines().map(|l| l.trim_end_matches('\r')).collect();
+
+    // The shared head and tail cost nothing to match and usually make up most
+    // of the file, so only the middle goes through the search.
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut ops: Vec<LineChange> = vec![LineChange::Same; prefix];
+    ops.extend(myers(mid_a, mid_b));
+    ops.extend(std::iter::repeat_n(LineChange::Same, suffix));
+
+    let (mut i, mut j) = (0, 0);
+    ops.into_iter()
+        .map(|change| {
+            let line = match change {
+                LineChange::Same => DiffLine {
+                    change,
+                    left: Some(i + 1),
+                    right: Some(j + 1),
+                    text: a[i].to_string(),
+                },
+                LineChange::Removed => DiffLine {
+                    change,
+                    left: Some(i + 1),
+                    right: None,
+                    text: a[i].to_string(),
+                },
+                LineChange::Added => DiffLine {
+                    change,
+                    left: None,
+                    right: Some(j + 1),
+                    text: b[j].to_string(),
+                },
+            };
+            if change != LineChange::Added {
+                i += 1;
+            }
+            if change != LineChange::Removed {
+                j += 1;
+            }
+            line
+        })
+        .collect()
+}
+
+/// The shortest edit script from `a` to `b`, one entry per line of output.
+fn myers(a: &[&str], b: &[&str]) -> Vec<LineChange> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let give_up = || {
+        let mut ops = vec![LineChange::Removed; a.len()];
+        ops.extend(std::iter::repeat_n(LineChange::Added, b.len()));
+        ops
+    };
+    if n == 0 || m == 0 {
+        return give_up();
+    }
+
+    // `v[k]` is the furthest x reached on diagonal k = x - y. Each round's `v`
+    // is kept, trimmed to the diagonals the next round reads, so the path can
+    // be walked back once the end is reached.
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    let mut end = None;
+    'search: for d in 0..=max.min(MAX_EDITS) as isize {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let at = |k: isize| (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n && y >= m {
+                end = Some(d);
+                break 'search;
+            }
+        }
+    }
+    let Some(edits) = end else {
+        return give_up();
+    };
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (0..=edits).rev() {
+        let v = &trace[d as usize];
+        let at = |k: isize| (k + d + 1) as usize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v[at(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            ops.push(LineChange::Same);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            ops.push(if x == prev_x {
+                LineChange::Added
+            } else {
+                LineChange::Removed
+            });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    ops.reverse();
+    ops
+}
+
+/// Pair the lines of a unified diff into side-by-side rows. A run of removed
+/// lines followed by a run of added ones is a change, and its lines face each
+/// other; whatever one run has over the other faces a blank.
+pub fn side_by_side(lines: &[DiffLine]) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut rows = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].change == LineChange::Same {
+            rows.push((Some(i), Some(i)));
+            i += 1;
+            continue;
+        }
+        let removed_start = i;
+        while i < lines.len() && lines[i].change == LineChange::Removed {
+            i += 1;
+        }
+        let added_start = i;
+        while i < lines.len() && lines[i].change == LineChange::Added {
+            i += 1;
+        }
+        let removed = added_start - removed_start;
+        let added = i - added_start;
+        for r in 0..removed.max(added) {
+            rows.push((
+                (r < removed).then_some(removed_start + r),
+                (r < added).then_some(added_start + r),
+            ));
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn script(lines: &[DiffLine]) -> String {
+        lines
+            .iter()
+            .map(|l| match l.change {
+                LineChange::Same => format!(" {}", l.text),
+                LineChange::Removed => format!("-{}", l.text),
+                LineChange::Added => format!("+{}", l.text),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn a_changed_line_is_removed_then_added() {
+        let lines = diff_lines("a\nb\nc\n", "a\nB\nc\n");
+        assert_eq!(script(&lines), " a\n-b\n+B\n c");
+        assert_eq!(lines[2].right, Some(2));
+        assert_eq!(lines[3].left, Some(3));
+    }
+
+    /// Lines that match but sit in the middle of changes still line up, which
+    /// the head-and-tail trim alone would not manage.
+    #[test]
+    fn matches_are_found_between_changes() {
+        let lines = diff_lines("x\nkeep\ny\n", "keep\nz\n");
+        assert_eq!(script(&lines), "-x\n keep\n-y\n+z");
+    }
+
+    /// Whatever the script, keeping its `Same` and `Removed` lines must give
+    /// back the left text and its `Same` and `Added` lines the right.
+    #[test]
+    fn both_sides_can_be_rebuilt_from_the_script() {
+        let mut seed = 7u32;
+        let mut text = |len: usize| {
+            (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    ((seed >> 16) % 4).to_string()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        for len in [0, 1, 5, 30, 200] {
+            let (left, right) = (text(len), text(len / 2 + 1));
+            let lines = diff_lines(&left, &right);
+            let side = |skip: LineChange| {
+                lines
+                    .iter()
+                    .filter(|l| l.change != skip)
+                    .map(|l| l.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            assert_eq!(side(LineChange::Added), left);
+            assert_eq!(side(LineChange::Removed), right);
+        }
+    }
+
+    #[test]
+    fn line_endings_do_not_count_as_a_difference() {
+        let lines = diff_lines("a\r\nb\r\n", "a\nb\n");
+        assert!(lines.iter().all(|l| l.change == LineChange::Same));
+    }
+
+    #[test]
+    fn side_by_side_faces_a_change_with_its_replacement() {
+        let lines = diff_lines("a\nb\nc\nd\n", "a\nB\nd\n");
+        let rows = side_by_side(&lines);
+        let text = |i: Option<usize>| i.map(|i| lines[i].text.as_str());
+        let shown: Vec<_> = rows.iter().map(|&(l, r)| (text(l), text(r))).collect();
+        assert_eq!(
+            shown,
+            vec![
+                (Some("a"), Some("a")),
+                (Some("b"), Some("B")),
+                (Some("c"), None),
+                (Some("d"), Some("d")),
+            ]
+        );
+    }
+
+    #[test]
+    fn binary_files_are_refused() {
+        let tmp = TempDir::new("diff");
+        let bin = tmp.0.join("blob.bin");
+        fs::write(&bin, [1u8, 0, 2]).unwrap();
+        let text = tmp.0.join("text.txt");
+        fs::write(&text, "hi").unwrap();
+        let err = diff_files(&bin.to_string_lossy(), &text.to_string_lossy()).unwrap_err();
+        assert!(err.contains("Not a text file"), "{}", err);
+    }
+}
@@ -0,0 +1,316 @@
+//! The folders visited most, and most lately: the database behind the TUI's
+//! `z` jump and the desktop's path dropdown.
+//!
+//! Ranked the way zoxide ranks, so that a database brought over from it
+//! keeps its order: each visit adds one, a score weighs that by how long ago
+//! the last visit was, and once the ranks add up past a ceiling they are all
+//! scaled down and the faintest forgotten. Kept in `~/.panex/frecency.json`.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// What the ranks may add up to before they are aged.
+const MAX_TOTAL: f64 = 10_000.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Dir {
+    pub path: String,
+    pub rank: f64,
+    /// Seconds since the epoch.
+    pub last: u64,
+}
+
+impl Dir {
+    pub fn score(&self, now: u64) -> f64 {
+        match now.saturating_sub(self.last) {
+            age if age < HOUR => self.rank * 4.0,
+            age if age < DAY => self.rank * 2.0,
+            age if age < WEEK => self.rank / 2.0,
+            _ => self.rank / 4.0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Frecency {
+    pub dirs: Vec<Dir>,
+}
+
+pub fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl Frecency {
+    pub fn path() -> Result<PathBuf, String> {
+        let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+        Ok(home.join(".panex").join("frecency.json"))
+    }
+
+    /// The saved database. A missing or unreadable one is empty: it fills
+    /// up again as folders are visited.
+    pub fn load() -> Self {
+        Self::path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let content = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize frecency: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write frecency: {}", e))
+    }
+
+    pub fn visit(&mut self, path: &str, now: u64) {
+        self.add(path, 1.0, now);
+        self.age();
+    }
+
+    fn add(&mut self, path: &str, rank: f64, last: u64) {
+        match self.dirs.iter_mut().find(|d| d.path == path) {
+            Some(dir) => {
+                dir.rank += rank;
+                dir.last = dir.last.max(last);
+            }
+            None => self.dirs.push(Dir {
+                path: path.to_string(),
+                rank,
+                last,
+            }),
+        }
+    }
+
+    fn age(&mut self) {
+        let total: f64 = self.dirs.iter().map(|d| d.rank).sum();
+        if total > MAX_TOTAL {
+            let factor = 0.9 * MAX_TOTAL / total;
+            for dir in &mut self.dirs {
+                dir.rank *= factor;
+            }
+            self.dirs.retain(|d| d.rank >= 1.0);
+        }
+    }
+
+    /// The folders matching `query`, best first, leaving out any that have
+    /// gone. The query is words, each found in the path after the one
+    /// before it, ignoring case, the last in the folder's own name —
+    /// `dev pan` finds `~/dev/panex` but not `~/panels/dev`. An empty query
+    /// matches everything.
+    pub fn query(&self, query: &str, now: u64) -> Vec<&Dir> {
+        let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut found: Vec<&Dir> = self
+            .dirs
+            .iter()
+            .filter(|d| matches(&words, &d.path) && Path::new(&d.path).is_dir())
+            .collect();
+        found.sort_by(|a, b| b.score(now).total_cmp(&a.score(now)));
+        found
+    }
+
+    /// Bring in another tool's database: zoxide's `db.zo`, autojump's
+    /// `autojump.txt`, or any `score path` lines, such as `zoxide query
+    /// --list --score` prints. Ranks add to those already here. Returns how
+    /// many folders were read.
+    pub fn import(&mut self, file: &str) -> Result<usize, String> {
+        let bytes = fs::read(file).map_err(|e| format!("Failed to read file: {}", e))?;
+        let dirs = if bytes.starts_with(&ZOXIDE_VERSION.to_le_bytes()) {
+            read_zoxide(&bytes).ok_or_else(|| format!("Not a zoxide database: {}", file))?
+        } else {
+            read_scored_lines(&String::from_utf8_lossy(&bytes))
+        };
+        let count = dirs.len();
+        for dir in dirs {
+            self.add(&dir.path, dir.rank, dir.last);
+        }
+        self.age();
+        Ok(count)
+    }
+}
+
+/// Where zoxide and autojump keep their databases, those that exist.
+pub fn import_sources() -> Vec<PathBuf> {
+    let mut candidates = Vec::new();
+    if let Some(dir) = std::env::var_os("_ZO_DATA_DIR") {
+        candidates.push(PathBuf::from(dir).join("db.zo"));
+    }
+    if let Some(data) = dirs::data_dir() {
+        candidates.push(data.join("zoxide").join("db.zo"));
+        candidates.push(data.join("autojump").join("autojump.txt"));
+    }
+    if let Some(home) = dirs::home_dir() {
+        // Where autojump keeps it on macOS.
+        candidates.push(home.join("Library").join("autojump").join("autojump.txt"));
+    }
+    candidates.into_iter().filter(|p| p.is_file()).collect()
+}
+
+/// As zoxide matches: the last word in the last part of the path, at its
+/// last place there, and each word before it somewhere further left.
+fn matches(words: &[String], path: &str) -> bool {
+    let Some((last, rest)) = words.split_last() else {
+        return true;
+    };
+    let path = path.to_lowercase();
+    let path = path.trim_end_matches('/');
+    let Some(i) = path.rfind(last.as_str()) else {
+        return false;
+    };
+    // A last word with a slash in it is aimed at the path, not the name.
+    if !last.contains('/') && path[i + last.len()..].contains('/') {
+        return false;
+    }
+    let mut before = &path[..i];
+    for word in rest.iter().rev() {
+        match before.rfind(word.as_str()) {
+            Some(i) => before = &before[..i],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// The version zoxide writes at the head of `db.zo`.
+const ZOXIDE_VERSION: u32 = 3;
+
+/// zoxide's `db.zo`: bincode with fixed-width little-endian integers — the
+/// version, then a count, then each folder as a length-prefixed path, an
+/// `f64` rank and a `u64` last-visit time.
+fn read_zoxide(bytes: &[u8]) -> Option<Vec<Dir>> {
+    let mut at: usize = 4;
+    let mut take = |n: usize| -> Option<&[u8]> {
+        let slice = bytes.get(at..at.checked_add(n)?)?;
+        at += n;
+        Some(slice)
+    };
+    let u64_at = |slice: &[u8]| u64::from_le_bytes(slice.try_into().unwrap_or([0; 8]));
+    let count = u64_at(take(8)?);
+    let mut dirs = Vec::new();
+    for _ in 0..count {
+        let len = usize::try_from(u64_at(take(8)?)).ok()?;
+        let path = String::from_utf8_lossy(take(len)?).into_owned();
+        let rank = f64::from_le_bytes(take(8)?.try_into().ok()?);
+        let last = u64_at(take(8)?);
+        dirs.push(Dir { path, rank, last });
+    }
+    Some(dirs)
+}
+
+/// `score path` lines, autojump's with a tab between. There are no times
+/// in them, so the folders come in as last visited long ago: their ranks
+/// were earned over however long the other tool was in use.
+fn read_scored_lines(text: &str) -> Vec<Dir> {
+    text.lines()
+        .filter_map(|line| {
+            let (rank, path) = line.trim_start().split_once(char::is_whitespace)?;
+            let rank: f64 = rank.parse().ok()?;
+            let path = path.trim();
+            (rank > 0.0 && path.starts_with('/')).then(|| Dir {
+                path: path.to_string(),
+                rank,
+                last: 0,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn dir(path: &str, rank: f64, last: u64) -> Dir {
+        Dir {
+            path: path.to_string(),
+            rank,
+            last,
+        }
+    }
+
+    #[test]
+    fn words_match_in_order_and_the_last_in_the_name() {
+        let words = |q: &str| -> Vec<String> { q.split_whitespace().map(str::to_lowercase).collect() };
+        assert!(matches(&words("dev pan"), "/home/me/dev/PanEx"));
+        assert!(!matches(&words("pan dev"), "/home/me/dev/panex"));
+        assert!(!matches(&words("dev"), "/home/me/dev/panex"), "dev is not in the name");
+        assert!(matches(&words("dev/"), "/home/me/dev/panex"));
+        assert!(matches(&words(""), "/anything"));
+        // The name's own "dev" counts, whatever the folders above hold.
+        assert!(matches(&words("dev"), "/home/me/dev/x/devtools"));
+        assert!(matches(&words("dev dev"), "/home/me/dev/x/devtools"));
+        assert!(!matches(&words("x dev dev"), "/home/me/dev/x/devtools"));
+    }
+
+    #[test]
+    fn recent_visits_outrank_old_ones_of_the_same_count() {
+        let tmp = TempDir::new("frecency");
+        let (a, b) = (tmp.0.join("frecency-a"), tmp.0.join("frecency-b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let (a, b) = (a.to_string_lossy().to_string(), b.to_string_lossy().to_string());
+        let now = 10 * WEEK;
+        let mut db = Frecency::default();
+        for _ in 0..3 {
+            db.visit(&a, now - 2 * WEEK);
+            db.visit(&b, now - 10);
+        }
+        db.visit(&a, now - 2 * WEEK);
+        db.dirs.push(dir("/no/such/panex/frecency", 100.0, now));
+
+        let found: Vec<&str> = db.query("frecency", now).iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(found, [b.as_str(), a.as_str()]);
+        assert_eq!(db.dirs[0].rank, 4.0);
+    }
+
+    #[test]
+    fn ranks_past_the_ceiling_are_scaled_down_and_the_faintest_dropped() {
+        let mut db = Frecency {
+            dirs: vec![dir("/big", MAX_TOTAL, 0), dir("/faint", 1.0, 0)],
+        };
+        db.visit("/new", 0);
+        assert!(db.dirs.iter().map(|d| d.rank).sum::<f64>() <= MAX_TOTAL);
+        assert!(db.dirs.iter().all(|d| d.path != "/faint"));
+    }
+
+    #[test]
+    fn reads_zoxide_and_autojump_databases() {
+        let mut zo = ZOXIDE_VERSION.to_le_bytes().to_vec();
+        zo.extend(2u64.to_le_bytes());
+        for (path, rank, last) in [("/a", 2.5f64, 100u64), ("/b", 1.0, 200)] {
+            zo.extend((path.len() as u64).to_le_bytes());
+            zo.extend(path.as_bytes());
+            zo.extend(rank.to_le_bytes());
+            zo.extend(last.to_le_bytes());
+        }
+        assert_eq!(read_zoxide(&zo).unwrap(), [dir("/a", 2.5, 100), dir("/b", 1.0, 200)]);
+        assert_eq!(read_zoxide(&zo[..zo.len() - 1]), None, "cut short");
+
+        let text = "10.0\t/home/me/dev\n  3 /tmp/with space\nnot a line\n";
+        assert_eq!(
+            read_scored_lines(text),
+            [dir("/home/me/dev", 10.0, 0), dir("/tmp/with space", 3.0, 0)]
+        );
+
+        let tmp = TempDir::new("zo");
+        let file = tmp.0.join("db.zo");
+        fs::write(&file, &zo).unwrap();
+        let mut db = Frecency {
+            dirs: vec![dir("/a", 1.0, 50)],
+        };
+        assert_eq!(db.import(&file.to_string_lossy()), Ok(2));
+        assert_eq!(db.dirs[0], dir("/a", 3.5, 100));
+    }
+}
@@ -0,0 +1,630 @@
+//! Git, as far as a file list needs it: which repository a folder is in,
+//! which branch it is on, and what state each path in it is in — and the
+//! handful of things done to a selection without leaving for a terminal:
+//! staging, unstaging, discarding, and reading a file's diff and history.
+//!
+//! Read from the `git` program rather than a library, so that the answer is
+//! the one `git status` gives — the same ignore rules, the same config —
+//! and so that nothing here has to keep up with the repository format.
+
+use crate::diff::{self, DiffLine};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A path's state, in rising order of how much it wants attention: a folder
+/// shows the highest of what is below it.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum GitStatus {
+    Ignored,
+    Untracked,
+    /// Changed in the index only: committing would take it as it is.
+    Staged,
+    /// Changed in the work tree, whether or not some of it is staged too.
+    Modified,
+    /// Left mid-merge with conflicts.
+    Conflicted,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoStatus {
+    /// The work tree's top, written the way the folder asked about was —
+    /// through the same symlinks — so that entries' paths can be looked up
+    /// as they are.
+    pub root: String,
+    /// `None` when the HEAD is detached.
+    pub branch: Option<String>,
+    /// The commit checked out, abbreviated. `None` before the first commit.
+    pub head: Option<String>,
+    pub upstream: Option<String>,
+    pub ahead: u32,
+    pub behind: u32,
+    /// Paths with a status, folders holding one included.
+    entries: HashMap<String, GitStatus>,
+    /// Untracked and ignored folders, reported whole: what is inside them
+    /// is not listed, and shares their status.
+    whole: HashMap<String, GitStatus>,
+}
+
+impl RepoStatus {
+    /// Whether `path` is in this repository's work tree.
+    pub fn contains(&self, path: &str) -> bool {
+        Path::new(path).starts_with(&self.root)
+    }
+
+    /// `path`'s status. `None` is clean, or not in this repository.
+    pub fn status_of(&self, path: &str) -> Option<GitStatus> {
+        if let Some(status) = self.entries.get(path) {
+            return Some(*status);
+        }
+        let mut at = Path::new(path).parent();
+        while let Some(dir) = at {
+            if !dir.starts_with(&self.root) {
+                break;
+            }
+            if let Some(status) = self.whole.get(dir.to_string_lossy().as_ref()) {
+                return Some(*status);
+            }
+            at = dir.parent();
+        }
+        None
+    }
+
+    /// The branch, or the commit when there is none, then how far it is
+    /// from its upstream: `main ↑2 ↓1`.
+    pub fn describe(&self) -> String {
+        let mut text = match (&self.branch, &self.head) {
+            (Some(branch), _) => branch.clone(),
+            (None, Some(head)) => format!("({})", head),
+            (None, None) => "(no commits)".to_string(),
+        };
+        if self.ahead > 0 {
+            text.push_str(&format!(" ↑{}", self.ahead));
+        }
+        if self.behind > 0 {
+            text.push_str(&format!(" ↓{}", self.behind));
+        }
+        text
+    }
+}
+
+/// Runs `git` in `dir`, without taking the index lock a status refresh would
+/// otherwise take — this runs in the background, beside whatever git the
+/// user is running themselves.
+fn git(dir: &str, args: &[&str]) -> Result<std::process::Output, String> {
+    Command::new("git")
+        .arg("--no-optional-locks")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))
+}
+
+/// The status of the repository `dir` is in, or `None` when it is not in
+/// one. An error is git itself failing to run.
+pub fn status(dir: &str) -> Result<Option<RepoStatus>, String> {
+    // The prefix is `dir`'s place in the work tree, e.g. `src/`. The root
+    // is worked out from it rather than taken from `--show-toplevel`, which
+    // resolves symlinks and so would not match the paths being shown.
+    let output = git(dir, &["rev-parse", "--is-inside-work-tree", "--show-prefix"])?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    if lines.next() != Some("true") {
+        return Ok(None);
+    }
+    let prefix = lines.next().unwrap_or("");
+    let mut root = PathBuf::from(dir);
+    for _ in Path::new(prefix).components() {
+        root.pop();
+    }
+
+    let output = git(
+        dir,
+        &["status", "--porcelain=v2", "--branch", "-z", "--ignored=matching"],
+    )?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to read git status: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(Some(parse_status(
+        &root.to_string_lossy(),
+        &String::from_utf8_lossy(&output.stdout),
+    )))
+}
+
+/// Reads `git status --porcelain=v2 --branch -z` for the work tree at `root`.
+fn parse_status(root: &str, text: &str) -> RepoStatus {
+    let mut repo = RepoStatus {
+        root: root.to_string(),
+        ..RepoStatus::default()
+    };
+    let mut records = text.split('\0');
+    while let Some(record) = records.next() {
+        let (kind, rest) = (record.get(..2).unwrap_or(""), record.get(2..).unwrap_or(""));
+        let (status, path) = match kind {
+            "# " => {
+                read_header(&mut repo, rest);
+                continue;
+            }
+            "? " => (GitStatus::Untracked, rest),
+            "! " => (GitStatus::Ignored, rest),
+            "1 " | "2 " | "u " => {
+                // `1 XY sub mH mI mW hH hI path`, with one more field before
+                // the path for a rename (`2`) and three for a conflict (`u`).
+                let fields = match kind {
+                    "1 " => 8,
+                    "2 " => 9,
+                    _ => 10,
+                };
+                let mut parts = record.splitn(fields + 1, ' ');
+                let xy = parts.nth(1).unwrap_or("..");
+                let Some(path) = parts.nth(fields - 2) else {
+                    continue;
+                };
+                if kind == "2 " {
+                    // A rename's old path comes as a record of its own.
+                    records.next();
+                }
+                let status = if kind == "u " {
+                    GitStatus::Conflicted
+                } else if xy.as_bytes().get(1).is_some_and(|&y| y != b'.') {
+                    GitStatus::Modified
+                } else {
+                    GitStatus::Staged
+                };
+                (status, path)
+            }
+            _ => continue,
+        };
+        repo.add(path, status);
+    }
+    repo
+}
+
+fn read_header(repo: &mut RepoStatus, line: &str) {
+    let Some((key, value)) = line.split_once(' ') else {
+        return;
+    };
+    match key {
+        "branch.oid" if value != "(initial)" => {
+            repo.head = Some(value.chars().take(7).collect());
+        }
+        "branch.head" if value != "(detached)" => repo.branch = Some(value.to_string()),
+        "branch.upstream" => repo.upstream = Some(value.to_string()),
+        "branch.ab" => {
+            for part in value.split(' ') {
+                if let Some(n) = part.strip_prefix('+') {
+                    repo.ahead = n.parse().unwrap_or(0);
+                } else if let Some(n) = part.strip_prefix('-') {
+                    repo.behind = n.parse().unwrap_or(0);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+impl RepoStatus {
+    /// Record `relative`'s status on it and on each folder above it. Being
+    /// ignored does not pass up: a folder is not ignored for holding
+    /// something that is.
+    fn add(&mut self, relative: &str, status: GitStatus) {
+        let is_dir = relative.ends_with('/');
+        let path = Path::new(&self.root).join(relative.trim_end_matches('/'));
+        if is_dir && matches!(status, GitStatus::Untracked | GitStatus::Ignored) {
+            self.whole.insert(path.to_string_lossy().to_string(), status);
+        }
+        let mut at = Some(path.as_path());
+        while let Some(p) = at {
+            if p == Path::new(&self.root) {
+                break;
+            }
+            let entry = self
+                .entries
+                .entry(p.to_string_lossy().to_string())
+                .or_insert(status);
+            *entry = (*entry).max(status);
+            if status == GitStatus::Ignored {
+                break;
+            }
+            at = p.parent();
+        }
+    }
+}
+
+/// One commit in a path's history.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Commit {
+    /// Abbreviated.
+    pub id: String,
+    pub author: String,
+    /// Seconds since the epoch.
+    pub time: i64,
+    pub subject: String,
+}
+
+/// Runs `git` for one of the actions below: its output, or what it said
+/// on failing. Pathspecs are taken literally — these are file names, and a
+/// file called `*.rs` should not stage every source file.
+fn run(dir: &str, args: &[&str]) -> Result<String, String> {
+    let output = git(dir, &[&["--literal-pathspecs"], args].concat())?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to run git {}: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `paths` by the folder they are in, as names in it, folders in the order
+/// they first come. Each group is run from its own folder, so that a
+/// selection need not share a repository.
+fn by_folder(paths: &[String]) -> Vec<(String, Vec<String>)> {
+    let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            continue;
+        };
+        let (dir, name) = (dir.to_string_lossy().to_string(), name.to_string_lossy().to_string());
+        match groups.iter_mut().find(|(d, _)| *d == dir) {
+            Some((_, names)) => names.push(name),
+            None => groups.push((dir, vec![name])),
+        }
+    }
+    groups
+}
+
+/// Which of `names`, in `dir`, are in the commit checked out. None are
+/// before the first commit.
+fn in_head(dir: &str, names: &[String]) -> HashSet<String> {
+    let mut args = vec!["ls-tree", "-z", "--name-only", "HEAD", "--"];
+    args.extend(names.iter().map(String::as_str));
+    match run(dir, &args) {
+        Ok(text) => text.split('\0').filter(|n| !n.is_empty()).map(str::to_string).collect(),
+        Err(_) => HashSet::new(),
+    }
+}
+
+/// The files at or under `names`, in `dir`, that differ from the commit
+/// checked out, staged or not, as paths: what restoring them would lose.
+fn changed_files(dir: &str, names: &[String]) -> Result<Vec<String>, String> {
+    let mut args = vec!["diff", "--name-only", "-z", "--no-renames", "--relative", "HEAD", "--"];
+    args.extend(names.iter().map(String::as_str));
+    Ok(run(dir, &args)?
+        .split('\0')
+        .filter(|n| !n.is_empty())
+        .map(|n| Path::new(dir).join(n).to_string_lossy().to_string())
+        .collect())
+}
+
+/// `git add` each path, folders whole.
+pub fn stage(paths: &[String]) -> Result<(), String> {
+    for (dir, names) in by_folder(paths) {
+        let mut args = vec!["add", "--"];
+        args.extend(names.iter().map(String::as_str));
+        run(&dir, &args)?;
+    }
+    Ok(())
+}
+
+/// Take each path's changes out of the index, leaving the work tree as it
+/// is. `reset` rather than `restore --staged`, which has nothing to restore
+/// from before the first commit.
+pub fn unstage(paths: &[String]) -> Result<(), String> {
+    for (dir, names) in by_folder(paths) {
+        let mut args = vec!["reset", "-q", "--"];
+        args.extend(names.iter().map(String::as_str));
+        run(&dir, &args)?;
+    }
+    Ok(())
+}
+
+/// Put each path back the way the commit checked out has it, staged changes
+/// and all. A path the commit does not have is taken out of the index and
+/// deleted. Unless `permanent`, every file the restore would change or take
+/// away goes to the trash first, so that a discard can be undone — within a
+/// folder too, including files staged since that the commit does not have.
+/// Anything untracked inside a folder is left alone.
+pub fn discard(paths: &[String], permanent: bool) -> Result<(), String> {
+    for (dir, names) in by_folder(paths) {
+        let known = in_head(&dir, &names);
+        let (tracked, new): (Vec<String>, Vec<String>) =
+            names.into_iter().partition(|name| known.contains(name));
+        for name in &new {
+            run(&dir, &["rm", "-r", "-q", "--cached", "--ignore-unmatch", "--", name])?;
+            let path = Path::new(&dir).join(name);
+            if path.symlink_metadata().is_ok() {
+                crate::delete_entry(&path.to_string_lossy(), permanent)?;
+            }
+        }
+        if tracked.is_empty() {
+            continue;
+        }
+        if !permanent {
+            for path in changed_files(&dir, &tracked)? {
+                if Path::new(&path).is_file() {
+                    crate::delete_entry(&path, false)?;
+                }
+            }
+        }
+        let mut args = vec!["restore", "--source=HEAD", "--staged", "--worktree", "--"];
+        args.extend(tracked.iter().map(String::as_str));
+        run(&dir, &args)?;
+    }
+    Ok(())
+}
+
+/// A file as it is against the commit checked out — what is staged and
+/// what is not, together — the whole file, not just the hunks, so that it
+/// reads in the same viewer as any other diff. A file new since, or gone
+/// since, is diffed against nothing.
+pub fn diff(path: &str) -> Result<Vec<DiffLine>, String> {
+    let file = Path::new(path);
+    let (Some(dir), Some(name)) = (file.parent(), file.file_name()) else {
+        return Err(format!("Not a file: {}", path));
+    };
+    if file.is_dir() {
+        return Err(format!("Not a file: {}", path));
+    }
+    let (dir, name) = (dir.to_string_lossy(), name.to_string_lossy().to_string());
+    let before = if in_head(&dir, std::slice::from_ref(&name)).is_empty() {
+        String::new()
+    } else {
+        let spec = format!("HEAD:./{}", name);
+        let output = git(&dir, &["show", &spec])?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to read {}: {}",
+                spec,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        diff::as_text(output.stdout, path)?
+    };
+    let after = match std::fs::read(file) {
+        Ok(bytes) => diff::as_text(bytes, path)?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Failed to read file: {}", e)),
+    };
+    Ok(diff::diff_lines(&before, &after))
+}
+
+/// The last `limit` commits that touched `path`, newest first. A file's
+/// history follows it back through renames; a folder's is everything below
+/// it.
+pub fn log(path: &str, limit: usize) -> Result<Vec<Commit>, String> {
+    let limit = format!("-n{}", limit);
+    let mut args = vec!["log", "-z", "--format=%h%x1f%an%x1f%at%x1f%s", &limit];
+    let file = Path::new(path);
+    let (dir, spec) = if file.is_dir() {
+        (path.to_string(), ".".to_string())
+    } else {
+        args.push("--follow");
+        match (file.parent(), file.file_name()) {
+            (Some(dir), Some(name)) => (
+                dir.to_string_lossy().to_string(),
+                name.to_string_lossy().to_string(),
+            ),
+            _ => return Err(format!("Not a file: {}", path)),
+        }
+    };
+    args.extend(["--", &spec]);
+    let text = run(&dir, &args)?;
+    Ok(text
+        .split('\0')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(4, '\x1f');
+            Some(Commit {
+                id: fields.next().filter(|id| !id.is_empty())?.to_string(),
+                author: fields.next()?.to_string(),
+                time: fields.next()?.parse().unwrap_or(0),
+                subject: fields.next().unwrap_or("").to_string(),
+            })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A repository in a temp dir, removed on drop.
+    struct Repo(PathBuf);
+
+    impl Repo {
+        fn new(tag: &str) -> Self {
+            let root = std::env::temp_dir().join(format!("panex-git-{}-{}", tag, std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            let repo = Repo(root);
+            repo.git(&["init", "-q", "-b", "main"]);
+            repo.git(&["config", "user.email", "panex@example.com"]);
+            repo.git(&["config", "user.name", "PanEx"]);
+            repo.git(&["config", "commit.gpgsign", "false"]);
+            repo
+        }
+
+        fn git(&self, args: &[&str]) {
+            let output = git(&self.path(""), args).unwrap();
+            assert!(output.status.success(), "git {:?}: {:?}", args, output);
+        }
+
+        fn write(&self, name: &str, text: &str) {
+            let path = self.0.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+
+        fn path(&self, name: &str) -> String {
+            let path = if name.is_empty() { self.0.clone() } else { self.0.join(name) };
+            path.to_string_lossy().to_string()
+        }
+    }
+
+    impl Drop for Repo {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn statuses_roll_up_to_the_folders_holding_them() {
+        let repo = Repo::new("status");
+        repo.write(".gitignore", "target/\n*.log\n");
+        repo.write("src/lib.rs", "one");
+        repo.write("src/main.rs", "one");
+        repo.write("docs/guide.md", "one");
+        repo.git(&["add", "."]);
+        repo.git(&["commit", "-q", "-m", "first"]);
+
+        repo.write("src/lib.rs", "two");
+        repo.write("docs/guide.md", "two");
+        repo.git(&["add", "docs/guide.md"]);
+        repo.write("new/a.txt", "new");
+        repo.write("target/debug/out", "built");
+        repo.write("docs/run.log", "log");
+
+        let status = status(&repo.path("src")).unwrap().unwrap();
+        assert_eq!(status.root, repo.path(""));
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        let of = |name: &str| status.status_of(&repo.path(name));
+        assert_eq!(of("src/lib.rs"), Some(GitStatus::Modified));
+        assert_eq!(of("src/main.rs"), None);
+        assert_eq!(of("src"), Some(GitStatus::Modified));
+        assert_eq!(of("docs/guide.md"), Some(GitStatus::Staged));
+        assert_eq!(of("docs"), Some(GitStatus::Staged), "an ignored log does not pass up");
+        assert_eq!(of("docs/run.log"), Some(GitStatus::Ignored));
+        assert_eq!(of("new"), Some(GitStatus::Untracked));
+        assert_eq!(of("new/a.txt"), Some(GitStatus::Untracked));
+        assert_eq!(of("target"), Some(GitStatus::Ignored));
+        assert_eq!(of("target/debug/out"), Some(GitStatus::Ignored));
+        assert_eq!(of(".gitignore"), None);
+    }
+
+    #[test]
+    fn a_folder_outside_any_repository_has_none() {
+        let dir = std::env::temp_dir().join(format!("panex-nogit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // A temp dir could itself sit in a checkout; only then is this moot.
+        let found = status(&dir.to_string_lossy()).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        if let Some(repo) = found {
+            assert!(dir.starts_with(&repo.root));
+        }
+    }
+
+    #[test]
+    fn reads_the_branch_and_how_far_it_is_from_upstream() {
+        let text = "# branch.oid 0123456789abcdef\0# branch.head feature\0\
+                    # branch.upstream origin/feature\0# branch.ab +2 -1\0\
+                    2 R. N... 100644 100644 100644 abc abc R100 new.rs\0old.rs\0\
+                    u UU N... 100644 100644 100644 100644 a b c both.rs\0";
+        let repo = parse_status("/r", text);
+        assert_eq!(repo.describe(), "feature ↑2 ↓1");
+        assert_eq!(repo.head.as_deref(), Some("0123456"));
+        assert_eq!(repo.upstream.as_deref(), Some("origin/feature"));
+        assert_eq!(repo.status_of("/r/new.rs"), Some(GitStatus::Staged));
+        assert_eq!(repo.status_of("/r/old.rs"), None);
+        assert_eq!(repo.status_of("/r/both.rs"), Some(GitStatus::Conflicted));
+
+        let detached = parse_status("/r", "# branch.oid 0123456789\0# branch.head (detached)\0");
+        assert_eq!(detached.describe(), "(0123456)");
+    }
+
+    #[test]
+    fn staging_unstaging_and_discarding_move_a_file_between_states() {
+        let repo = Repo::new("actions");
+        repo.write("a.txt", "one\n");
+        repo.write("dir/b.txt", "one\n");
+        repo.git(&["add", "."]);
+        repo.git(&["commit", "-q", "-m", "first"]);
+        let of = |name: &str| status(&repo.path("")).unwrap().unwrap().status_of(&repo.path(name));
+
+        repo.write("a.txt", "two\n");
+        repo.write("new.txt", "new\n");
+        stage(&[repo.path("a.txt"), repo.path("new.txt")]).unwrap();
+        assert_eq!(of("a.txt"), Some(GitStatus::Staged));
+        assert_eq!(of("new.txt"), Some(GitStatus::Staged));
+
+        unstage(&[repo.path("a.txt")]).unwrap();
+        assert_eq!(of("a.txt"), Some(GitStatus::Modified));
+
+        repo.write("dir/b.txt", "two\n");
+        discard(&[repo.path("a.txt"), repo.path("new.txt"), repo.path("dir")], true).unwrap();
+        assert_eq!(fs::read_to_string(repo.path("a.txt")).unwrap(), "one\n");
+        assert_eq!(fs::read_to_string(repo.path("dir/b.txt")).unwrap(), "one\n");
+        assert!(!Path::new(&repo.path("new.txt")).exists(), "a file HEAD lacks is deleted");
+        assert_eq!(of(""), None);
+    }
+
+    /// What a discard short of permanent trashes before restoring.
+    #[test]
+    fn discarding_a_folder_finds_its_changed_and_staged_new_files_first() {
+        let repo = Repo::new("discard-dir");
+        repo.write("dir/same.txt", "one\n");
+        repo.write("dir/changed.txt", "one\n");
+        repo.git(&["add", "."]);
+        repo.git(&["commit", "-q", "-m", "first"]);
+
+        repo.write("dir/changed.txt", "two\n");
+        repo.write("dir/added.txt", "new\n");
+        repo.git(&["add", "dir/added.txt"]);
+        repo.write("dir/untracked.txt", "mine\n");
+        let mut changed = changed_files(&repo.path(""), &["dir".to_string()]).unwrap();
+        changed.sort();
+        assert_eq!(changed, [repo.path("dir/added.txt"), repo.path("dir/changed.txt")]);
+
+        discard(&[repo.path("dir")], true).unwrap();
+        assert!(!Path::new(&repo.path("dir/added.txt")).exists());
+        assert!(Path::new(&repo.path("dir/untracked.txt")).exists());
+    }
+
+    #[test]
+    fn a_file_diffs_against_head_and_its_log_follows_renames() {
+        let repo = Repo::new("history");
+        repo.write("old.txt", "one\ntwo\n");
+        repo.git(&["add", "."]);
+        repo.git(&["commit", "-q", "-m", "add it"]);
+        repo.git(&["mv", "old.txt", "new.txt"]);
+        repo.git(&["commit", "-q", "-m", "rename it"]);
+        repo.write("new.txt", "one\nthree\n");
+
+        let changes: Vec<_> = diff(&repo.path("new.txt"))
+            .unwrap()
+            .into_iter()
+            .map(|line| (line.change, line.text))
+            .collect();
+        use crate::diff::LineChange::*;
+        assert_eq!(
+            changes,
+            [(Same, "one".into()), (Removed, "two".into()), (Added, "three".into())]
+        );
+
+        repo.write("fresh.txt", "hi\n");
+        let fresh = diff(&repo.path("fresh.txt")).unwrap();
+        assert!(fresh.iter().all(|line| line.change == Added));
+
+        let subjects: Vec<String> = log(&repo.path("new.txt"), 10)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.subject)
+            .collect();
+        assert_eq!(subjects, ["rename it", "add it"]);
+        let commits = log(&repo.path(""), 1).unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].author, "PanEx");
+        assert!(commits[0].time > 0);
+    }
+}
@@ -1,6 +1,14 @@
+pub mod apps;
+pub mod compare;
 pub mod config;
+pub mod diff;
+pub mod frecency;
+pub mod git;
+pub mod rename;
+pub mod usage;
 
 use serde::Serialize;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
@@ -12,6 +20,73 @@ pub struct FileEntry {
     pub is_dir: bool,
     pub size: u64,
     pub modified: u64,
+    pub kind: FileKind,
+    /// When the entry was made, where the filesystem keeps that; 0 otherwise.
+    pub created: u64,
+    /// `st_mode`: the type and permission bits. 0 where there are none.
+    pub mode: u32,
+    /// The owner's user id. 0 where there is none.
+    pub uid: u32,
+    /// How many entries a folder holds, once `count_items` has looked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<u64>,
+    /// What a folder's contents take on disk, once measured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents_size: Option<u64>,
+    /// Its git status, once looked up. Clean, and outside any repository,
+    /// are both `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<git::GitStatus>,
+}
+
+/// What an entry is on disk, beyond folder or not — enough to colour it the
+/// way `ls` would.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    File,
+    Dir,
+    Symlink,
+    /// A symlink whose target is gone.
+    BrokenSymlink,
+    Executable,
+    Socket,
+    Fifo,
+    Device,
+}
+
+impl FileKind {
+    /// The kind of `path`, given its metadata read without following links.
+    pub fn of(path: &Path, metadata: &fs::Metadata) -> Self {
+        let file_type = metadata.file_type();
+        if file_type.is_symlink() {
+            return if fs::metadata(path).is_ok() {
+                FileKind::Symlink
+            } else {
+                FileKind::BrokenSymlink
+            };
+        }
+        if file_type.is_dir() {
+            return FileKind::Dir;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+            if file_type.is_socket() {
+                return FileKind::Socket;
+            }
+            if file_type.is_fifo() {
+                return FileKind::Fifo;
+            }
+            if file_type.is_block_device() || file_type.is_char_device() {
+                return FileKind::Device;
+            }
+            if metadata.permissions().mode() & 0o111 != 0 {
+                return FileKind::Executable;
+            }
+        }
+        FileKind::File
+    }
 }
 
 pub fn get_home_dir() -> Result<String, String> {
@@ -36,19 +111,27 @@ pub fn read_directory(path: &str) -> Result<Vec<FileEntry>, String> {
             .metadata()
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
 
-        let modified = metadata
-            .modified()
-            .ok()
-            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
+        let secs = |time: std::io::Result<std::time::SystemTime>| {
+            time.ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0)
+        };
+        let (mode, uid) = mode_and_owner(&metadata);
 
         entries.push(FileEntry {
             name: entry.file_name().to_string_lossy().to_string(),
             path: entry.path().to_string_lossy().to_string(),
             is_dir: metadata.is_dir(),
             size: disk_size(&metadata),
-            modified,
+            modified: secs(metadata.modified()),
+            kind: FileKind::of(&entry.path(), &metadata),
+            created: secs(metadata.created()),
+            mode,
+            uid,
+            items: None,
+            contents_size: None,
+            git: None,
         });
     }
 
@@ -59,6 +142,26 @@ pub fn read_directory(path: &str) -> Result<Vec<FileEntry>, String> {
     Ok(entries)
 }
 
+/// Count what each folder in `entries` holds, where that is not known yet. A
+/// `read_dir` per folder, so it is left to callers that show the count; one
+/// that cannot be read keeps `None`.
+pub fn count_items(entries: &mut [FileEntry]) {
+    for entry in entries.iter_mut().filter(|e| e.is_dir && e.items.is_none()) {
+        entry.items = fs::read_dir(&entry.path).ok().map(|dir| dir.count() as u64);
+    }
+}
+
+#[cfg(unix)]
+fn mode_and_owner(meta: &fs::Metadata) -> (u32, u32) {
+    use std::os::unix::fs::MetadataExt;
+    (meta.mode(), meta.uid())
+}
+
+#[cfg(not(unix))]
+fn mode_and_owner(_meta: &fs::Metadata) -> (u32, u32) {
+    (0, 0)
+}
+
 pub fn rename_entry(path: &str, new_name: &str) -> Result<(), String> {
     let source = PathBuf::from(path);
     if !source.exists() {
@@ -214,29 +317,128 @@ fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<(), String> {
     Ok(())
 }
 
-pub fn calculate_directory_size(path: &str) -> Result<u64, String> {
+/// How a size walk treats what it meets. The default stays on the
+/// filesystem it starts on and counts a symlink as the link, not its target.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SizeOptions {
+    pub follow_symlinks: bool,
+    pub cross_filesystems: bool,
+}
+
+/// What a size walk found below a folder. Not counting the folder itself.
+#[derive(Debug, Serialize, Clone, Copy, Default, PartialEq)]
+pub struct DirSize {
+    /// The files' lengths, added up — what copying them elsewhere would take.
+    pub apparent: u64,
+    /// The blocks they take where they are: less for sparse files, more for
+    /// many small ones.
+    pub on_disk: u64,
+    pub files: u64,
+    pub dirs: u64,
+    /// Folders and entries that could not be read, and so are not counted.
+    pub errors: u64,
+}
+
+pub fn calculate_directory_size(path: &str) -> Result<DirSize, String> {
+    directory_size(path, SizeOptions::default())
+}
+
+/// Adds up everything below `path`. A file with several hard links is
+/// counted once, however many of them the walk meets — package caches are
+/// full of them, and each link would otherwise count the whole file again.
+pub fn directory_size(path: &str, options: SizeOptions) -> Result<DirSize, String> {
     let dir_path = Path::new(path);
-    if !dir_path.is_dir() {
+    let root = fs::metadata(dir_path).map_err(|e| format!("Failed to read metadata: {}", e))?;
+    if !root.is_dir() {
         return Err(format!("Not a directory: {}", path));
     }
 
-    fn walk(dir: &Path) -> u64 {
-        let mut total: u64 = 0;
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                if let Ok(meta) = entry.metadata() {
-                    if meta.is_dir() {
-                        total += walk(&entry.path());
-                    } else {
-                        total += disk_size(&meta);
-                    }
+    struct Walk {
+        options: SizeOptions,
+        device: u64,
+        /// Folders already entered, when links are followed and could lead
+        /// back into one.
+        seen: HashSet<(u64, u64)>,
+        /// Files with more than one link, once one of them is counted.
+        linked: HashSet<(u64, u64)>,
+        size: DirSize,
+    }
+
+    fn walk(dir: &Path, state: &mut Walk) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            state.size.errors += 1;
+            return;
+        };
+        for entry in entries {
+            let Ok(entry) = entry else {
+                state.size.errors += 1;
+                continue;
+            };
+            let meta = if state.options.follow_symlinks {
+                // A dangling link still takes its own space.
+                fs::metadata(entry.path()).or_else(|_| entry.metadata())
+            } else {
+                entry.metadata()
+            };
+            let Ok(meta) = meta else {
+                state.size.errors += 1;
+                continue;
+            };
+            if meta.is_dir() {
+                let (device, inode) = file_id(&meta);
+                if !state.options.cross_filesystems && device != state.device {
+                    continue;
+                }
+                if state.options.follow_symlinks && !state.seen.insert((device, inode)) {
+                    continue;
                 }
+                state.size.dirs += 1;
+                walk(&entry.path(), state);
+            } else {
+                if link_count(&meta) > 1 && !state.linked.insert(file_id(&meta)) {
+                    continue;
+                }
+                state.size.files += 1;
+                state.size.apparent += meta.len();
+                state.size.on_disk += disk_size(&meta);
             }
         }
-        total
     }
 
-    Ok(walk(dir_path))
+    let mut state = Walk {
+        options,
+        device: file_id(&root).0,
+        seen: HashSet::from([file_id(&root)]),
+        linked: HashSet::new(),
+        size: DirSize::default(),
+    };
+    walk(dir_path, &mut state);
+    Ok(state.size)
+}
+
+/// The device and inode of a file, or zeros where there are none — which
+/// makes every file look to be on the one filesystem.
+#[cfg(unix)]
+fn file_id(meta: &fs::Metadata) -> (u64, u64) {
+    use std::os::unix::fs::MetadataExt;
+    (meta.dev(), meta.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &fs::Metadata) -> (u64, u64) {
+    (0, 0)
+}
+
+/// How many names a file has. 1 where the filesystem does not say.
+#[cfg(unix)]
+fn link_count(meta: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.nlink()
+}
+
+#[cfg(not(unix))]
+fn link_count(_meta: &fs::Metadata) -> u64 {
+    1
 }
 
 /// Returns actual disk usage (blocks * 512) on Unix, logical size on Windows.
@@ -252,23 +454,220 @@ fn disk_size(meta: &fs::Metadata) -> u64 {
 }
 
 pub fn create_file(dir: &str, name: &str) -> Result<(), String> {
-    let path = Path::new(dir).join(name);
+    let path = nested_path(dir, name)?;
     if path.exists() {
         return Err(format!("A file named '{}' already exists", name));
     }
+    create_parents(&path)?;
     fs::File::create(&path).map_err(|e| format!("Failed to create file: {}", e))?;
     Ok(())
 }
 
 pub fn create_folder(dir: &str, name: &str) -> Result<(), String> {
-    let path = Path::new(dir).join(name);
+    let path = nested_path(dir, name)?;
     if path.exists() {
         return Err(format!("A folder named '{}' already exists", name));
     }
+    create_parents(&path)?;
     fs::create_dir(&path).map_err(|e| format!("Failed to create folder: {}", e))?;
     Ok(())
 }
 
+/// `dir` joined with a name that may reach into subfolders (`a/b/c.txt`) but
+/// never out of `dir` — an absolute path or a `..` is refused, as is a name
+/// with nothing in it.
+fn nested_path(dir: &str, name: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(name.trim_end_matches('/'));
+    let mut components = relative.components().peekable();
+    if components.peek().is_none() {
+        return Err("Name is empty".to_string());
+    }
+    if components.any(|c| !matches!(c, std::path::Component::Normal(_))) {
+        return Err(format!("Not a name inside this folder: {}", name));
+    }
+    Ok(Path::new(dir).join(relative))
+}
+
+fn create_parents(path: &Path) -> Result<(), String> {
+    match path.parent() {
+        Some(parent) => fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create folder: {}", e)),
+        None => Ok(()),
+    }
+}
+
+/// Create everything `spec` names under `dir`: comma-separated names, each of
+/// which may be nested (`a/b/c.txt`) and use braces (`src/{lib,main}.rs`).
+/// A name ending in `/` is a folder whatever `folders` says, so one prompt
+/// can make both.
+///
+/// Returns the paths created, in the order written, and an error for each
+/// name that could not be. One failure does not stop the rest.
+pub fn create_entries(dir: &str, spec: &str, folders: bool) -> (Vec<String>, Vec<String>) {
+    let names = match expand_names(spec) {
+        Ok(names) => names,
+        Err(e) => return (Vec::new(), vec![e]),
+    };
+    let mut created = Vec::new();
+    let mut errors = Vec::new();
+    for name in names {
+        let result = if folders || name.ends_with('/') {
+            create_folder(dir, &name)
+        } else {
+            create_file(dir, &name)
+        };
+        match result {
+            Ok(()) => created.push(
+                Path::new(dir)
+                    .join(name.trim_end_matches('/'))
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            Err(e) => errors.push(e),
+        }
+    }
+    (created, errors)
+}
+
+/// Split `spec` at the commas outside braces, and expand the braces in each
+/// part: `{a,b}.rs, c` is `a.rs`, `b.rs` and `c`. Braces nest, and a
+/// duplicate produced by two parts is kept once.
+///
+/// A backslash takes the meaning out of the character after it, for names
+/// that hold one: `a\,b` is one name, `\{a\,b\}` keeps its braces and
+/// comma, and `\ ` is a space that is not trimmed. Before anything else it
+/// is a backslash.
+pub fn expand_names(spec: &str) -> Result<Vec<String>, String> {
+    let mut names: Vec<String> = Vec::new();
+    for part in split_top_level(spec)? {
+        let part = trim_unescaped(part);
+        if part.is_empty() {
+            continue;
+        }
+        for name in expand_braces(part)? {
+            let name = unescape(&name);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    if names.is_empty() {
+        return Err("Name is empty".to_string());
+    }
+    Ok(names)
+}
+
+/// What a backslash can escape in a name spec.
+const ESCAPABLE: [char; 5] = [',', '{', '}', '\\', ' '];
+
+/// The characters of `spec` that mean something to it, with where they
+/// are: all but an escaping backslash and what it escapes.
+fn unescaped(spec: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut chars = spec.char_indices().peekable();
+    std::iter::from_fn(move || loop {
+        let (i, c) = chars.next()?;
+        if c == '\\' && chars.peek().is_some_and(|(_, next)| ESCAPABLE.contains(next)) {
+            chars.next();
+            continue;
+        }
+        return Some((i, c));
+    })
+}
+
+/// `part` without the whitespace around it, an escaped space excepted.
+fn trim_unescaped(part: &str) -> &str {
+    let mut kept: Option<(usize, usize)> = None;
+    let mut chars = part.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let end = match chars.peek() {
+            Some(&(_, next)) if c == '\\' && ESCAPABLE.contains(&next) => {
+                chars.next();
+                i + 1 + next.len_utf8()
+            }
+            _ if c.is_whitespace() => continue,
+            _ => i + c.len_utf8(),
+        };
+        kept = Some((kept.map_or(i, |(start, _)| start), end));
+    }
+    kept.map_or("", |(start, end)| &part[start..end])
+}
+
+fn unescape(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match chars.peek() {
+            Some(&next) if c == '\\' && ESCAPABLE.contains(&next) => {
+                out.push(next);
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn split_top_level(spec: &str) -> Result<Vec<&str>, String> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in unescaped(spec) {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| format!("Unmatched '}}' in {}", spec))?;
+            }
+            ',' if depth == 0 => {
+                parts.push(&spec[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        return Err(format!("Unmatched '{{' in {}", spec));
+    }
+    parts.push(&spec[start..]);
+    Ok(parts)
+}
+
+/// Expand the first brace group in `name` and recurse on each result, so
+/// later groups (and groups within the alternatives) expand in turn.
+fn expand_braces(name: &str) -> Result<Vec<String>, String> {
+    let Some(open) = unescaped(name).find(|&(_, c)| c == '{').map(|(i, _)| i) else {
+        return Ok(vec![name.to_string()]);
+    };
+    let mut depth = 0;
+    let close = unescaped(&name[open..])
+        .find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            (depth == 0).then_some(open + i)
+        })
+        .ok_or_else(|| format!("Unmatched '{{' in {}", name))?;
+    let (head, tail) = (&name[..open], &name[close + 1..]);
+    let alternatives = split_top_level(&name[open + 1..close])?;
+    // As in the shell, braces with no comma are not a group: `{draft}.txt`
+    // is a name with braces in it.
+    if alternatives.len() < 2 {
+        let group = &name[open..=close];
+        return Ok(expand_braces(tail)?
+            .into_iter()
+            .map(|rest| format!("{}{}{}", head, group, rest))
+            .collect());
+    }
+    let mut names = Vec::new();
+    for alternative in alternatives {
+        names.extend(expand_braces(&format!("{}{}{}", head, alternative, tail))?);
+    }
+    Ok(names)
+}
+
 pub fn open_in_terminal(path: &str) -> Result<(), String> {
     let dir = Path::new(path);
     if !dir.is_dir() {
@@ -422,9 +821,72 @@ pub fn open_in_terminal_with_command(command: &str, args: &[&str]) -> Result<(),
     Ok(())
 }
 
+/// Whether a new terminal window is any use: not over SSH or inside tmux,
+/// where it would open on some other screen or not at all, and on Linux
+/// only in an X or Wayland session.
+pub fn can_open_terminal_window() -> bool {
+    let set = |var: &str| std::env::var_os(var).is_some_and(|v| !v.is_empty());
+    if set("SSH_CONNECTION") || set("SSH_TTY") || set("TMUX") {
+        return false;
+    }
+    !cfg!(target_os = "linux") || set("DISPLAY") || set("WAYLAND_DISPLAY")
+}
+
+/// Quote one argument for the shell `shell_command` runs it with.
+pub fn quote_arg(s: &str) -> String {
+    #[cfg(target_os = "windows")]
+    {
+        cmd_quote(s)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        shell_quote(s)
+    }
+}
+
+/// `command` with `args` appended, as the shell will run it. For programs
+/// that run in the terminal they are started from; the caller waits.
+pub fn shell_command(command: &str, args: &[&str]) -> std::process::Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut line = command.to_string();
+        for arg in args {
+            line.push(' ');
+            line.push_str(&cmd_quote(arg));
+        }
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", &line]);
+        cmd
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.args(["-c", &build_shell_command(command, args)]);
+        cmd
+    }
+}
+
+/// Start `command` with `args` appended and leave it running, with nothing
+/// of the terminal's: a graphical program that writes to stdout would
+/// otherwise draw over the TUI.
+pub fn spawn_detached(command: &str, args: &[&str]) -> Result<(), String> {
+    let mut cmd = shell_command(command, args);
+    cmd.stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null());
+    // Its own process group, so that ^C in the terminal does not reach it.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    cmd.spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Failed to run {}: {}", command, e))
+}
+
 /// Quote one argument for a POSIX shell. Single quotes protect everything
 /// except a single quote itself, which has to be closed, escaped, and reopened.
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[cfg(not(target_os = "windows"))]
 fn shell_quote(s: &str) -> String {
     format!("'{}'", s.replace('\'', r"'\''"))
 }
@@ -434,7 +896,7 @@ fn shell_quote(s: &str) -> String {
 /// `command` comes from the user's config (`[open.tui]`) and is passed through
 /// as a shell fragment, so an entry like `"nvim -R"` works as written. The
 /// arguments are paths we supply, so those are quoted.
-#[cfg(any(target_os = "macos", target_os = "linux"))]
+#[cfg(not(target_os = "windows"))]
 fn build_shell_command(command: &str, args: &[&str]) -> String {
     let mut line = command.to_string();
     for arg in args {
@@ -492,9 +954,114 @@ pub fn move_entry(source: &str, dest_dir: &str) -> Result<String, String> {
     }
 }
 
+/// What the tests across the crate share.
+#[cfg(test)]
+pub(crate) mod test_util {
+    use std::path::PathBuf;
+
+    /// A directory under the system temp dir, removed when the test ends —
+    /// failed or not.
+    pub(crate) struct TempDir(pub(crate) PathBuf);
+
+    impl TempDir {
+        pub(crate) fn new(tag: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("panex-{}-{}", tag, std::process::id()));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+}
+
 #[cfg(all(test, any(target_os = "macos", target_os = "linux")))]
 mod tests {
     use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn expands_braces_and_commas() {
+        assert_eq!(
+            expand_names("src/{lib,main}.rs, README.md").unwrap(),
+            ["src/lib.rs", "src/main.rs", "README.md"]
+        );
+        assert_eq!(
+            expand_names("{a,b{1,2}}/x").unwrap(),
+            ["a/x", "b1/x", "b2/x"]
+        );
+        assert_eq!(expand_names("{draft}.txt").unwrap(), ["{draft}.txt"]);
+        assert!(expand_names("src/{lib,main.rs").is_err());
+        assert!(expand_names(" , ").is_err());
+    }
+
+    #[test]
+    fn a_backslash_escapes_commas_braces_and_spaces() {
+        assert_eq!(expand_names(r"a\,b, c").unwrap(), ["a,b", "c"]);
+        assert_eq!(
+            expand_names(r"\{x\,y\}.txt, {p,q\}}").unwrap(),
+            ["{x,y}.txt", "p", "q}"]
+        );
+        assert_eq!(expand_names(r"\ padded\ , two\\").unwrap(), [" padded ", r"two\"]);
+        assert_eq!(expand_names(r"dir\name").unwrap(), [r"dir\name"]);
+    }
+
+    #[test]
+    fn creates_nested_entries_inside_the_folder_only() {
+        let tmp = TempDir::new("create");
+        let dir = &tmp.0;
+        let base = dir.to_string_lossy().to_string();
+
+        let (created, errors) = create_entries(&base, "a/b/c.txt, notes/, ../escape.txt", false);
+        let made = |p: &str| dir.join(p);
+        assert!(made("a/b/c.txt").is_file());
+        assert!(made("notes").is_dir());
+        assert_eq!(created.len(), 2);
+        assert_eq!(errors.len(), 1, "the .. name should be refused");
+        assert!(!dir.join("../escape.txt").exists());
+
+        let (created, errors) = create_entries(&base, "a/b/c.txt", false);
+        assert!(created.is_empty() && errors.len() == 1, "existing file reused");
+    }
+
+    #[test]
+    fn hard_links_are_counted_once_and_both_sizes_reported() {
+        let tmp = TempDir::new("dirsize");
+        let dir = &tmp.0;
+        fs::create_dir_all(dir.join("cache/a")).unwrap();
+        fs::create_dir_all(dir.join("cache/b")).unwrap();
+        fs::write(dir.join("cache/a/blob"), vec![1u8; 100_000]).unwrap();
+        fs::hard_link(dir.join("cache/a/blob"), dir.join("cache/b/blob")).unwrap();
+        fs::write(dir.join("note"), b"hello").unwrap();
+
+        let size = directory_size(&dir.to_string_lossy(), SizeOptions::default()).unwrap();
+        assert_eq!(size.files, 2, "the second link is the same file");
+        assert_eq!(size.dirs, 3);
+        assert_eq!(size.errors, 0);
+        assert_eq!(size.apparent, 100_005);
+        assert!(size.on_disk >= 100_000 && size.on_disk < 200_000);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn followed_links_back_up_the_tree_end_the_walk() {
+        let tmp = TempDir::new("dirloop");
+        let dir = &tmp.0;
+        fs::create_dir_all(dir.join("inner")).unwrap();
+        fs::write(dir.join("inner/data"), b"data").unwrap();
+        std::os::unix::fs::symlink(dir, dir.join("inner/up")).unwrap();
+
+        let options = SizeOptions {
+            follow_symlinks: true,
+            ..SizeOptions::default()
+        };
+        let size = directory_size(&dir.to_string_lossy(), options).unwrap();
+        assert_eq!((size.files, size.dirs, size.apparent), (1, 1, 4));
+    }
 
     /// The bug in #1: a path with spaces reached the target program as many
     /// arguments, because nothing ever quoted it for the shell.
@@ -0,0 +1,484 @@
+//! Renaming many entries at once by a rule, previewed before it is applied.
+//!
+//! A rule works on the name without its extension: find/replace (literal or
+//! regex, with `$1`-style capture groups), `{n}` / `{n:03}` counters, a case
+//! change, and a new extension. Folders have no extension; the whole name is
+//! theirs to change.
+
+use regex::{NoExpand, Regex};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Default)]
+pub enum CaseChange {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    /// First letter of each word upper case, the rest lower.
+    Title,
+}
+
+impl CaseChange {
+    pub fn cycle(self) -> Self {
+        match self {
+            CaseChange::Keep => CaseChange::Lower,
+            CaseChange::Lower => CaseChange::Upper,
+            CaseChange::Upper => CaseChange::Title,
+            CaseChange::Title => CaseChange::Keep,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            CaseChange::Keep => "keep case",
+            CaseChange::Lower => "lower",
+            CaseChange::Upper => "UPPER",
+            CaseChange::Title => "Title",
+        }
+    }
+
+    fn apply(self, s: &str) -> String {
+        match self {
+            CaseChange::Keep => s.to_string(),
+            CaseChange::Lower => s.to_lowercase(),
+            CaseChange::Upper => s.to_uppercase(),
+            CaseChange::Title => {
+                let mut out = String::with_capacity(s.len());
+                let mut word_start = true;
+                for c in s.chars() {
+                    if word_start {
+                        out.extend(c.to_uppercase());
+                    } else {
+                        out.extend(c.to_lowercase());
+                    }
+                    word_start = !c.is_alphanumeric();
+                }
+                out
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct RenameRule {
+    /// What to replace. Empty matches the whole name, so a replacement alone
+    /// (`photo_{n:03}`) renames outright.
+    pub find: String,
+    pub replace: String,
+    /// Whether `find` is a regex. Literal otherwise, and the replacement is
+    /// taken as written — `$1` only means something when there are groups.
+    pub regex: bool,
+    pub case: CaseChange,
+    /// The new extension, with or without its dot. Empty keeps each one's own.
+    pub extension: String,
+}
+
+/// One entry's rename as it would happen. `new_name` equal to `old_name`
+/// means the rule leaves it alone.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RenamePreview {
+    pub path: String,
+    pub old_name: String,
+    pub new_name: String,
+    /// Why this rename cannot be done. Any problem holds up the whole batch.
+    pub problem: Option<String>,
+    /// Part of a chain or cycle (`a → b` while `b → a`): fine, but only
+    /// because the batch goes through temporary names first.
+    pub chained: bool,
+}
+
+impl RenamePreview {
+    pub fn changes(&self) -> bool {
+        self.new_name != self.old_name
+    }
+}
+
+/// Work out what `rule` would do to each of `paths`, counting `{n}` from 1
+/// in the order given. Fails only on a regex that does not compile; every
+/// per-entry problem is reported on its preview instead, so all of them can
+/// be seen at once.
+pub fn preview_renames(paths: &[String], rule: &RenameRule) -> Result<Vec<RenamePreview>, String> {
+    let find = if rule.find.is_empty() {
+        None
+    } else if rule.regex {
+        Some(Regex::new(&rule.find).map_err(|e| format!("Invalid pattern: {}", e))?)
+    } else {
+        Some(Regex::new(&regex::escape(&rule.find)).expect("escaped pattern compiles"))
+    };
+    let extension = rule.extension.trim_start_matches('.');
+
+    let mut previews: Vec<RenamePreview> = paths
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let p = Path::new(path);
+            let old_name = p
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let (stem, ext) = split_extension(&old_name, p.is_dir());
+            let replacement = expand_counters(&rule.replace, i + 1);
+
+            let stem = match &find {
+                None if rule.replace.is_empty() => stem.to_string(),
+                None => replacement,
+                Some(re) if rule.regex => re.replace_all(stem, replacement.as_str()).into_owned(),
+                Some(re) => re.replace_all(stem, NoExpand(&replacement)).into_owned(),
+            };
+            let stem = rule.case.apply(&stem);
+            let new_name = match (extension.is_empty(), ext) {
+                (false, _) => format!("{}.{}", stem, extension),
+                (true, Some(ext)) => format!("{}.{}", stem, ext),
+                (true, None) => stem,
+            };
+            RenamePreview {
+                path: path.clone(),
+                old_name,
+                new_name,
+                problem: None,
+                chained: false,
+            }
+        })
+        .collect();
+
+    check(&mut previews);
+    Ok(previews)
+}
+
+/// Turn an edited list of names back into a batch. `text` holds one name per
+/// line in the order of `paths`, as written out for editing; a line that was
+/// changed is a rename. Lines can be edited but not added or removed — with
+/// the count off, nothing says which line belongs to which entry any more.
+pub fn preview_from_lines(paths: &[String], text: &str) -> Result<Vec<RenamePreview>, String> {
+    let mut names: Vec<&str> = text.lines().map(|l| l.trim_end_matches('\r')).collect();
+    // Editors like to leave a blank line or two at the end.
+    while names.len() > paths.len() && names.last() == Some(&"") {
+        names.pop();
+    }
+    if names.len() != paths.len() {
+        return Err(format!(
+            "Expected {} lines, found {} — lines can be changed, not added or removed",
+            paths.len(),
+            names.len()
+        ));
+    }
+    let mut previews: Vec<RenamePreview> = paths
+        .iter()
+        .zip(names)
+        .map(|(path, new_name)| RenamePreview {
+            path: path.clone(),
+            old_name: Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            new_name: new_name.to_string(),
+            problem: None,
+            chained: false,
+        })
+        .collect();
+    check(&mut previews);
+    Ok(previews)
+}
+
+/// `name` split at its last dot. A leading dot (`.bashrc`) is part of the
+/// name rather than the start of an extension.
+fn split_extension(name: &str, is_dir: bool) -> (&str, Option<&str>) {
+    if is_dir {
+        return (name, None);
+    }
+    match name.rfind('.') {
+        Some(dot) if dot > 0 => (&name[..dot], Some(&name[dot + 1..])),
+        _ => (name, None),
+    }
+}
+
+/// Replace `{n}` with `counter`, and `{n:03}` with it zero-padded to three.
+fn expand_counters(template: &str, counter: usize) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{n") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let expanded = after.find('}').and_then(|end| {
+            let spec = &after[..end];
+            let width = match spec.strip_prefix(':') {
+                Some(w) if !w.is_empty() && w.bytes().all(|b| b.is_ascii_digit()) => {
+                    w.parse::<usize>().ok()?
+                }
+                None if spec.is_empty() => 0,
+                _ => return None,
+            };
+            Some((format!("{:0w$}", counter, w = width), end + 1))
+        });
+        match expanded {
+            Some((text, used)) => {
+                out.push_str(&text);
+                rest = &after[used..];
+            }
+            // Not a counter after all — keep the text as it was.
+            None => {
+                out.push_str("{n");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Mark names that cannot be used, and names that collide — with each other,
+/// or with something already there that is not itself being renamed away.
+fn check(previews: &mut [RenamePreview]) {
+    let target = |p: &RenamePreview| parent_of(&p.path).join(&p.new_name);
+    let moving: HashSet<PathBuf> = previews
+        .iter()
+        .filter(|p| p.changes())
+        .map(|p| PathBuf::from(&p.path))
+        .collect();
+    let mut claimed: HashMap<PathBuf, usize> = HashMap::new();
+    for p in previews.iter() {
+        *claimed.entry(target(p)).or_default() += 1;
+    }
+
+    for p in previews.iter_mut() {
+        let to = target(p);
+        p.problem = if p.new_name.is_empty() {
+            Some("empty name".to_string())
+        } else if p.new_name == "." || p.new_name == ".." || p.new_name.contains('/') {
+            Some("not a valid name".to_string())
+        } else if claimed.get(&to).copied().unwrap_or(0) > 1 {
+            Some("same name as another".to_string())
+        } else if !p.changes() {
+            None
+        } else if moving.contains(&to) {
+            p.chained = true;
+            None
+        } else if to.symlink_metadata().is_ok() && !same_entry(&to, Path::new(&p.path)) {
+            Some("already exists".to_string())
+        } else {
+            None
+        };
+    }
+}
+
+/// True when `a` and `b` name the same entry — on a case-insensitive
+/// filesystem, `Photo.JPG` and `photo.jpg` do, and renaming one to the other
+/// is a case change rather than a collision.
+#[cfg(unix)]
+fn same_entry(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (a.symlink_metadata(), b.symlink_metadata()) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn same_entry(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn parent_of(path: &str) -> PathBuf {
+    Path::new(path)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Carry out a previewed batch: all of it, or — if any rename fails partway —
+/// none of it, with the ones already done put back.
+///
+/// Everything is first moved to a temporary name beside it, then from there
+/// to its new name. That is what lets `a → b, b → a` work at all, and it
+/// means no rename ever lands on a name another entry still holds.
+pub fn apply_renames(previews: &[RenamePreview]) -> Result<usize, String> {
+    if let Some(p) = previews.iter().find(|p| p.problem.is_some()) {
+        return Err(format!(
+            "{}: {}",
+            p.old_name,
+            p.problem.as_deref().unwrap_or_default()
+        ));
+    }
+    let moves: Vec<(PathBuf, PathBuf, PathBuf)> = previews
+        .iter()
+        .filter(|p| p.changes())
+        .enumerate()
+        .map(|(i, p)| {
+            let dir = parent_of(&p.path);
+            let temp = dir.join(format!(".panex-rename-{}-{}", std::process::id(), i));
+            (PathBuf::from(&p.path), temp, dir.join(&p.new_name))
+        })
+        .collect();
+
+    // Phase one: out of the way.
+    for (done, (from, temp, _)) in moves.iter().enumerate() {
+        if let Err(e) = fs::rename(from, temp) {
+            roll_back(moves[..done].iter().map(|(from, temp, _)| (temp, from)));
+            return Err(format!("Failed to rename {}: {}", from.display(), e));
+        }
+    }
+    // Phase two: into place. A target that has appeared since the preview is
+    // not overwritten — `rename` would replace it without a word.
+    for (done, (_, temp, to)) in moves.iter().enumerate() {
+        let result = if to.symlink_metadata().is_ok() {
+            Err(format!("{} already exists", to.display()))
+        } else {
+            fs::rename(temp, to).map_err(|e| format!("Failed to rename to {}: {}", to.display(), e))
+        };
+        if let Err(e) = result {
+            roll_back(moves[..done].iter().map(|(_, temp, to)| (to, temp)));
+            roll_back(moves.iter().map(|(from, temp, _)| (temp, from)));
+            return Err(e);
+        }
+    }
+    Ok(moves.len())
+}
+
+/// Best effort: a step that cannot be undone is left, and the rest still are.
+fn roll_back<'a>(steps: impl Iterator<Item = (&'a PathBuf, &'a PathBuf)>) {
+    for (from, to) in steps {
+        let _ = fs::rename(from, to);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    /// Files named `names` in `dir`, each holding its own name.
+    fn files(dir: &TempDir, names: &[&str]) -> Vec<String> {
+        names
+            .iter()
+            .map(|n| {
+                let path = dir.0.join(n);
+                fs::write(&path, n).unwrap();
+                path.to_string_lossy().to_string()
+            })
+            .collect()
+    }
+
+    fn new_names(previews: &[RenamePreview]) -> Vec<&str> {
+        previews.iter().map(|p| p.new_name.as_str()).collect()
+    }
+
+    #[test]
+    fn capture_groups_and_counters() {
+        let dir = TempDir::new("rename-groups");
+        let paths = files(&dir, &["IMG_2041.jpeg", "IMG_2042.jpeg"]);
+        let rule = RenameRule {
+            find: r"IMG_(\d+)".to_string(),
+            replace: "trip-{n:02}-$1".to_string(),
+            regex: true,
+            extension: ".jpg".to_string(),
+            ..Default::default()
+        };
+        let previews = preview_renames(&paths, &rule).unwrap();
+        assert_eq!(new_names(&previews), ["trip-01-2041.jpg", "trip-02-2042.jpg"]);
+    }
+
+    #[test]
+    fn an_empty_find_renames_the_whole_name_and_case_follows() {
+        let dir = TempDir::new("rename-whole");
+        let paths = files(&dir, &["a.txt", ".hidden"]);
+        let rule = RenameRule {
+            replace: "new file {n}".to_string(),
+            case: CaseChange::Title,
+            ..Default::default()
+        };
+        let previews = preview_renames(&paths, &rule).unwrap();
+        assert_eq!(new_names(&previews), ["New File 1.txt", "New File 2"]);
+    }
+
+    #[test]
+    fn literal_find_takes_dots_and_dollars_as_written() {
+        let dir = TempDir::new("rename-literal");
+        let paths = files(&dir, &["a.b.c.txt"]);
+        let rule = RenameRule {
+            find: ".".to_string(),
+            replace: "$".to_string(),
+            ..Default::default()
+        };
+        let previews = preview_renames(&paths, &rule).unwrap();
+        assert_eq!(new_names(&previews), ["a$b$c.txt"]);
+    }
+
+    #[test]
+    fn collisions_are_caught_before_anything_is_renamed() {
+        let dir = TempDir::new("rename-collide");
+        let paths = files(&dir, &["a1.txt", "a2.txt", "b.txt"]);
+        let rule = RenameRule {
+            find: r"\d".to_string(),
+            regex: true,
+            ..Default::default()
+        };
+        let previews = preview_renames(&paths[..2], &rule).unwrap();
+        assert!(previews.iter().all(|p| p.problem.is_some()), "{:?}", previews);
+        assert!(apply_renames(&previews).is_err());
+        assert!(dir.0.join("a1.txt").exists(), "nothing should have moved");
+
+        let onto_existing = RenameRule {
+            find: "a1".to_string(),
+            replace: "b".to_string(),
+            ..Default::default()
+        };
+        let previews = preview_renames(&paths[..1], &onto_existing).unwrap();
+        assert_eq!(previews[0].problem.as_deref(), Some("already exists"));
+    }
+
+    /// A swap is a cycle: each target is held by the other until it moves.
+    #[test]
+    fn cycles_go_through_temporary_names() {
+        let dir = TempDir::new("rename-cycle");
+        let paths = files(&dir, &["left", "right"]);
+        let mut previews = preview_renames(&paths, &RenameRule::default()).unwrap();
+        previews[0].new_name = "right".to_string();
+        previews[1].new_name = "left".to_string();
+        check(&mut previews);
+        assert!(previews.iter().all(|p| p.chained && p.problem.is_none()));
+
+        assert_eq!(apply_renames(&previews), Ok(2));
+        assert_eq!(fs::read_to_string(dir.0.join("left")).unwrap(), "right");
+        assert_eq!(fs::read_to_string(dir.0.join("right")).unwrap(), "left");
+    }
+
+    #[test]
+    fn edited_lines_become_renames() {
+        let dir = TempDir::new("rename-lines");
+        let paths = files(&dir, &["a", "b", "c"]);
+        let previews = preview_from_lines(&paths, "a\nB\nc\n\n").unwrap();
+        let changed: Vec<_> = previews.iter().filter(|p| p.changes()).collect();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].new_name, "B");
+
+        let err = preview_from_lines(&paths, "a\nc\n").unwrap_err();
+        assert!(err.contains("Expected 3 lines, found 2"), "{}", err);
+
+        let previews = preview_from_lines(&paths, "a\na\nc\n").unwrap();
+        assert_eq!(previews[1].problem.as_deref(), Some("same name as another"));
+    }
+
+    /// A target that turns up between preview and apply fails the batch, and
+    /// the renames already made are undone.
+    #[test]
+    fn a_late_failure_puts_everything_back() {
+        let dir = TempDir::new("rename-rollback");
+        let paths = files(&dir, &["one", "two"]);
+        let rule = RenameRule {
+            replace: "{n}-renamed".to_string(),
+            ..Default::default()
+        };
+        let previews = preview_renames(&paths, &rule).unwrap();
+        fs::write(dir.0.join("2-renamed"), "in the way").unwrap();
+
+        assert!(apply_renames(&previews).is_err());
+        assert!(dir.0.join("one").exists() && dir.0.join("two").exists());
+        assert!(!dir.0.join("1-renamed").exists());
+    }
+}
@@ -0,0 +1,318 @@
+//! A disk usage scan: every folder below a root, read on several threads at
+//! once and reported folder by folder as each is read, so that a view can
+//! fill in while the scan is still going — and be closed before it is done.
+//!
+//! The sizes are the on-disk ones `directory_size` counts, each hard-linked
+//! file once, with the same `SizeOptions`; only the shape differs. A scan
+//! reports listings, not totals: adding them up the tree is the reader's
+//! business, and is what lets a partial scan show partial sizes.
+
+use crate::{disk_size, file_id, link_count, SizeOptions};
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+
+/// What one folder holds, one level down.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listing {
+    pub path: String,
+    pub entries: Vec<UsageEntry>,
+    /// Why the folder could not be read; `entries` is empty then.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageEntry {
+    pub name: String,
+    pub is_dir: bool,
+    /// Space taken on disk. Zero for a folder, whose contents come in a
+    /// listing of their own, and for a hard link to a file already counted.
+    pub size: u64,
+}
+
+/// A file's (device, inode).
+type FileId = (u64, u64);
+
+/// The folders still to read, and how many threads are reading one. Under
+/// one lock, so that a thread finding the queue empty can tell "done" from
+/// "another thread is about to queue more".
+struct Work {
+    folders: VecDeque<PathBuf>,
+    reading: usize,
+    /// Folders already queued, by (device, inode), when links are followed
+    /// and could lead back into one.
+    seen: HashSet<FileId>,
+    /// Files with more than one link, once one of them is counted.
+    linked: HashSet<FileId>,
+}
+
+struct Shared {
+    work: Mutex<Work>,
+    changed: Condvar,
+    cancelled: AtomicBool,
+    options: SizeOptions,
+    device: u64,
+}
+
+/// A scan under way. Dropping it cancels whatever is left.
+pub struct Scan {
+    shared: Arc<Shared>,
+    rx: Receiver<Listing>,
+    done: bool,
+}
+
+impl Scan {
+    pub fn start(root: &str, options: SizeOptions) -> Result<Scan, String> {
+        let meta = fs::metadata(root).map_err(|e| format!("Failed to read metadata: {}", e))?;
+        if !meta.is_dir() {
+            return Err(format!("Not a directory: {}", root));
+        }
+        let shared = Arc::new(Shared {
+            work: Mutex::new(Work {
+                folders: VecDeque::from([PathBuf::from(root)]),
+                reading: 0,
+                seen: HashSet::from([file_id(&meta)]),
+                linked: HashSet::new(),
+            }),
+            changed: Condvar::new(),
+            cancelled: AtomicBool::new(false),
+            options,
+            device: file_id(&meta).0,
+        });
+        let (tx, rx) = mpsc::channel();
+        let threads = thread::available_parallelism().map_or(4, |n| n.get()).min(8);
+        for _ in 0..threads {
+            let (shared, tx) = (shared.clone(), tx.clone());
+            thread::spawn(move || read_folders(&shared, &tx));
+        }
+        Ok(Scan {
+            shared,
+            rx,
+            done: false,
+        })
+    }
+
+    /// The listings read since the last call.
+    pub fn drain(&mut self) -> Vec<Listing> {
+        let mut listings = Vec::new();
+        loop {
+            match self.rx.try_recv() {
+                Ok(listing) => listings.push(listing),
+                Err(TryRecvError::Empty) => break,
+                // Every thread has gone, so every listing is in.
+                Err(TryRecvError::Disconnected) => {
+                    self.done = true;
+                    break;
+                }
+            }
+        }
+        listings
+    }
+
+    /// Whether every listing has been drained — all of them, or, after
+    /// `cancel`, all there will be.
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Stop reading folders. Listings already read can still be drained.
+    pub fn cancel(&self) {
+        self.shared.cancelled.store(true, Ordering::Relaxed);
+        // Under the lock, so no thread can be between checking the flag and
+        // going to sleep when the wake-up is sent.
+        let _work = self.shared.work.lock().unwrap_or_else(|e| e.into_inner());
+        self.shared.changed.notify_all();
+    }
+}
+
+impl Drop for Scan {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// One thread: read folders off the queue, queueing what is below them,
+/// until there are none left and none being read.
+fn read_folders(shared: &Shared, tx: &Sender<Listing>) {
+    loop {
+        let dir = {
+            let mut work = shared.work.lock().unwrap_or_else(|e| e.into_inner());
+            loop {
+                if shared.cancelled.load(Ordering::Relaxed) {
+                    return;
+                }
+                if let Some(dir) = work.folders.pop_front() {
+                    work.reading += 1;
+                    break dir;
+                }
+                if work.reading == 0 {
+                    shared.changed.notify_all();
+                    return;
+                }
+                work = shared.changed.wait(work).unwrap_or_else(|e| e.into_inner());
+            }
+        };
+
+        let Read {
+            mut listing,
+            below,
+            linked,
+        } = read_folder(shared, &dir);
+        {
+            let mut work = shared.work.lock().unwrap_or_else(|e| e.into_inner());
+            for (index, id) in linked {
+                if !work.linked.insert(id) {
+                    listing.entries[index].size = 0;
+                }
+            }
+            for (path, id) in below {
+                if !shared.options.follow_symlinks || work.seen.insert(id) {
+                    work.folders.push_back(path);
+                }
+            }
+            work.reading -= 1;
+            shared.changed.notify_all();
+        }
+        if tx.send(listing).is_err() {
+            // The scan was dropped; nobody is reading any more.
+            shared.cancelled.store(true, Ordering::Relaxed);
+            return;
+        }
+    }
+}
+
+/// What reading one folder gives, before it is checked against the rest
+/// of the scan.
+struct Read {
+    listing: Listing,
+    /// The folders in it to read next, with their ids.
+    below: Vec<(PathBuf, FileId)>,
+    /// Entries with more than one hard link, by index, with their ids.
+    linked: Vec<(usize, FileId)>,
+}
+
+fn read_folder(shared: &Shared, dir: &std::path::Path) -> Read {
+    let mut read = Read {
+        listing: Listing {
+            path: dir.to_string_lossy().to_string(),
+            entries: Vec::new(),
+            error: None,
+        },
+        below: Vec::new(),
+        linked: Vec::new(),
+    };
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            read.listing.error = Some(format!("Failed to read directory: {}", e));
+            return read;
+        }
+    };
+    for entry in entries.flatten() {
+        let meta = if shared.options.follow_symlinks {
+            // A dangling link still takes its own space.
+            fs::metadata(entry.path()).or_else(|_| entry.metadata())
+        } else {
+            entry.metadata()
+        };
+        let Ok(meta) = meta else {
+            continue;
+        };
+        let name = entry.file_name().to_string_lossy().to_string();
+        if meta.is_dir() {
+            let id = file_id(&meta);
+            // A mount is left out, as `directory_size` leaves it out: it
+            // would never be read, so its size would never arrive.
+            if !shared.options.cross_filesystems && id.0 != shared.device {
+                continue;
+            }
+            read.below.push((entry.path(), id));
+            read.listing.entries.push(UsageEntry {
+                name,
+                is_dir: true,
+                size: 0,
+            });
+        } else {
+            if link_count(&meta) > 1 {
+                read.linked.push((read.listing.entries.len(), file_id(&meta)));
+            }
+            read.listing.entries.push(UsageEntry {
+                name,
+                is_dir: false,
+                size: disk_size(&meta),
+            });
+        }
+    }
+    read
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::time::{Duration, Instant};
+
+    fn drain_all(scan: &mut Scan) -> Vec<Listing> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut listings = Vec::new();
+        while !scan.is_done() && Instant::now() < deadline {
+            listings.extend(scan.drain());
+            thread::sleep(Duration::from_millis(5));
+        }
+        listings
+    }
+
+    #[test]
+    fn every_folder_is_listed_once_with_the_sizes_directory_size_counts() {
+        let tmp = TempDir::new("usage");
+        let root = &tmp.0;
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join("c")).unwrap();
+        fs::write(root.join("a/b/data"), vec![1u8; 64 * 1024]).unwrap();
+        fs::write(root.join("c/small"), b"hi").unwrap();
+        fs::write(root.join("top"), vec![2u8; 8 * 1024]).unwrap();
+        let path = root.to_string_lossy().to_string();
+
+        let mut scan = Scan::start(&path, SizeOptions::default()).unwrap();
+        let listings = drain_all(&mut scan);
+        assert!(scan.is_done());
+
+        let mut paths: Vec<&str> = listings.iter().map(|l| l.path.as_str()).collect();
+        paths.sort();
+        let expected: Vec<String> = ["", "/a", "/a/b", "/c"]
+            .iter()
+            .map(|p| format!("{}{}", path, p))
+            .collect();
+        assert_eq!(paths, expected);
+
+        let total: u64 = listings.iter().flat_map(|l| &l.entries).map(|e| e.size).sum();
+        assert_eq!(total, crate::directory_size(&path, SizeOptions::default()).unwrap().on_disk);
+
+        let top = listings.iter().find(|l| l.path == path).unwrap();
+        let mut names: Vec<(&str, bool)> =
+            top.entries.iter().map(|e| (e.name.as_str(), e.is_dir)).collect();
+        names.sort();
+        assert_eq!(names, [("a", true), ("c", true), ("top", false)]);
+    }
+
+    #[test]
+    fn a_cancelled_scan_finishes() {
+        let path = std::env::temp_dir().to_string_lossy().to_string();
+        let mut scan = Scan::start(&path, SizeOptions::default()).unwrap();
+        scan.cancel();
+        drain_all(&mut scan);
+        assert!(scan.is_done());
+    }
+
+    #[test]
+    fn only_folders_can_be_scanned() {
+        let tmp = TempDir::new("usage-file");
+        let file = tmp.0.join("file");
+        fs::write(&file, b"x").unwrap();
+        assert!(Scan::start(&file.to_string_lossy(), SizeOptions::default()).is_err());
+    }
+}
@@ -1,23 +1,40 @@
+use panex_core::git::GitStatus;
 use panex_core::FileEntry;
-use panex_core::config::PanexConfig;
+use panex_core::compare::{SyncDirection, SyncPlan};
+use panex_core::config::{LayoutPreset, Opener, PanexConfig, PresetSplit};
 use ratatui::layout::Rect;
 use ratatui::widgets::TableState;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
-use crate::layout::LayoutNode;
-use crate::sort::{apply_sort_and_filter, SortDirection, SortField};
+use crate::columns::Column;
+use crate::layout::{LayoutNode, SplitDirection};
+use crate::sort::{apply_sort_and_filter, sort_entries, SortDirection, SortField};
 
+/// What a session keeps of a pane is where it was and how it was being
+/// looked at. The listing is read afresh on restore, and a selection is
+/// something you make, not something you come back to.
+#[derive(Serialize, Deserialize)]
 pub struct PaneState {
     pub current_path: String,
+    #[serde(skip)]
     pub entries: Vec<FileEntry>,
+    #[serde(skip)]
     pub selected_paths: HashSet<String>,
     pub focus_index: i32,
     pub search_query: String,
+    #[serde(skip)]
     pub table_state: TableState,
     /// Size level on each axis: -1 smaller, 0 default, +1 larger. Tracked per
     /// axis because a sibling can reclaim one axis without touching the other.
     pub width_level: i8,
     pub height_level: i8,
+    /// This pane's own columns, set with `:columns`. Empty is the config's.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<crate::columns::ColumnSpec>,
+    /// Where back and forward go.
+    #[serde(skip)]
+    pub history: crate::history::History,
 }
 
 impl PaneState {
@@ -31,6 +48,20 @@ impl PaneState {
             table_state: TableState::default().with_selected(Some(0)),
             width_level: 0,
             height_level: 0,
+            columns: Vec::new(),
+            history: crate::history::History::default(),
+        }
+    }
+
+    /// This folder as it is being left.
+    fn visit(&self) -> crate::history::Visit {
+        crate::history::Visit {
+            path: self.current_path.clone(),
+            focused: usize::try_from(self.focus_index)
+                .ok()
+                .and_then(|i| self.entries.get(i))
+                .map(|e| e.path.clone()),
+            focus_index: self.focus_index,
         }
     }
 }
@@ -66,23 +97,79 @@ pub enum AppMode {
         cursor: usize,
         action: PromptAction,
     },
+    /// The path prompt. What is typed is completed as it is typed; the
+    /// candidates show below it, and Tab steps through them.
     PathEdit {
         pane_id: String,
         input: String,
         cursor: usize,
-        completions: Vec<String>,
+        completions: Vec<crate::complete::Candidate>,
         completion_index: Option<usize>,
-        completion_prefix: String,
+        /// A folder was too slow to list, so there may be more candidates.
+        partial: bool,
     },
     FavoritesList {
         pane_id: String,
         selected: usize,
     },
+    /// Waiting for the key after `m`, to put the pane's folder under, or
+    /// after `'`, to go to the favorite under it.
+    Mark {
+        pane_id: String,
+        set: bool,
+    },
+    /// The `z` prompt: words to find a folder by, and the folders they find,
+    /// best first.
+    Jump {
+        pane_id: String,
+        input: String,
+        matches: Vec<String>,
+        selected: usize,
+    },
+    /// "Open with": the openers configured for the files, then the
+    /// desktop's, then the system default as None.
+    OpenWith {
+        paths: Vec<String>,
+        choices: Vec<Option<Opener>>,
+        selected: usize,
+    },
+    /// Picking from the folders visited lately, from `App::recent`.
+    RecentList {
+        pane_id: String,
+        selected: usize,
+    },
+    /// The copies a sync would make, shown before any of them are.
+    SyncPlan {
+        direction: SyncDirection,
+        plan: SyncPlan,
+        offset: usize,
+    },
+    /// A line diff of the files focused in two panes.
+    Diff { view: crate::diff::DiffView },
+    /// Renaming the selection by a rule, with the outcome previewed.
+    BulkRename { state: crate::rename::BulkRename },
+    /// Choosing one of the config's `[layouts]` to open.
+    LayoutPicker { selected: usize },
+    /// The command palette: any action by name, or a command with an argument.
+    Palette { state: crate::palette::Palette },
+    /// The disk usage view, drawn from `App::usage`. The scan lives there
+    /// rather than here so that it carries on under a confirmation dialog.
+    Usage,
+    /// The commits that touched a path, newest first.
+    GitLog {
+        path: String,
+        commits: Vec<panex_core::git::Commit>,
+        offset: usize,
+    },
 }
 
 #[derive(PartialEq)]
 pub enum ConfirmAction {
     Delete(Vec<String>),
+    /// Delete an entry of the disk usage view, by its node there.
+    DeleteUsage(usize),
+    /// Put paths back as the commit checked out has them.
+    GitDiscard(Vec<String>),
 }
 
 #[derive(PartialEq, Clone, Debug)]
@@ -93,6 +180,30 @@ pub enum PromptAction {
     /// tab closing while the prompt is open cannot land the name on a
     /// neighbour — it fails instead.
     RenameTab(String),
+    /// Name the favorite with this path; empty shows the path again.
+    FavoriteLabel(String),
+    /// File the favorite with this path under a group; empty ungroups it.
+    FavoriteGroup(String),
+    /// Bind the favorite with this path to a key; empty unbinds it.
+    FavoriteKey(String),
+    /// A `!` command, to be run in this folder.
+    Shell(String),
+}
+
+/// Work that needs the terminal to itself: the TUI steps aside while it
+/// runs and comes back when it is done. Input handling only asks for it —
+/// the terminal belongs to the event loop, which carries it out.
+#[derive(PartialEq, Debug)]
+pub enum Suspend {
+    /// Edit these entries' names as lines of text in `$EDITOR`, then rename
+    /// whatever lines changed.
+    EditNames { pane_id: String, paths: Vec<String> },
+    /// Run openers that asked for this terminal, one after another, each
+    /// with its files.
+    Open { runs: Vec<(Opener, Vec<String>)> },
+    /// A command typed at `!`, its placeholders filled in, run in `dir`.
+    /// What it prints stays up until Enter.
+    Shell { command: String, dir: String },
 }
 
 pub struct FileClipboard {
@@ -106,6 +217,14 @@ pub enum ClipMode {
     Cut,
 }
 
+/// `--pick` or `--pick-multi`: Enter chooses rather than opens, and what
+/// was chosen is printed on quit.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Pick {
+    One,
+    Many,
+}
+
 pub struct App {
     pub layout_root: LayoutNode,
     pub pane_map: HashMap<String, PaneState>,
@@ -122,6 +241,9 @@ pub struct App {
     pub status_message: Option<String>,
     pub status_message_at: Option<std::time::Instant>,
     pub should_quit: bool,
+    pub pick: Option<Pick>,
+    /// What Enter chose, in pick mode.
+    pub picked: Vec<String>,
     pub config: PanexConfig,
     /// Pane, row and time of the last left click, so the next one can be
     /// recognised as the second half of a double click. Crossterm reports
@@ -156,6 +278,36 @@ pub struct App {
     /// puts the keyboard back where it was rather than on whichever pane
     /// happens to be first in the layout.
     pub oko_return_to: Option<String>,
+    /// The two panes being compared, if any. Kept current as either one moves,
+    /// so walking both into the same subfolder compares that next.
+    pub comparison: Option<crate::compare::Comparison>,
+    pub suspend: Option<Suspend>,
+    /// Built from the config's `[keys]` at startup.
+    pub keymap: crate::keys::Keymap,
+    /// The keys of a sequence typed so far, e.g. the first `g` of `gg`.
+    pub pending_keys: Vec<crate::keys::Key>,
+    /// Lines run from the command palette, most recent first.
+    pub recent_commands: Vec<String>,
+    /// Built from the config's `[theme]` at startup.
+    pub theme: crate::theme::Theme,
+    /// What in `[theme]` could not be used; shown beside the keymap's.
+    pub theme_problems: Vec<String>,
+    /// Colours and icons for the file list, from `[files]` and `$LS_COLORS`.
+    pub file_styles: crate::filetype::FileStyles,
+    /// The file list's columns and formats, from `[files]`.
+    pub columns: crate::columns::Columns,
+    /// Folder sizes measured so far, and those being measured.
+    pub dir_sizes: crate::dirsize::DirSizes,
+    /// The disk usage view, while it is open; closing it ends its scan.
+    pub usage: Option<crate::usage::Usage>,
+    /// What git says of the repositories the panes are in.
+    pub git: crate::gitstatus::GitStatuses,
+    /// Whether files git ignores are left out of the lists.
+    pub hide_ignored: bool,
+    /// Folders visited lately, in any pane.
+    pub recent: crate::history::Recent,
+    /// Every folder visited, ranked for `z`.
+    pub frecency: panex_core::frecency::Frecency,
 }
 
 impl App {
@@ -168,7 +320,24 @@ impl App {
 
         let mut pane = PaneState::new(&start_path);
 
-        let raw_entries = panex_core::read_directory(&start_path)?;
+        let config = PanexConfig::load();
+        let keymap = crate::keys::Keymap::new(&config.keys);
+        let (theme, theme_problems) = crate::theme::Theme::load(&config.theme);
+        let file_styles = crate::filetype::FileStyles::load(&config.files, &theme);
+        let columns = crate::columns::Columns::load(&config.files);
+        let dir_sizes = crate::dirsize::DirSizes::new(config.sizes.options());
+        let mut git = crate::gitstatus::GitStatuses::new(config.git.is_enabled());
+        git.request(&start_path);
+        let hide_ignored = config.git.hide_ignored;
+        let mut recent = crate::history::Recent::load();
+        recent.visit(&start_path);
+        let mut frecency = panex_core::frecency::Frecency::load();
+        frecency.visit(&start_path, panex_core::frecency::now());
+
+        let mut raw_entries = panex_core::read_directory(&start_path)?;
+        if columns.default.iter().any(|s| s.column == Column::Items) {
+            panex_core::count_items(&mut raw_entries);
+        }
         let filtered = apply_sort_and_filter(&raw_entries, false, "", SortField::Name, SortDirection::Asc);
         pane.entries = filtered;
 
@@ -177,8 +346,6 @@ impl App {
         raw_entries_map.insert(pane_id.clone(), raw_entries);
         pane_map.insert(pane_id.clone(), pane);
 
-        let config = PanexConfig::load();
-
         Ok(Self {
             layout_root: LayoutNode::Leaf {
                 pane_id: pane_id.clone(),
@@ -197,6 +364,8 @@ impl App {
             status_message: None,
             status_message_at: None,
             should_quit: false,
+            pick: None,
+            picked: Vec::new(),
             config,
             last_click: None,
             oko_available: crate::oko::is_available(),
@@ -208,9 +377,141 @@ impl App {
             oko_offset: 0,
             oko_capacity: 0,
             oko_return_to: None,
+            comparison: None,
+            suspend: None,
+            keymap,
+            pending_keys: Vec::new(),
+            recent_commands: Vec::new(),
+            theme,
+            theme_problems,
+            file_styles,
+            columns,
+            dir_sizes,
+            usage: None,
+            git,
+            hide_ignored,
+            recent,
+            frecency,
         })
     }
 
+    /// Everything in the config that was ignored at startup, `[keys]` first.
+    pub fn config_problems(&self) -> Vec<&str> {
+        self.keymap
+            .problems
+            .iter()
+            .chain(&self.theme_problems)
+            .chain(&self.file_styles.problems)
+            .chain(&self.columns.problems)
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Take the folder sizes measured since the last call and show them in
+    /// every pane listing those folders. Returns true if any arrived.
+    pub fn pump_dir_sizes(&mut self) -> bool {
+        if !self.dir_sizes.is_busy() {
+            return false;
+        }
+        let done: HashMap<String, u64> = self.dir_sizes.drain().into_iter().collect();
+        if done.is_empty() {
+            return false;
+        }
+        {
+            for (pane_id, raw) in self.raw_entries_map.iter_mut() {
+                let mut touched = false;
+                for entry in raw.iter_mut() {
+                    if let Some(size) = done.get(&entry.path) {
+                        entry.contents_size = Some(*size);
+                        touched = true;
+                    }
+                }
+                if let (true, Some(pane)) = (touched, self.pane_map.get_mut(pane_id)) {
+                    for entry in pane.entries.iter_mut() {
+                        if let Some(size) = done.get(&entry.path) {
+                            entry.contents_size = Some(*size);
+                        }
+                    }
+                    // Sorted by size, the new sizes move rows; the cursor
+                    // stays on the entry it was on, not on the row number.
+                    if self.sort_field == SortField::Size {
+                        let focused = usize::try_from(pane.focus_index)
+                            .ok()
+                            .and_then(|i| pane.entries.get(i))
+                            .map(|e| e.path.clone());
+                        sort_entries(&mut pane.entries, self.sort_field, self.sort_direction);
+                        if let Some(i) = focused
+                            .and_then(|path| pane.entries.iter().position(|e| e.path == path))
+                        {
+                            pane.focus_index = i as i32;
+                            pane.table_state.select(Some(i));
+                        }
+                    }
+                }
+            }
+        }
+        let progress = self.dir_sizes.progress();
+        self.set_status(progress);
+        true
+    }
+
+    /// Take in the git statuses that have come in, and show them in every
+    /// pane. Returns true if any came.
+    pub fn pump_git(&mut self) -> bool {
+        if !self.git.is_busy() || !self.git.drain() {
+            return false;
+        }
+        let pane_ids: Vec<String> = self.pane_map.keys().cloned().collect();
+        for pane_id in pane_ids {
+            let Some(pane) = self.pane_map.get(&pane_id) else {
+                continue;
+            };
+            let dir = pane.current_path.clone();
+            let Some(raw) = self.raw_entries_map.get_mut(&pane_id) else {
+                continue;
+            };
+            let mut changed = false;
+            for entry in raw.iter_mut() {
+                let status = self.git.status_of(&dir, &entry.path);
+                changed |= entry.git != status;
+                entry.git = status;
+            }
+            if !changed {
+                continue;
+            }
+            if self.hide_ignored || self.sort_field == SortField::Git {
+                // What is ignored may have changed, and with it what is
+                // listed or where; the cursor stays on the entry it was on.
+                let focused = self.pane_map.get(&pane_id).and_then(|pane| {
+                    let i = usize::try_from(pane.focus_index).ok()?;
+                    pane.entries.get(i).map(|e| e.path.clone())
+                });
+                self.refilter_pane(&pane_id);
+                if let Some(path) = focused {
+                    self.focus_path(&pane_id, &path);
+                }
+            } else {
+                let raw = &self.raw_entries_map[&pane_id];
+                let statuses: HashMap<&str, Option<GitStatus>> =
+                    raw.iter().map(|e| (e.path.as_str(), e.git)).collect();
+                if let Some(pane) = self.pane_map.get_mut(&pane_id) {
+                    for entry in pane.entries.iter_mut() {
+                        if let Some(status) = statuses.get(entry.path.as_str()) {
+                            entry.git = *status;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Take in what the disk usage scan has read since the last call.
+    /// Returns true if the view changed.
+    pub fn pump_usage(&mut self) -> bool {
+        self.usage.as_mut().is_some_and(|usage| usage.pump())
+    }
+
     /// Take whatever the oko reader has queued. Returns true if the cards
     /// changed, so the caller redraws only then — the stream is already quiet
     /// by design, and an identical snapshot should not cost a frame.
@@ -262,16 +563,107 @@ impl App {
         self.status_message_at = Some(std::time::Instant::now());
     }
 
+    /// The columns `pane_id` shows: its own, or the config's.
+    pub fn pane_columns(&self, pane_id: &str) -> &[crate::columns::ColumnSpec] {
+        match self.pane_map.get(pane_id) {
+            Some(pane) if !pane.columns.is_empty() => &pane.columns,
+            _ => &self.columns.default,
+        }
+    }
+
+    /// Whether `pane_id`'s folders need counting: a read per folder, so only
+    /// while the count is shown or sorted by.
+    fn counts_items(&self, pane_id: &str) -> bool {
+        self.sort_field == SortField::Items
+            || self.pane_columns(pane_id).iter().any(|s| s.column == Column::Items)
+    }
+
+    /// `path`'s listing, as `pane_id` will show it. The git status is what
+    /// was last known, and a fresh one is asked for.
+    pub fn read_entries(&mut self, pane_id: &str, path: &str) -> Result<Vec<FileEntry>, String> {
+        let mut entries = panex_core::read_directory(path)?;
+        if self.counts_items(pane_id) {
+            panex_core::count_items(&mut entries);
+        }
+        for entry in entries.iter_mut() {
+            if entry.is_dir {
+                entry.contents_size = self.dir_sizes.lookup(&entry.path, entry.modified);
+            }
+            entry.git = self.git.status_of(path, &entry.path);
+        }
+        self.git.request(path);
+        Ok(entries)
+    }
+
+    /// `raw` filtered and sorted as the panes show it.
+    pub fn visible(&self, raw: &[FileEntry], search_query: &str) -> Vec<FileEntry> {
+        let mut entries = apply_sort_and_filter(
+            raw,
+            self.show_hidden,
+            search_query,
+            self.sort_field,
+            self.sort_direction,
+        );
+        if self.hide_ignored {
+            entries.retain(|e| e.git != Some(GitStatus::Ignored));
+        }
+        entries
+    }
+
+    /// Show `path` in the pane, leaving where it was in its history.
     pub fn navigate_to(&mut self, pane_id: &str, path: &str) {
-        match panex_core::read_directory(path) {
+        let Some(here) = self.pane_map.get(pane_id).map(PaneState::visit) else {
+            return;
+        };
+        if !self.load(pane_id, path) {
+            return;
+        }
+        if here.path != path {
+            if let Some(pane) = self.pane_map.get_mut(pane_id) {
+                pane.history.leave(here);
+            }
+        }
+        self.remember(path);
+    }
+
+    /// Count a visit to `path`, for the recent list and for `z`.
+    fn remember(&mut self, path: &str) {
+        self.recent.visit(path);
+        self.frecency.visit(path, panex_core::frecency::now());
+    }
+
+    /// Step back through the pane's history, or forward, skipping folders
+    /// that have gone since. The cursor goes back to where it was left.
+    pub fn go_back(&mut self, pane_id: &str, forward: bool) -> bool {
+        let Some(pane) = self.pane_map.get_mut(pane_id) else {
+            return false;
+        };
+        let here = pane.visit();
+        let usable = |v: &crate::history::Visit| std::path::Path::new(&v.path).is_dir();
+        let Some(to) = pane.history.step(forward, here, usable) else {
+            return false;
+        };
+        if !self.load(pane_id, &to.path) {
+            return false;
+        }
+        self.remember(&to.path);
+        let found = to.focused.is_some_and(|path| self.focus_path(pane_id, &path));
+        if let Some(pane) = self.pane_map.get_mut(pane_id) {
+            if !found && !pane.entries.is_empty() {
+                let index = to.focus_index.clamp(0, pane.entries.len() as i32 - 1);
+                pane.focus_index = index;
+                pane.table_state.select(Some(index as usize));
+            }
+        }
+        true
+    }
+
+    /// Read `path` into the pane, cursor at the top. Returns false, with
+    /// the error in the status bar, if it could not be read.
+    fn load(&mut self, pane_id: &str, path: &str) -> bool {
+        match self.read_entries(pane_id, path) {
             Ok(raw_entries) => {
-                let filtered = apply_sort_and_filter(
-                    &raw_entries,
-                    self.show_hidden,
-                    "",
-                    self.sort_field,
-                    self.sort_direction,
-                );
+                let filtered = self.visible(&raw_entries, "");
                 if let Some(pane) = self.pane_map.get_mut(pane_id) {
                     pane.current_path = path.to_string();
                     pane.entries = filtered;
@@ -286,13 +678,32 @@ impl App {
                 }
                 self.raw_entries_map.insert(pane_id.to_string(), raw_entries);
                 self.status_message = None;
+                self.recompare_if_involved(pane_id);
+                true
             }
             Err(e) => {
                 self.set_status(format!("Error: {}", e));
+                false
             }
         }
     }
 
+    /// Put the cursor on the entry at `path`, if the pane lists it. Returns
+    /// whether it did — a hidden or filtered-out entry is not there to land on.
+    pub fn focus_path(&mut self, pane_id: &str, path: &str) -> bool {
+        let Some(pane) = self.pane_map.get_mut(pane_id) else {
+            return false;
+        };
+        match pane.entries.iter().position(|e| e.path == path) {
+            Some(i) => {
+                pane.focus_index = i as i32;
+                pane.table_state.select(Some(i));
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn refresh_pane(&mut self, pane_id: &str) {
         let path = if let Some(pane) = self.pane_map.get(pane_id) {
             pane.current_path.clone()
@@ -305,15 +716,9 @@ impl App {
             .map(|p| p.search_query.clone())
             .unwrap_or_default();
 
-        match panex_core::read_directory(&path) {
+        match self.read_entries(pane_id, &path) {
             Ok(raw_entries) => {
-                let filtered = apply_sort_and_filter(
-                    &raw_entries,
-                    self.show_hidden,
-                    &search_query,
-                    self.sort_field,
-                    self.sort_direction,
-                );
+                let filtered = self.visible(&raw_entries, &search_query);
                 if let Some(pane) = self.pane_map.get_mut(pane_id) {
                     pane.entries = filtered;
                     // Clamp focus
@@ -326,6 +731,7 @@ impl App {
                     }
                 }
                 self.raw_entries_map.insert(pane_id.to_string(), raw_entries);
+                self.recompare_if_involved(pane_id);
             }
             Err(e) => {
                 self.set_status(format!("Error: {}", e));
@@ -333,21 +739,92 @@ impl App {
         }
     }
 
+    /// Compare two panes' directories and mark their rows. Replaces any
+    /// comparison already running — only one pair is compared at a time.
+    pub fn compare_panes(&mut self, left: &str, right: &str, by_content: bool) {
+        self.comparison = Some(crate::compare::Comparison::new(left, right, by_content));
+        self.recompare();
+    }
+
+    fn recompare_if_involved(&mut self, pane_id: &str) {
+        if self.comparison.as_ref().is_some_and(|c| c.involves(pane_id)) {
+            self.recompare();
+        }
+    }
+
+    /// Run the current comparison again against whatever the two panes show
+    /// now. A pane that can no longer be read ends it, rather than leaving
+    /// marks up that describe a directory nobody is looking at. By content,
+    /// it only starts: `pump_compare` takes the answer in.
+    pub fn recompare(&mut self) {
+        let Some(comparison) = &self.comparison else {
+            return;
+        };
+        let path = |id: &str| self.pane_map.get(id).map(|p| p.current_path.clone());
+        let (Some(left_dir), Some(right_dir)) = (path(&comparison.left), path(&comparison.right))
+        else {
+            self.comparison = None;
+            return;
+        };
+        if comparison.by_content {
+            if let Some(comparison) = self.comparison.as_mut() {
+                comparison.start(left_dir, right_dir);
+            }
+            return;
+        }
+        let result = panex_core::compare::compare_directories(&left_dir, &right_dir, false);
+        self.take_comparison(left_dir, right_dir, result);
+    }
+
+    /// Take in a content comparison that has finished. Returns true if one
+    /// had.
+    pub fn pump_compare(&mut self) -> bool {
+        let Some((left_dir, right_dir, result)) =
+            self.comparison.as_mut().and_then(|c| c.poll())
+        else {
+            return false;
+        };
+        self.take_comparison(left_dir, right_dir, result);
+        true
+    }
+
+    fn take_comparison(
+        &mut self,
+        left_dir: String,
+        right_dir: String,
+        result: Result<Vec<panex_core::compare::DirDiff>, String>,
+    ) {
+        match result {
+            Ok(diffs) => {
+                let marks = crate::compare::marks_for(&diffs, &left_dir, &right_dir);
+                if let Some(comparison) = self.comparison.as_mut() {
+                    comparison.diffs = diffs;
+                    comparison.marks = marks;
+                }
+            }
+            Err(e) => {
+                self.comparison = None;
+                self.set_status(format!("Compare failed: {}", e));
+            }
+        }
+    }
+
     pub fn refilter_pane(&mut self, pane_id: &str) {
-        let raw = self.raw_entries_map.get(pane_id).cloned().unwrap_or_default();
+        let counts_items = self.counts_items(pane_id);
+        let mut raw = self.raw_entries_map.get(pane_id).cloned().unwrap_or_default();
+        // The columns or the sort have just asked for counts the listing was
+        // read without.
+        if counts_items && raw.iter().any(|e| e.is_dir && e.items.is_none()) {
+            panex_core::count_items(&mut raw);
+            self.raw_entries_map.insert(pane_id.to_string(), raw.clone());
+        }
         let search_query = self
             .pane_map
             .get(pane_id)
             .map(|p| p.search_query.clone())
             .unwrap_or_default();
 
-        let filtered = apply_sort_and_filter(
-            &raw,
-            self.show_hidden,
-            &search_query,
-            self.sort_field,
-            self.sort_direction,
-        );
+        let filtered = self.visible(&raw, &search_query);
         if let Some(pane) = self.pane_map.get_mut(pane_id) {
             pane.entries = filtered;
             if pane.entries.is_empty() {
@@ -360,6 +837,149 @@ impl App {
         }
     }
 
+    /// Replace the layout with the named preset from the config. A folder
+    /// that does not exist opens at the nearest one above it that does, and
+    /// is listed in the status line, so a typo does not pass unnoticed.
+    pub fn open_layout(&mut self, name: &str) -> Result<(), String> {
+        let preset = self
+            .config
+            .layouts
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("No layout named '{}'", name))?;
+        let missing = self.replace_layout(&preset);
+        if missing.is_empty() {
+            self.set_status(format!("Layout {}", name));
+        } else {
+            self.set_status(format!(
+                "Layout {}: not found, opened the nearest folder instead: {}",
+                name,
+                missing.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Replace the layout with the folders given on the command line, side
+    /// by side unless `split` says otherwise. Asked to split one folder, it
+    /// is shown twice, to be taken somewhere else in one of them.
+    pub fn open_folders(&mut self, folders: &[String], split: Option<PresetSplit>) {
+        let mut panes: Vec<LayoutPreset> =
+            folders.iter().cloned().map(LayoutPreset::Path).collect();
+        if split.is_some() && panes.len() == 1 {
+            panes.push(panes[0].clone());
+        }
+        let preset = match panes.len() {
+            1 => panes.remove(0),
+            _ => LayoutPreset::Split {
+                split: split.unwrap_or(PresetSplit::Vertical),
+                ratio: None,
+                panes,
+            },
+        };
+        self.replace_layout(&preset);
+    }
+
+    /// Focus `file` in the first pane showing its folder. A dotfile would
+    /// be filtered out of the listing, so asking for one shows hidden files.
+    pub fn select_file(&mut self, file: &str) -> bool {
+        let path = std::path::Path::new(file);
+        let Some(folder) = path.parent().map(|p| p.to_string_lossy().to_string()) else {
+            return false;
+        };
+        let leaves = crate::layout::collect_leaf_ids(&self.layout_root);
+        let Some(pane_id) = leaves
+            .iter()
+            .find(|id| self.pane_map.get(*id).is_some_and(|p| p.current_path == folder))
+            .cloned()
+        else {
+            return false;
+        };
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden && !self.show_hidden {
+            self.show_hidden = true;
+            for id in &leaves {
+                self.refilter_pane(id);
+            }
+        }
+        self.active_pane_id = pane_id.clone();
+        self.focus_path(&pane_id, file)
+    }
+
+    /// Put `preset` in place of whatever was open, returning the folders
+    /// that were not found.
+    fn replace_layout(&mut self, preset: &LayoutPreset) -> Vec<String> {
+        // Whatever was open goes: the card stream with its pane, and any
+        // comparison between panes that are about to stop existing.
+        self.oko_pane_id = None;
+        self.oko_stream = None;
+        self.oko_return_to = None;
+        self.comparison = None;
+        self.pane_map.clear();
+        self.raw_entries_map.clear();
+
+        let mut missing = Vec::new();
+        self.layout_root = self.build_layout(preset, &mut missing);
+        self.active_pane_id = crate::layout::collect_leaf_ids(&self.layout_root)[0].clone();
+        missing
+    }
+
+    fn build_layout(&mut self, preset: &LayoutPreset, missing: &mut Vec<String>) -> LayoutNode {
+        match preset {
+            LayoutPreset::Path(path) => {
+                let wanted = if path.starts_with('~') {
+                    path.replacen('~', &self.home_path, 1)
+                } else {
+                    path.clone()
+                };
+                let dir = crate::session::nearest_existing(&wanted)
+                    .unwrap_or_else(|| self.home_path.clone());
+                if dir != wanted.trim_end_matches('/') {
+                    missing.push(path.clone());
+                }
+                let pane_id = self.next_pane_id();
+                self.pane_map.insert(pane_id.clone(), PaneState::new(&dir));
+                self.navigate_to(&pane_id, &dir);
+                LayoutNode::Leaf { pane_id }
+            }
+            LayoutPreset::Split {
+                split,
+                ratio,
+                panes,
+            } => {
+                let mut children: Vec<LayoutNode> =
+                    panes.iter().map(|p| self.build_layout(p, missing)).collect();
+                let Some(mut node) = children.pop() else {
+                    return self.build_layout(&LayoutPreset::Path(self.home_path.clone()), missing);
+                };
+                let direction = match split {
+                    PresetSplit::Vertical => SplitDirection::Vertical,
+                    PresetSplit::Horizontal => SplitDirection::Horizontal,
+                };
+                // Folded from the right, so each split's first side is one
+                // pane and its second the rest: 1/n of what is left keeps the
+                // panes even.
+                let count = children.len() + 1;
+                for (i, first) in children.into_iter().enumerate().rev() {
+                    let even = 1.0 / (count - i) as f64;
+                    let share = match ratio {
+                        Some(r) if i == 0 => r.clamp(0.1, 0.9),
+                        _ => even,
+                    };
+                    node = LayoutNode::Split {
+                        direction: direction.clone(),
+                        first: Box::new(first),
+                        second: Box::new(node),
+                        ratio: share,
+                    };
+                }
+                node
+            }
+        }
+    }
+
     pub fn next_pane_id(&mut self) -> String {
         self.pane_counter += 1;
         format!("pane-{}", self.pane_counter)
@@ -0,0 +1,237 @@
+//! The command line. Parsed apart from acting on it, so that what a given
+//! line means can be checked without starting the app — `main` prints the
+//! help, the version or the error.
+
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+use lexopt::prelude::*;
+use panex_core::config::PresetSplit;
+
+use crate::app::Pick;
+
+/// What the command line asks of the app itself.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub restore: bool,
+    pub session: Option<String>,
+    pub layout: Option<String>,
+    /// Where to write the active pane's folder on quit, for a shell
+    /// function to `cd` to.
+    pub choosedir: Option<PathBuf>,
+    pub pick: Option<Pick>,
+    /// One pane each, in the order given.
+    pub folders: Vec<PathBuf>,
+    pub split: Option<PresetSplit>,
+    /// A file to start on, focused in the pane showing its folder.
+    pub select: Option<PathBuf>,
+    /// A config file to read and save instead of ~/.panex/config.toml.
+    pub config: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            restore: true,
+            session: None,
+            layout: None,
+            choosedir: None,
+            pick: None,
+            folders: Vec::new(),
+            split: None,
+            select: None,
+            config: None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Parsed {
+    Run(Options),
+    Help,
+    Version,
+}
+
+/// Read the arguments, the program's name left off. `--help` and
+/// `--version` win over anything after them, as they always have.
+pub fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Parsed, String> {
+    let mut options = Options::default();
+    let mut parser = lexopt::Parser::from_args(args);
+    let text = |value: OsString| value.string().map_err(|e| e.to_string());
+    while let Some(arg) = parser.next().map_err(|e| e.to_string())? {
+        match arg {
+            Short('h') | Short('H') | Long("help") => return Ok(Parsed::Help),
+            Short('V') | Short('v') | Long("version") => return Ok(Parsed::Version),
+            Long("no-restore") => options.restore = false,
+            Long("session") => {
+                let name = parser.value().map_err(|_| "--session needs a name")?;
+                options.session = Some(text(name)?);
+            }
+            Long("layout") => {
+                let name = parser.value().map_err(|_| "--layout needs a name")?;
+                options.layout = Some(text(name)?);
+            }
+            Long("choosedir") => {
+                let file = parser.value().map_err(|_| "--choosedir needs a file")?;
+                options.choosedir = Some(PathBuf::from(file));
+            }
+            Long("pick") => options.pick = Some(Pick::One),
+            Long("pick-multi") => options.pick = Some(Pick::Many),
+            Long("split") => {
+                let way = parser.value().map_err(|_| "--split needs a direction")?;
+                options.split = Some(match text(way)?.as_str() {
+                    "vertical" => PresetSplit::Vertical,
+                    "horizontal" => PresetSplit::Horizontal,
+                    _ => return Err("--split takes vertical or horizontal".to_string()),
+                });
+            }
+            Long("select") => {
+                let file = parser.value().map_err(|_| "--select needs a file")?;
+                options.select = Some(PathBuf::from(file));
+            }
+            Long("config") => {
+                let file = parser.value().map_err(|_| "--config needs a file")?;
+                options.config = Some(PathBuf::from(file));
+            }
+            Value(folder) => options.folders.push(PathBuf::from(folder)),
+            _ => return Err(arg.unexpected().to_string()),
+        }
+    }
+    Ok(Parsed::Run(options))
+}
+
+/// Make the paths given absolute from `cwd` and refuse any that are not
+/// there, so that a typo is reported on the command line rather than
+/// opened as some other folder. `--select` alone opens its own folder.
+pub fn check_paths(options: &mut Options, cwd: &Path) -> Result<(), String> {
+    if options.layout.is_some()
+        && (!options.folders.is_empty() || options.split.is_some() || options.select.is_some())
+    {
+        return Err("--layout opens its own folders; leave out the folders, --split and --select"
+            .to_string());
+    }
+    for folder in &mut options.folders {
+        let path = absolute(cwd, folder);
+        if !path.exists() {
+            return Err(format!("{}: no such folder", folder.display()));
+        }
+        if !path.is_dir() {
+            return Err(format!(
+                "{}: not a folder (--select starts on a file)",
+                folder.display()
+            ));
+        }
+        *folder = path;
+    }
+    if let Some(file) = &mut options.select {
+        let path = absolute(cwd, file);
+        if !path.exists() {
+            return Err(format!("{}: no such file", file.display()));
+        }
+        let parent = path.parent().unwrap_or(Path::new("/")).to_path_buf();
+        if options.folders.is_empty() {
+            options.folders.push(parent);
+        } else if !options.folders.contains(&parent) {
+            return Err(format!(
+                "{}: not in any of the folders given",
+                file.display()
+            ));
+        }
+        *file = path;
+    }
+    if options.split.is_some() && options.folders.is_empty() {
+        options.folders.push(cwd.to_path_buf());
+    }
+    if let Some(file) = &mut options.config {
+        *file = absolute(cwd, file);
+    }
+    Ok(())
+}
+
+/// `path` from `cwd`, with `.` and `..` taken away. Lexically, as the
+/// shell's `cd` does, so a folder reached through a link keeps its name.
+fn absolute(cwd: &Path, path: &Path) -> PathBuf {
+    let mut out = PathBuf::from("/");
+    for part in cwd.join(path).components() {
+        match part {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::Normal(name) => out.push(name),
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::click_tests::TempDir;
+
+    fn parse_line(line: &str) -> Result<Parsed, String> {
+        parse(line.split_whitespace().map(OsString::from))
+    }
+
+    #[test]
+    fn options_take_their_value_either_way() {
+        let Ok(Parsed::Run(options)) =
+            parse_line("--session=work --choosedir /tmp/dir --pick-multi --no-restore")
+        else {
+            panic!("should parse");
+        };
+        assert_eq!(
+            options,
+            Options {
+                restore: false,
+                session: Some("work".to_string()),
+                choosedir: Some(PathBuf::from("/tmp/dir")),
+                pick: Some(Pick::Many),
+                ..Options::default()
+            }
+        );
+        assert_eq!(parse_line("--pick -h --bogus"), Ok(Parsed::Help));
+    }
+
+    #[test]
+    fn what_is_not_understood_is_refused() {
+        assert_eq!(parse_line("--layout"), Err("--layout needs a name".to_string()));
+        assert!(parse_line("--bogus").unwrap_err().contains("--bogus"));
+        assert!(parse_line("--pick=yes").is_err());
+        assert_eq!(
+            parse_line("--split diagonal"),
+            Err("--split takes vertical or horizontal".to_string())
+        );
+    }
+
+    #[test]
+    fn folders_and_the_selected_file_are_checked_from_the_cwd() {
+        let tmp = TempDir::new("args");
+        let root = &tmp.0;
+        std::fs::create_dir_all(root.join("a")).unwrap();
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        std::fs::write(root.join("b/notes.txt"), "").unwrap();
+        let checked = |line: &str| {
+            let Ok(Parsed::Run(mut options)) = parse_line(line) else {
+                panic!("should parse");
+            };
+            check_paths(&mut options, root).map(|_| options)
+        };
+
+        let options = checked("a ./b/../b --split horizontal --select b/notes.txt").unwrap();
+        assert_eq!(options.folders, vec![root.join("a"), root.join("b")]);
+        assert_eq!(options.split, Some(PresetSplit::Horizontal));
+        assert_eq!(options.select, Some(root.join("b/notes.txt")));
+        // On its own, the file brings its folder.
+        let options = checked("--select b/notes.txt").unwrap();
+        assert_eq!(options.folders, vec![root.join("b")]);
+
+        assert_eq!(checked("a c").unwrap_err(), "c: no such folder");
+        assert!(checked("b/notes.txt").unwrap_err().contains("not a folder"));
+        assert_eq!(
+            checked("a --select b/notes.txt").unwrap_err(),
+            "b/notes.txt: not in any of the folders given"
+        );
+        assert!(checked("a --layout work").is_err());
+    }
+}
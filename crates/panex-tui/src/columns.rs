@@ -0,0 +1,551 @@
+//! The file list's columns: which, in what order and how wide, and how the
+//! sizes and dates in them are written.
+//!
+//! ```toml
+//! [files]
+//! columns = ["name", "size:10", "modified", "permissions", "owner", "git"]
+//! size_format = "exact"       # or "human", the default
+//! date_format = "relative"    # or "absolute", the default, or "iso"
+//! ```
+//!
+//! That is every pane's set; `:columns` gives the active pane its own.
+
+use panex_core::config::FilesConfig;
+use panex_core::git::GitStatus;
+use panex_core::FileEntry;
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::sort::SortField;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Column {
+    Name,
+    Ext,
+    Size,
+    Modified,
+    Created,
+    Permissions,
+    Owner,
+    /// How many entries a folder holds.
+    Items,
+    /// Git's letter for the entry, blank when it is clean.
+    Git,
+}
+
+impl Column {
+    pub const ALL: [Column; 9] = [
+        Column::Name,
+        Column::Ext,
+        Column::Size,
+        Column::Modified,
+        Column::Created,
+        Column::Permissions,
+        Column::Owner,
+        Column::Items,
+        Column::Git,
+    ];
+
+    /// As written in the config and after `:columns`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Name => "name",
+            Column::Ext => "ext",
+            Column::Size => "size",
+            Column::Modified => "modified",
+            Column::Created => "created",
+            Column::Permissions => "permissions",
+            Column::Owner => "owner",
+            Column::Items => "items",
+            Column::Git => "git",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Ext => "Ext",
+            Column::Size => "Size",
+            Column::Modified => "Modified",
+            Column::Created => "Created",
+            Column::Permissions => "Perms",
+            Column::Owner => "Owner",
+            Column::Items => "Items",
+            Column::Git => "Git",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "extension" => Some(Column::Ext),
+            "perms" | "mode" => Some(Column::Permissions),
+            "count" => Some(Column::Items),
+            "status" => Some(Column::Git),
+            name => Column::ALL.into_iter().find(|c| c.name() == name),
+        }
+    }
+
+    /// The width a column gets when none is given. For the name it is the
+    /// least it is squeezed to; it takes whatever the others leave.
+    fn default_width(self) -> u16 {
+        match self {
+            Column::Name => 10,
+            Column::Ext => 6,
+            Column::Size => 8,
+            Column::Modified | Column::Created => 12,
+            Column::Permissions => 10,
+            Column::Owner => 8,
+            Column::Items => 6,
+            Column::Git => 3,
+        }
+    }
+
+    pub fn sort_field(self) -> SortField {
+        match self {
+            Column::Name => SortField::Name,
+            Column::Ext => SortField::Extension,
+            Column::Size => SortField::Size,
+            Column::Modified => SortField::Modified,
+            Column::Created => SortField::Created,
+            Column::Permissions => SortField::Permissions,
+            Column::Owner => SortField::Owner,
+            Column::Items => SortField::Items,
+            Column::Git => SortField::Git,
+        }
+    }
+}
+
+/// A column and, if it was given one, its width: `size:10`.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ColumnSpec {
+    pub column: Column,
+    pub width: Option<u16>,
+}
+
+impl ColumnSpec {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, width) = match spec.split_once(':') {
+            Some((name, width)) => (name, Some(width)),
+            None => (spec, None),
+        };
+        let column = Column::parse(name).ok_or_else(|| {
+            let names: Vec<&str> = Column::ALL.iter().map(|c| c.name()).collect();
+            format!("unknown column '{}' (have: {})", name, names.join(", "))
+        })?;
+        let width = match width {
+            None => None,
+            Some(w) => match w.parse::<u16>() {
+                Ok(w) if w > 0 => Some(w),
+                _ => return Err(format!("{}: '{}' is not a width", name, w)),
+            },
+        };
+        Ok(ColumnSpec { column, width })
+    }
+
+    fn width(&self) -> u16 {
+        self.width.unwrap_or(self.column.default_width())
+    }
+}
+
+impl TryFrom<String> for ColumnSpec {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, String> {
+        ColumnSpec::parse(&spec)
+    }
+}
+
+impl From<ColumnSpec> for String {
+    fn from(spec: ColumnSpec) -> String {
+        match spec.width {
+            Some(width) => format!("{}:{}", spec.column.name(), width),
+            None => spec.column.name().to_string(),
+        }
+    }
+}
+
+/// A list of columns, e.g. `name size:10 modified`. The name is always
+/// shown, first if it was not placed — the marks and icons live in it.
+pub fn parse_list<'a>(specs: impl IntoIterator<Item = &'a str>) -> Result<Vec<ColumnSpec>, String> {
+    let mut list: Vec<ColumnSpec> = Vec::new();
+    for spec in specs {
+        let spec = ColumnSpec::parse(spec)?;
+        if list.iter().any(|s| s.column == spec.column) {
+            return Err(format!("'{}' is listed twice", spec.column.name()));
+        }
+        list.push(spec);
+    }
+    if !list.iter().any(|s| s.column == Column::Name) {
+        list.insert(0, ColumnSpec { column: Column::Name, width: None });
+    }
+    Ok(list)
+}
+
+/// What the list showed before columns could be chosen.
+fn built_in() -> Vec<ColumnSpec> {
+    [Column::Name, Column::Ext, Column::Size, Column::Modified]
+        .into_iter()
+        .map(|column| ColumnSpec { column, width: None })
+        .collect()
+}
+
+/// The next sort field `s` moves to: the one after `field` among `columns`.
+pub fn next_sort_field(columns: &[ColumnSpec], field: SortField) -> SortField {
+    let fields: Vec<SortField> = columns.iter().map(|s| s.column.sort_field()).collect();
+    match fields.iter().position(|f| *f == field) {
+        Some(i) => fields[(i + 1) % fields.len()],
+        None => fields.first().copied().unwrap_or(SortField::Name),
+    }
+}
+
+/// Where each column is drawn across `area`, laid out exactly as the table
+/// lays out its cells, so that the header lines up and a click on it can
+/// be told which column it hit.
+pub fn areas(columns: &[ColumnSpec], area: Rect) -> Rc<[Rect]> {
+    Layout::horizontal(constraints(columns))
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(area)
+}
+
+pub fn constraints(columns: &[ColumnSpec]) -> Vec<Constraint> {
+    columns
+        .iter()
+        .map(|spec| match spec.column {
+            Column::Name => Constraint::Min(spec.width()),
+            _ => Constraint::Length(spec.width()),
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SizeFormat {
+    /// `1.5 MB`.
+    Human,
+    /// `1,572,864`.
+    Exact,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DateFormat {
+    /// `24/05/25`.
+    Absolute,
+    /// `3h ago`.
+    Relative,
+    /// `2025-05-24 13:05`, UTC.
+    Iso,
+}
+
+/// `[files]`'s columns and formats, checked once at startup.
+#[derive(Debug)]
+pub struct Columns {
+    pub default: Vec<ColumnSpec>,
+    pub size: SizeFormat,
+    pub date: DateFormat,
+    pub problems: Vec<String>,
+}
+
+impl Columns {
+    pub fn load(config: &FilesConfig) -> Self {
+        let mut problems = Vec::new();
+        let default = if config.columns.is_empty() {
+            built_in()
+        } else {
+            parse_list(config.columns.iter().map(String::as_str)).unwrap_or_else(|e| {
+                problems.push(format!("[files] columns: {}", e));
+                built_in()
+            })
+        };
+        let size = match config.size_format.as_deref() {
+            None | Some("human") => SizeFormat::Human,
+            Some("exact") => SizeFormat::Exact,
+            Some(other) => {
+                problems.push(format!("[files] size_format = '{}': use human or exact", other));
+                SizeFormat::Human
+            }
+        };
+        let date = match config.date_format.as_deref() {
+            None | Some("absolute") => DateFormat::Absolute,
+            Some("relative") => DateFormat::Relative,
+            Some("iso") => DateFormat::Iso,
+            Some(other) => {
+                problems.push(format!(
+                    "[files] date_format = '{}': use absolute, relative or iso",
+                    other
+                ));
+                DateFormat::Absolute
+            }
+        };
+        Columns { default, size, date, problems }
+    }
+
+    /// The text of any column but the name, which the list draws itself.
+    pub fn cell(&self, column: Column, entry: &FileEntry, now: u64) -> String {
+        match column {
+            Column::Name => entry.name.clone(),
+            Column::Ext if entry.is_dir => String::new(),
+            Column::Ext => entry
+                .name
+                .rsplit_once('.')
+                .map(|(_, e)| e.to_string())
+                .unwrap_or_default(),
+            Column::Size => match (entry.is_dir, entry.contents_size) {
+                (true, None) => String::from("—"),
+                (true, Some(size)) => self.size(size),
+                (false, _) => self.size(entry.size),
+            },
+            Column::Modified => self.date(entry.modified, now),
+            Column::Created => self.date(entry.created, now),
+            Column::Permissions => format_mode(entry.mode),
+            Column::Owner => owner_name(entry.uid),
+            Column::Items => match entry.items {
+                Some(n) => n.to_string(),
+                None if entry.is_dir => String::from("—"),
+                None => String::new(),
+            },
+            Column::Git => entry.git.map(git_letter).unwrap_or_default().to_string(),
+        }
+    }
+
+    fn size(&self, bytes: u64) -> String {
+        match self.size {
+            SizeFormat::Human => format_size(bytes),
+            SizeFormat::Exact => format_exact(bytes),
+        }
+    }
+
+    fn date(&self, timestamp: u64, now: u64) -> String {
+        if timestamp == 0 {
+            return "—".to_string();
+        }
+        match self.date {
+            DateFormat::Absolute => format_date(timestamp),
+            DateFormat::Relative => format_relative(timestamp, now),
+            DateFormat::Iso => {
+                let (y, m, d) = civil(timestamp);
+                let secs = timestamp % 86400;
+                format!("{}-{:02}-{:02} {:02}:{:02}", y, m, d, secs / 3600, secs % 3600 / 60)
+            }
+        }
+    }
+}
+
+/// Git's own letters where it has them: `?` and `!` as `git status -s`
+/// writes them, `U` for unmerged.
+pub fn git_letter(status: GitStatus) -> &'static str {
+    match status {
+        GitStatus::Conflicted => "U",
+        GitStatus::Modified => "M",
+        GitStatus::Staged => "S",
+        GitStatus::Untracked => "?",
+        GitStatus::Ignored => "!",
+    }
+}
+
+/// Seconds since the epoch, for relative dates.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn format_size(bytes: u64) -> String {
+    if bytes == 0 {
+        return "0 B".to_string();
+    }
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit_idx = 0;
+    while size >= 1024.0 && unit_idx < units.len() - 1 {
+        size /= 1024.0;
+        unit_idx += 1;
+    }
+    if unit_idx == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, units[unit_idx])
+    }
+}
+
+/// Bytes, in groups of three.
+fn format_exact(bytes: u64) -> String {
+    let digits = bytes.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn format_date(timestamp: u64) -> String {
+    let (y, m, d) = civil(timestamp);
+    format!("{:02}/{:02}/{:02}", d, m, y % 100)
+}
+
+/// How long ago, in the largest unit that is at least one. A time in the
+/// future — a clock set wrong somewhere — is written as a date instead.
+pub fn format_relative(timestamp: u64, now: u64) -> String {
+    let Some(age) = now.checked_sub(timestamp) else {
+        return format_date(timestamp);
+    };
+    match age {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{}m ago", age / 60),
+        3600..86400 => format!("{}h ago", age / 3600),
+        86400..604800 => format!("{}d ago", age / 86400),
+        604800..2592000 => format!("{}w ago", age / 604800),
+        2592000..31536000 => format!("{}mo ago", age / 2592000),
+        _ => format!("{}y ago", age / 31536000),
+    }
+}
+
+/// Year, month and day of a time, in UTC.
+fn civil(timestamp: u64) -> (i32, u32, u32) {
+    let mut y = 1970i32;
+    let mut remaining = (timestamp / 86400) as i64;
+    loop {
+        let days_in_year = if is_leap(y) { 366 } else { 365 };
+        if remaining < days_in_year {
+            break;
+        }
+        remaining -= days_in_year;
+        y += 1;
+    }
+    let month_days = if is_leap(y) {
+        [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+    } else {
+        [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
+    };
+    let mut m = 0usize;
+    for (i, &md) in month_days.iter().enumerate() {
+        if remaining < md as i64 {
+            m = i;
+            break;
+        }
+        remaining -= md as i64;
+    }
+    (y, m as u32 + 1, remaining as u32 + 1)
+}
+
+fn is_leap(y: i32) -> bool {
+    (y % 4 == 0 && y % 100 != 0) || y % 400 == 0
+}
+
+/// `drwxr-xr-x`, as `ls -l` writes it. Where there are no Unix modes, a dash.
+fn format_mode(mode: u32) -> String {
+    if mode == 0 {
+        return "—".to_string();
+    }
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o010000 => 'p',
+        0o140000 => 's',
+        0o060000 => 'b',
+        0o020000 => 'c',
+        _ => '-',
+    };
+    let mut out = String::from(kind);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 0o7;
+        out.push(if bits & 4 != 0 { 'r' } else { '-' });
+        out.push(if bits & 2 != 0 { 'w' } else { '-' });
+        out.push(if bits & 1 != 0 { 'x' } else { '-' });
+    }
+    out
+}
+
+/// The name of user `uid`, or the number if it has none. Looked up once per
+/// user: a listing is mostly one owner, and sorting asks for it repeatedly.
+pub fn owner_name(uid: u32) -> String {
+    static NAMES: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+    let names = NAMES.get_or_init(Default::default);
+    let mut names = names.lock().unwrap_or_else(|e| e.into_inner());
+    names.entry(uid).or_insert_with(|| lookup_user(uid)).clone()
+}
+
+#[cfg(unix)]
+fn lookup_user(uid: u32) -> String {
+    uzers::get_user_by_uid(uid)
+        .map(|user| user.name().to_string_lossy().to_string())
+        .unwrap_or_else(|| uid.to_string())
+}
+
+#[cfg(not(unix))]
+fn lookup_user(_uid: u32) -> String {
+    "—".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_parse_with_and_without_widths() {
+        let list = parse_list(["size:10", "Modified", "perms"]).unwrap();
+        let columns: Vec<Column> = list.iter().map(|s| s.column).collect();
+        assert_eq!(
+            columns,
+            [Column::Name, Column::Size, Column::Modified, Column::Permissions]
+        );
+        assert_eq!(list[1].width, Some(10));
+        assert_eq!(String::from(list[1]), "size:10");
+        assert!(parse_list(["size:0"]).is_err());
+        assert!(parse_list(["name", "name"]).is_err());
+        assert!(parse_list(["colour"]).unwrap_err().contains("unknown column"));
+    }
+
+    #[test]
+    fn sizes_and_dates_follow_the_format() {
+        assert_eq!(format_exact(1_572_864), "1,572,864");
+        assert_eq!(format_exact(999), "999");
+        assert_eq!(format_relative(1_000, 1_030), "just now");
+        assert_eq!(format_relative(1_000, 1_000 + 3 * 3600), "3h ago");
+        assert_eq!(format_relative(1_000, 1_000 + 400 * 86400), "1y ago");
+        // 2024-02-29 13:05 UTC.
+        let leap_day = 1_709_211_900;
+        assert_eq!(format_date(leap_day), "29/02/24");
+        let columns = Columns {
+            date: DateFormat::Iso,
+            ..Columns::load(&FilesConfig::default())
+        };
+        assert_eq!(columns.date(leap_day, 0), "2024-02-29 13:05");
+    }
+
+    #[test]
+    fn modes_read_like_ls() {
+        assert_eq!(format_mode(0o040755), "drwxr-xr-x");
+        assert_eq!(format_mode(0o100640), "-rw-r-----");
+        assert_eq!(format_mode(0), "—");
+    }
+
+    #[test]
+    fn s_moves_through_the_shown_columns() {
+        let list = parse_list(["name", "size", "owner"]).unwrap();
+        assert_eq!(next_sort_field(&list, SortField::Name), SortField::Size);
+        assert_eq!(next_sort_field(&list, SortField::Owner), SortField::Name);
+        assert_eq!(next_sort_field(&list, SortField::Modified), SortField::Name);
+    }
+
+    #[test]
+    fn a_bad_config_falls_back_and_says_so() {
+        let config = FilesConfig {
+            columns: vec!["name".into(), "wat".into()],
+            date_format: Some("soon".into()),
+            ..FilesConfig::default()
+        };
+        let columns = Columns::load(&config);
+        assert_eq!(columns.default, built_in());
+        assert_eq!(columns.date, DateFormat::Absolute);
+        assert_eq!(columns.problems.len(), 2);
+    }
+}
@@ -0,0 +1,152 @@
+//! Two panes compared side by side: which panes, and how each row came out.
+//!
+//! The comparing is panex-core's. What lives here is the translation into
+//! something a single row can show — a row only ever speaks for its own side,
+//! so "left is newer" reads as newer in one pane and older in the other.
+//!
+//! Compared by content, every same-sized pair is read in full, which on big
+//! files is long enough to freeze the screen; that comparison runs on a
+//! thread of its own, and the marks already up stay until it answers.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use panex_core::compare::{Difference, DirDiff};
+
+/// The two folders a background run compared, and what it found.
+pub type Outcome = (String, String, Result<Vec<DirDiff>, String>);
+
+/// How a row compares with its namesake in the other pane.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RowMark {
+    /// Nothing by this name on the other side.
+    Unique,
+    Newer,
+    Older,
+    /// Neither side newer, but not the same either.
+    Different,
+}
+
+impl RowMark {
+    pub fn glyph(self) -> &'static str {
+        match self {
+            RowMark::Unique => "+",
+            RowMark::Newer => "▲",
+            RowMark::Older => "▼",
+            RowMark::Different => "≠",
+        }
+    }
+}
+
+pub struct Comparison {
+    /// The pane on the left (or on top) — first of the two in layout order.
+    pub left: String,
+    pub right: String,
+    pub by_content: bool,
+    pub diffs: Vec<DirDiff>,
+    /// Marks keyed by entry path. Both panes share the map: the two sides are
+    /// different directories, so no path can belong to both.
+    pub marks: HashMap<String, RowMark>,
+    /// The run in the background, if there is one.
+    pending: Option<Receiver<Outcome>>,
+}
+
+impl Comparison {
+    pub fn new(left: &str, right: &str, by_content: bool) -> Self {
+        Comparison {
+            left: left.to_string(),
+            right: right.to_string(),
+            by_content,
+            diffs: Vec::new(),
+            marks: HashMap::new(),
+            pending: None,
+        }
+    }
+
+    pub fn involves(&self, pane_id: &str) -> bool {
+        self.left == pane_id || self.right == pane_id
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Compare `left_dir` with `right_dir` on another thread. A run still
+    /// going is forgotten: its answer is for folders no longer shown.
+    pub fn start(&mut self, left_dir: String, right_dir: String) {
+        let (tx, rx) = mpsc::channel();
+        let by_content = self.by_content;
+        thread::spawn(move || {
+            let result =
+                panex_core::compare::compare_directories(&left_dir, &right_dir, by_content);
+            let _ = tx.send((left_dir, right_dir, result));
+        });
+        self.pending = Some(rx);
+    }
+
+    /// The background run's answer, once it has come.
+    pub fn poll(&mut self) -> Option<Outcome> {
+        let outcome = match self.pending.as_ref()?.try_recv() {
+            Ok(outcome) => Some(outcome),
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => None,
+        };
+        self.pending = None;
+        outcome
+    }
+}
+
+/// Spread the differences over the rows of both directories.
+pub fn marks_for(diffs: &[DirDiff], left_dir: &str, right_dir: &str) -> HashMap<String, RowMark> {
+    let mut marks = HashMap::new();
+    let path = |dir: &str, name: &str| Path::new(dir).join(name).to_string_lossy().to_string();
+    for d in diffs {
+        let (left, right) = match d.difference {
+            Difference::OnlyLeft => (Some(RowMark::Unique), None),
+            Difference::OnlyRight => (None, Some(RowMark::Unique)),
+            Difference::LeftNewer => (Some(RowMark::Newer), Some(RowMark::Older)),
+            Difference::RightNewer => (Some(RowMark::Older), Some(RowMark::Newer)),
+            Difference::Different => (Some(RowMark::Different), Some(RowMark::Different)),
+        };
+        if let Some(mark) = left {
+            marks.insert(path(left_dir, &d.name), mark);
+        }
+        if let Some(mark) = right {
+            marks.insert(path(right_dir, &d.name), mark);
+        }
+    }
+    marks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diff(name: &str, difference: Difference) -> DirDiff {
+        DirDiff {
+            name: name.to_string(),
+            is_dir: false,
+            difference,
+        }
+    }
+
+    /// One difference, told from each side: newer on the left is older on the
+    /// right, and a name on one side only marks that side alone.
+    #[test]
+    fn each_side_reads_the_difference_from_where_it_stands() {
+        let marks = marks_for(
+            &[
+                diff("a", Difference::LeftNewer),
+                diff("b", Difference::OnlyRight),
+            ],
+            "/l",
+            "/r",
+        );
+        assert_eq!(marks.get("/l/a"), Some(&RowMark::Newer));
+        assert_eq!(marks.get("/r/a"), Some(&RowMark::Older));
+        assert_eq!(marks.get("/r/b"), Some(&RowMark::Unique));
+        assert_eq!(marks.get("/l/b"), None);
+    }
+}
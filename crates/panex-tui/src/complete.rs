@@ -0,0 +1,372 @@
+//! Completing a typed path for the path prompt (`e`).
+//!
+//! Each segment is matched loosely against what is on disk — a prefix, a
+//! substring, or its letters in order, ignoring case — so `~/d/pa/cr` finds
+//! `~/dev/PanEx/crates`. `~` and `$VAR` are expanded first and `.` and `..`
+//! are taken as they read, before the disk is asked anything.
+//!
+//! Every folder is read on a thread of its own and waited on only until a
+//! shared deadline: a folder on a mount that has stopped answering costs the
+//! prompt a fraction of a second, not the terminal, and its candidates are
+//! simply missing. The thread is left to finish, or not, by itself.
+
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// How long one completion may wait on the disk, all folders together.
+const TIME_LIMIT: Duration = Duration::from_millis(200);
+
+/// How many entries of one folder are looked at.
+const ENTRY_LIMIT: usize = 5_000;
+
+/// How many folders a loose segment may stand for at once, before the
+/// worst matches are dropped.
+const BRANCH_LIMIT: usize = 16;
+
+/// How many candidates are offered.
+const CANDIDATE_LIMIT: usize = 200;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    /// The whole path, as it replaces what was typed: under `~` if that was
+    /// how it was typed, with a `/` after a folder.
+    pub path: String,
+    pub is_dir: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Completion {
+    pub candidates: Vec<Candidate>,
+    /// Some folder did not answer in time, so there may be more.
+    pub partial: bool,
+}
+
+/// `input` as a path to go to: `~` and `$VAR` expanded, made absolute from
+/// `base`, with `.` and `..` taken away.
+pub fn resolve(input: &str, base: &str, home: &str) -> String {
+    let expanded = expand(input, home);
+    let path = Path::new(base).join(&expanded);
+    let mut out = PathBuf::from("/");
+    for part in path.components() {
+        match part {
+            Component::ParentDir => {
+                out.pop();
+            }
+            Component::Normal(name) => out.push(name),
+            _ => {}
+        }
+    }
+    let mut resolved = out.to_string_lossy().to_string();
+    // A trailing slash says "inside this folder", which completion needs.
+    if expanded.ends_with('/') && !resolved.ends_with('/') {
+        resolved.push('/');
+    }
+    resolved
+}
+
+/// `~` at the start, then `$NAME` and `${NAME}` anywhere. A variable that
+/// is not set is left as typed, so the path reads as wrong rather than as
+/// some other path.
+fn expand(input: &str, home: &str) -> String {
+    let input = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home, rest),
+        _ => input.to_string(),
+    };
+    let mut out = String::new();
+    let mut rest = input.as_str();
+    while let Some(at) = rest.find('$') {
+        out.push_str(&rest[..at]);
+        let after = &rest[at + 1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(inner) => match inner.find('}') {
+                Some(end) => (&inner[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => out.push_str(&value),
+            _ => out.push_str(&rest[at..at + 1 + len]),
+        }
+        rest = &rest[at + 1 + len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// How well `segment` (lower-cased) matches `name`, lower being better:
+/// the name itself, a prefix of it, inside it, or its letters in order.
+fn rank(segment: &str, name: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    if name == segment {
+        Some(0)
+    } else if name.starts_with(segment) {
+        Some(1)
+    } else if name.contains(segment) {
+        Some(2)
+    } else {
+        let mut letters = name.chars();
+        segment
+            .chars()
+            .all(|c| letters.any(|n| n == c))
+            .then_some(3)
+    }
+}
+
+enum Listing {
+    /// Each entry's name, and whether it is a folder — following a link to
+    /// see.
+    Entries(Vec<(String, bool)>),
+    /// Not readable: perhaps only passable, as a folder without read
+    /// permission is.
+    Unreadable,
+    /// No answer by the deadline.
+    Late,
+}
+
+fn list(dir: &Path, deadline: Instant) -> Listing {
+    let (tx, rx) = mpsc::channel();
+    let dir = dir.to_path_buf();
+    std::thread::spawn(move || {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(read) => Listing::Entries(
+                read.flatten()
+                    .take(ENTRY_LIMIT)
+                    .map(|entry| {
+                        let is_dir = match entry.file_type() {
+                            Ok(ty) if ty.is_symlink() => entry.path().is_dir(),
+                            Ok(ty) => ty.is_dir(),
+                            Err(_) => false,
+                        };
+                        (entry.file_name().to_string_lossy().to_string(), is_dir)
+                    })
+                    .collect(),
+            ),
+            Err(_) => Listing::Unreadable,
+        };
+        let _ = tx.send(entries);
+    });
+    rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+        .unwrap_or(Listing::Late)
+}
+
+/// The paths `input` could be completed to, best first.
+pub fn complete(input: &str, base: &str, home: &str, show_hidden: bool) -> Completion {
+    let deadline = Instant::now() + TIME_LIMIT;
+    let resolved = resolve(input, base, home);
+    let mut segments: Vec<&str> = resolved.split('/').filter(|s| !s.is_empty()).collect();
+    // A `.` or `..` just typed has already been taken: what is left is the
+    // folder it leads to, offered as it is.
+    let taken = matches!(input.rsplit('/').next(), Some(".." | "."));
+    let last = if resolved.ends_with('/') {
+        Some("")
+    } else if taken {
+        None
+    } else {
+        segments.pop()
+    };
+    let mut partial = false;
+
+    // Every folder the segments so far could mean, with how loosely.
+    let mut branches: Vec<(PathBuf, u32)> = vec![(PathBuf::from("/"), 0)];
+    for segment in segments {
+        let lower = segment.to_lowercase();
+        let mut next = Vec::new();
+        for (dir, score) in &branches {
+            let entries = match list(dir, deadline) {
+                Listing::Entries(entries) => entries,
+                // Not listed: trust the segment as typed.
+                Listing::Unreadable => {
+                    next.push((dir.join(segment), *score));
+                    continue;
+                }
+                Listing::Late => {
+                    partial = true;
+                    next.push((dir.join(segment), *score));
+                    continue;
+                }
+            };
+            if entries
+                .iter()
+                .any(|(name, is_dir)| *is_dir && name == segment)
+            {
+                next.push((dir.join(segment), *score));
+                continue;
+            }
+            let found: Vec<(&String, u32)> = entries
+                .iter()
+                .filter(|(name, is_dir)| *is_dir && visible(name, segment, show_hidden))
+                .filter_map(|(name, _)| rank(&lower, name).map(|r| (name, r)))
+                .collect();
+            // Only the closest kind of match: `pa` should not wander into
+            // every folder with a `p` and then an `a` when `PanEx` is there.
+            let best = found.iter().map(|(_, r)| *r).min();
+            for (name, r) in found {
+                if Some(r) == best {
+                    next.push((dir.join(name), score + r));
+                }
+            }
+        }
+        next.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        next.dedup_by(|a, b| a.0 == b.0);
+        next.truncate(BRANCH_LIMIT);
+        branches = next;
+    }
+
+    let mut found: Vec<(String, bool, u32)> = Vec::new();
+    match last {
+        None => {
+            for (dir, score) in branches {
+                found.push((dir.to_string_lossy().to_string(), true, score));
+            }
+        }
+        Some(segment) => {
+            let lower = segment.to_lowercase();
+            for (dir, score) in &branches {
+                let entries = match list(dir, deadline) {
+                    Listing::Entries(entries) => entries,
+                    Listing::Unreadable => continue,
+                    Listing::Late => {
+                        partial = true;
+                        continue;
+                    }
+                };
+                for (name, is_dir) in entries {
+                    if !visible(&name, segment, show_hidden) {
+                        continue;
+                    }
+                    if let Some(r) = rank(&lower, &name) {
+                        found.push((
+                            dir.join(&name).to_string_lossy().to_string(),
+                            is_dir,
+                            score + r,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    found.sort_by(|a, b| {
+        a.2.cmp(&b.2)
+            .then_with(|| a.0.to_lowercase().cmp(&b.0.to_lowercase()))
+    });
+    found.truncate(CANDIDATE_LIMIT);
+
+    let under_home = input.starts_with('~');
+    let candidates = found
+        .into_iter()
+        .map(|(path, is_dir, _)| {
+            let mut path = match path.strip_prefix(home) {
+                Some(rest) if under_home && (rest.is_empty() || rest.starts_with('/')) => {
+                    format!("~{}", rest)
+                }
+                _ => path,
+            };
+            if is_dir && !path.ends_with('/') {
+                path.push('/');
+            }
+            Candidate { path, is_dir }
+        })
+        .collect();
+    Completion {
+        candidates,
+        partial,
+    }
+}
+
+/// Hidden entries are offered when they are shown, or asked for with a dot.
+fn visible(name: &str, segment: &str, show_hidden: bool) -> bool {
+    show_hidden || !name.starts_with('.') || segment.starts_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::click_tests::TempDir;
+
+    fn tree(tag: &str) -> TempDir {
+        let tmp = TempDir::new(&format!("complete-{}", tag));
+        let root = &tmp.0;
+        for dir in [
+            "dev/PanEx/crates",
+            "dev/PanEx/src",
+            "dev/paper",
+            "docs",
+            ".config",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("dev/PanEx/Cargo.toml"), b"").unwrap();
+        std::fs::write(root.join(".profile"), b"").unwrap();
+        tmp
+    }
+
+    fn paths(completion: &Completion) -> Vec<&str> {
+        completion
+            .candidates
+            .iter()
+            .map(|c| c.path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn each_segment_is_matched_loosely() {
+        let tmp = tree("loose");
+        let home = tmp.0.to_string_lossy().to_string();
+        let found = complete("~/d/pa/cr", "/", &home, false);
+        assert_eq!(
+            paths(&found),
+            ["~/dev/PanEx/crates/", "~/dev/PanEx/Cargo.toml"]
+        );
+
+        let found = complete("~/dev/PanEx/c", "/", &home, false);
+        assert_eq!(
+            paths(&found),
+            [
+                "~/dev/PanEx/Cargo.toml",
+                "~/dev/PanEx/crates/",
+                "~/dev/PanEx/src/"
+            ]
+        );
+        assert!(!found.candidates[0].is_dir);
+    }
+
+    #[test]
+    fn variables_and_dots_are_taken_as_they_read() {
+        let tmp = tree("dots");
+        let home = tmp.0.to_string_lossy().to_string();
+        std::env::set_var("PANEX_COMPLETE_TEST", tmp.0.join("dev"));
+        let found = complete("$PANEX_COMPLETE_TEST/PanEx/../pap", "/", "/nowhere", false);
+        assert_eq!(paths(&found), [format!("{}/dev/paper/", home)]);
+
+        let found = complete("~/dev/PanEx/..", "/", &home, false);
+        assert_eq!(paths(&found), ["~/dev/"]);
+        let base = format!("{}/dev/PanEx", home);
+        assert_eq!(
+            resolve("src/../crates", &base, &home),
+            format!("{}/crates", base)
+        );
+        assert_eq!(
+            expand("$PANEX_UNSET_FOR_TEST/x", &home),
+            "$PANEX_UNSET_FOR_TEST/x"
+        );
+    }
+
+    #[test]
+    fn hidden_entries_need_showing_or_a_dot() {
+        let tmp = tree("hidden");
+        let home = tmp.0.to_string_lossy().to_string();
+        assert_eq!(
+            paths(&complete("~/", "/", &home, false)),
+            ["~/dev/", "~/docs/"]
+        );
+        assert_eq!(paths(&complete("~/", "/", &home, true)).len(), 4);
+        assert_eq!(paths(&complete("~/.p", "/", &home, false)), ["~/.profile"]);
+    }
+}
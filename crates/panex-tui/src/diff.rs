@@ -0,0 +1,147 @@
+//! The text diff viewer: two files, one line diff, shown unified or side by
+//! side. The diffing is panex-core's; this is the scrolling and the switch
+//! between the two layouts.
+
+use panex_core::diff::{side_by_side, DiffLine, LineChange};
+
+#[derive(PartialEq, Debug)]
+pub struct DiffView {
+    pub left_path: String,
+    pub right_path: String,
+    pub lines: Vec<DiffLine>,
+    /// Side-by-side rows, as indices into `lines`. Worked out once, up front,
+    /// since the layout can be switched back and forth.
+    pub rows: Vec<(Option<usize>, Option<usize>)>,
+    pub unified: bool,
+    /// First row on screen, in whichever layout is showing.
+    pub offset: usize,
+    /// Rows on screen as last drawn — what a page scroll moves by.
+    pub page: usize,
+}
+
+impl DiffView {
+    pub fn new(left_path: String, right_path: String, lines: Vec<DiffLine>) -> Self {
+        let rows = side_by_side(&lines);
+        DiffView {
+            left_path,
+            right_path,
+            lines,
+            rows,
+            unified: false,
+            offset: 0,
+            page: 10,
+        }
+    }
+
+    pub fn row_count(&self) -> usize {
+        if self.unified {
+            self.lines.len()
+        } else {
+            self.rows.len()
+        }
+    }
+
+    /// How many separate changes there are — runs of changed rows, counted
+    /// the way the `n`/`N` jumps step through them.
+    pub fn change_count(&self) -> usize {
+        (0..self.row_count())
+            .filter(|&row| self.starts_change(row))
+            .count()
+    }
+
+    fn is_change(&self, row: usize) -> bool {
+        let changed = |i: usize| self.lines[i].change != LineChange::Same;
+        if self.unified {
+            changed(row)
+        } else {
+            let (l, r) = self.rows[row];
+            l.or(r).is_some_and(changed)
+        }
+    }
+
+    fn starts_change(&self, row: usize) -> bool {
+        self.is_change(row) && (row == 0 || !self.is_change(row - 1))
+    }
+
+    pub fn scroll(&mut self, delta: isize) {
+        let last = self.row_count().saturating_sub(1);
+        self.offset = self.offset.saturating_add_signed(delta).min(last);
+    }
+
+    pub fn scroll_page(&mut self, pages: isize) {
+        self.scroll(pages * self.page.max(1) as isize);
+    }
+
+    /// Bring the next change to the top, if there is one below.
+    pub fn next_change(&mut self) -> bool {
+        match (self.offset + 1..self.row_count()).find(|&row| self.starts_change(row)) {
+            Some(row) => {
+                self.offset = row;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn previous_change(&mut self) -> bool {
+        match (0..self.offset).rev().find(|&row| self.starts_change(row)) {
+            Some(row) => {
+                self.offset = row;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Switch layouts and keep the same lines at the top of the screen.
+    pub fn toggle_layout(&mut self) {
+        if self.unified {
+            let line = self.offset;
+            self.offset = self
+                .rows
+                .iter()
+                .position(|&(l, r)| l.max(r).is_some_and(|i| i >= line))
+                .unwrap_or(0);
+        } else {
+            let (l, r) = self.rows.get(self.offset).copied().unwrap_or_default();
+            self.offset = l.or(r).unwrap_or(0);
+        }
+        self.unified = !self.unified;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use panex_core::diff::diff_lines;
+
+    fn view(left: &str, right: &str) -> DiffView {
+        DiffView::new("l".into(), "r".into(), diff_lines(left, right))
+    }
+
+    #[test]
+    fn n_steps_from_one_change_to_the_next() {
+        let mut v = view("a\nb\nc\nd\ne\n", "a\nB\nc\nd\nE\n");
+        assert_eq!(v.change_count(), 2);
+        assert!(v.next_change());
+        assert_eq!(v.offset, 1);
+        assert!(v.next_change());
+        assert_eq!(v.offset, 4);
+        assert!(!v.next_change(), "there is no third change");
+        assert!(v.previous_change());
+        assert_eq!(v.offset, 1);
+    }
+
+    /// The unified layout has a row per removed and per added line, so the
+    /// same text sits at a different row there — the switch follows it.
+    #[test]
+    fn switching_layout_keeps_the_place() {
+        let mut v = view("a\nb\nc\nd\n", "a\nB\nc\nd\n");
+        v.offset = 2; // side by side: the row for "c"
+        v.toggle_layout();
+        assert!(v.unified);
+        assert_eq!(v.lines[v.offset].text, "c");
+        v.toggle_layout();
+        assert_eq!(v.offset, 2);
+    }
+}
@@ -0,0 +1,196 @@
+//! Folder sizes, measured on request (`u`) on background threads, and kept
+//! by path and modification time so that coming back to a folder shows them
+//! at once.
+//!
+//! A folder's own modification time changes when something is added to it
+//! or taken out, not when a file further down grows, so a size can go stale
+//! under an unchanged time. Asking again always measures afresh.
+
+use panex_core::{DirSize, SizeOptions};
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The folders waiting to be measured, and how many threads are measuring.
+/// One lock for both, so a thread cannot decide there is nothing left just
+/// as a folder is queued for it.
+#[derive(Default)]
+struct Queue {
+    folders: VecDeque<String>,
+    workers: usize,
+}
+
+pub struct DirSizes {
+    options: SizeOptions,
+    /// Path to the modification time it was measured at, and the size.
+    cache: HashMap<String, (u64, u64)>,
+    /// Path to the modification time it was asked for at.
+    pending: HashMap<String, u64>,
+    queue: Arc<Mutex<Queue>>,
+    tx: Sender<(String, Result<DirSize, String>)>,
+    rx: Receiver<(String, Result<DirSize, String>)>,
+    /// How many finished, how many failed, and how many had parts that could
+    /// not be read, since the last time all of them were done — for the
+    /// status line.
+    finished: usize,
+    failed: usize,
+    partial: usize,
+}
+
+impl DirSizes {
+    pub fn new(options: SizeOptions) -> Self {
+        let (tx, rx) = mpsc::channel();
+        DirSizes {
+            options,
+            cache: HashMap::new(),
+            pending: HashMap::new(),
+            queue: Arc::default(),
+            tx,
+            rx,
+            finished: 0,
+            failed: 0,
+            partial: 0,
+        }
+    }
+
+    /// The size of `path` as measured at modification time `modified`.
+    pub fn lookup(&self, path: &str, modified: u64) -> Option<u64> {
+        match self.cache.get(path) {
+            Some(&(at, size)) if at == modified => Some(size),
+            _ => None,
+        }
+    }
+
+    pub fn is_pending(&self, path: &str) -> bool {
+        self.pending.contains_key(path)
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Queue each `(path, modified)` for measuring, unless it already is.
+    /// Returns how many were queued.
+    pub fn request(&mut self, folders: Vec<(String, u64)>) -> usize {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        let mut queued = 0;
+        for (path, modified) in folders {
+            if self.pending.contains_key(&path) {
+                continue;
+            }
+            self.pending.insert(path.clone(), modified);
+            queue.folders.push_back(path);
+            queued += 1;
+        }
+        let limit = thread::available_parallelism().map_or(4, |n| n.get()).min(8);
+        while queue.workers < limit && queue.workers < queue.folders.len() {
+            queue.workers += 1;
+            let (queue, tx, options) = (self.queue.clone(), self.tx.clone(), self.options);
+            thread::spawn(move || measure(&queue, &tx, options));
+        }
+        queued
+    }
+
+    /// Sizes on disk measured since the last call, now in the cache. Failures
+    /// are counted for `progress` and otherwise dropped: the folder keeps its
+    /// dash.
+    pub fn drain(&mut self) -> Vec<(String, u64)> {
+        let mut done = Vec::new();
+        for (path, result) in self.rx.try_iter() {
+            let Some(modified) = self.pending.remove(&path) else {
+                continue;
+            };
+            self.finished += 1;
+            match result {
+                Ok(size) => {
+                    if size.errors > 0 {
+                        self.partial += 1;
+                    }
+                    self.cache.insert(path.clone(), (modified, size.on_disk));
+                    done.push((path, size.on_disk));
+                }
+                Err(_) => self.failed += 1,
+            }
+        }
+        done
+    }
+
+    /// A line for the status bar: how far along, or, once all are done, what
+    /// came of it — after which the count starts again.
+    pub fn progress(&mut self) -> String {
+        if self.is_busy() {
+            let total = self.finished + self.pending.len();
+            return format!("Measuring folders… {}/{}", self.finished, total);
+        }
+        let mut line = format!("Measured {} folder(s)", self.finished - self.failed);
+        if self.failed > 0 {
+            line.push_str(&format!(", {} could not be read", self.failed));
+        }
+        if self.partial > 0 {
+            line.push_str(&format!(", {} only in part", self.partial));
+        }
+        self.finished = 0;
+        self.failed = 0;
+        self.partial = 0;
+        line
+    }
+}
+
+/// One worker: measure folders off the queue until it is empty.
+fn measure(
+    queue: &Mutex<Queue>,
+    tx: &Sender<(String, Result<DirSize, String>)>,
+    options: SizeOptions,
+) {
+    loop {
+        let path = {
+            let mut queue = queue.lock().unwrap_or_else(|e| e.into_inner());
+            match queue.folders.pop_front() {
+                Some(path) => path,
+                None => {
+                    queue.workers -= 1;
+                    return;
+                }
+            }
+        };
+        let result = panex_core::directory_size(&path, options);
+        if tx.send((path, result)).is_err() {
+            // The app is gone; nobody is waiting for the rest.
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::click_tests::TempDir;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn sizes_arrive_and_are_kept_by_modification_time() {
+        let tmp = TempDir::new("dirsize");
+        let root = &tmp.0;
+        std::fs::create_dir_all(root.join("a/b")).unwrap();
+        std::fs::write(root.join("a/b/data"), vec![1u8; 64 * 1024]).unwrap();
+        let path = root.join("a").to_string_lossy().to_string();
+
+        let mut sizes = DirSizes::new(SizeOptions::default());
+        assert_eq!(sizes.request(vec![(path.clone(), 7), (path.clone(), 7)]), 1);
+        assert!(sizes.is_pending(&path));
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut done = Vec::new();
+        while done.is_empty() && Instant::now() < deadline {
+            done = sizes.drain();
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(done.len(), 1);
+        assert!(done[0].1 >= 64 * 1024);
+        assert!(!sizes.is_busy());
+        assert_eq!(sizes.lookup(&path, 7), Some(done[0].1));
+        assert_eq!(sizes.lookup(&path, 8), None, "a changed folder is measured again");
+        assert_eq!(sizes.progress(), "Measured 1 folder(s)");
+    }
+}
@@ -0,0 +1,441 @@
+//! How each entry in the file list looks: its colour, from `$LS_COLORS` the
+//! way `ls` would use it, or from `[files.colors]` in the config, and a Nerd
+//! Font icon for its type.
+//!
+//! ```toml
+//! [files]
+//! icons = true         # false where the terminal font has no Nerd Font glyphs
+//! ls_colors = true     # false to ignore $LS_COLORS
+//!
+//! [files.colors]
+//! executable = "bold green"
+//! archive = "red"
+//! "*.rs" = "#dea584"
+//! ```
+//!
+//! Precedence, highest first: a `*.ext` from the config, a type from the
+//! config, `$LS_COLORS`, the built-in colours. The built-in colours for
+//! archives, images and so on step aside when `$LS_COLORS` is read, since
+//! it names its own extensions.
+
+use panex_core::config::FilesConfig;
+use panex_core::{FileEntry, FileKind};
+use ratatui::style::{Color, Modifier, Style};
+use std::collections::HashMap;
+
+use crate::theme::Theme;
+
+/// What an entry is for colouring: its kind on disk, and for a plain file
+/// what its extension says it holds.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Type {
+    Dir,
+    File,
+    Symlink,
+    BrokenSymlink,
+    Executable,
+    Socket,
+    Fifo,
+    Device,
+    Archive,
+    Image,
+    Audio,
+    Video,
+    Document,
+}
+
+const ARCHIVES: &[&str] = &[
+    "zip", "tar", "gz", "tgz", "bz2", "tbz", "xz", "txz", "zst", "7z", "rar", "lz", "lzma", "jar",
+    "deb", "rpm", "dmg", "iso",
+];
+const IMAGES: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "ico", "tif", "tiff", "heic", "avif",
+];
+const AUDIO: &[&str] = &["mp3", "flac", "wav", "ogg", "m4a", "opus", "aac", "aiff"];
+const VIDEO: &[&str] = &["mp4", "mkv", "mov", "avi", "webm", "m4v", "wmv", "flv"];
+const DOCUMENTS: &[&str] = &[
+    "pdf", "md", "txt", "rtf", "doc", "docx", "odt", "xls", "xlsx", "ods", "ppt", "pptx", "epub",
+];
+
+impl Type {
+    /// The names `[files.colors]` accepts for each type.
+    const NAMES: [(&'static str, Type); 13] = [
+        ("dir", Type::Dir),
+        ("file", Type::File),
+        ("symlink", Type::Symlink),
+        ("broken-symlink", Type::BrokenSymlink),
+        ("executable", Type::Executable),
+        ("socket", Type::Socket),
+        ("fifo", Type::Fifo),
+        ("device", Type::Device),
+        ("archive", Type::Archive),
+        ("image", Type::Image),
+        ("audio", Type::Audio),
+        ("video", Type::Video),
+        ("document", Type::Document),
+    ];
+
+    pub fn of(entry: &FileEntry) -> Type {
+        match entry.kind {
+            FileKind::Dir => Type::Dir,
+            FileKind::Symlink => Type::Symlink,
+            FileKind::BrokenSymlink => Type::BrokenSymlink,
+            FileKind::Executable => Type::Executable,
+            FileKind::Socket => Type::Socket,
+            FileKind::Fifo => Type::Fifo,
+            FileKind::Device => Type::Device,
+            FileKind::File => {
+                let Some((_, ext)) = entry.name.rsplit_once('.') else {
+                    return Type::File;
+                };
+                let ext = ext.to_lowercase();
+                let is = |list: &[&str]| list.contains(&ext.as_str());
+                if is(ARCHIVES) {
+                    Type::Archive
+                } else if is(IMAGES) {
+                    Type::Image
+                } else if is(AUDIO) {
+                    Type::Audio
+                } else if is(VIDEO) {
+                    Type::Video
+                } else if is(DOCUMENTS) {
+                    Type::Document
+                } else {
+                    Type::File
+                }
+            }
+        }
+    }
+
+    /// Whether the type is read from the name rather than from the disk —
+    /// the ones a `*.ext` pattern may override.
+    fn by_extension(self) -> bool {
+        matches!(
+            self,
+            Type::File | Type::Archive | Type::Image | Type::Audio | Type::Video | Type::Document
+        )
+    }
+
+    /// The Nerd Font glyph drawn before the name.
+    pub fn icon(self) -> &'static str {
+        match self {
+            Type::Dir => "\u{f07b}",
+            Type::File => "\u{f016}",
+            Type::Symlink => "\u{f0c1}",
+            Type::BrokenSymlink => "\u{f127}",
+            Type::Executable => "\u{f489}",
+            Type::Socket => "\u{f1e6}",
+            Type::Fifo => "\u{f0ec}",
+            Type::Device => "\u{f0a0}",
+            Type::Archive => "\u{f1c6}",
+            Type::Image => "\u{f1c5}",
+            Type::Audio => "\u{f1c7}",
+            Type::Video => "\u{f1c8}",
+            Type::Document => "\u{f15c}",
+        }
+    }
+}
+
+/// The styles, built once at startup from the environment and the config.
+#[derive(Debug, Default)]
+pub struct FileStyles {
+    pub icons: bool,
+    types: HashMap<Type, Style>,
+    /// `*.ext` patterns from the config, lower-cased, with the dot.
+    config_suffixes: Vec<(String, Style)>,
+    /// Suffix patterns from `$LS_COLORS`, lower-cased.
+    ls_suffixes: Vec<(String, Style)>,
+    /// What in `[files]` could not be used, for reporting at startup.
+    pub problems: Vec<String>,
+}
+
+impl FileStyles {
+    pub fn load(config: &FilesConfig, theme: &Theme) -> Self {
+        let ls_colors = match config.ls_colors {
+            Some(false) => None,
+            _ => std::env::var("LS_COLORS").ok().filter(|v| !v.is_empty()),
+        };
+        FileStyles::build(config, theme, ls_colors.as_deref())
+    }
+
+    fn build(config: &FilesConfig, theme: &Theme, ls_colors: Option<&str>) -> Self {
+        let mut styles = FileStyles {
+            icons: config.icons.unwrap_or(true),
+            ..FileStyles::default()
+        };
+        styles
+            .types
+            .insert(Type::Dir, Style::default().fg(theme.directory).add_modifier(Modifier::BOLD));
+        // Without colour a folder is still told apart by its weight, as it
+        // was before there were types; everything else is left plain.
+        if theme.no_color {
+            return styles;
+        }
+
+        let fg = |color| Style::default().fg(color);
+        for (ty, style) in [
+            (Type::Symlink, fg(Color::Cyan)),
+            (Type::BrokenSymlink, fg(Color::Red)),
+            (Type::Executable, fg(Color::Green).add_modifier(Modifier::BOLD)),
+            (Type::Socket, fg(Color::Magenta)),
+            (Type::Fifo, fg(Color::Yellow)),
+            (Type::Device, fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+        ] {
+            styles.types.insert(ty, style);
+        }
+        match ls_colors {
+            Some(spec) => styles.apply_ls_colors(spec),
+            None => {
+                for (ty, color) in [
+                    (Type::Archive, Color::Red),
+                    (Type::Image, Color::Magenta),
+                    (Type::Audio, Color::Cyan),
+                    (Type::Video, Color::Magenta),
+                ] {
+                    styles.types.insert(ty, fg(color));
+                }
+            }
+        }
+
+        for (key, value) in &config.colors {
+            let Some(style) = parse_style(value) else {
+                styles
+                    .problems
+                    .push(format!("[files] {}: '{}' is not a colour", key, value));
+                continue;
+            };
+            if let Some(ext) = key.strip_prefix('*') {
+                styles.config_suffixes.push((ext.to_lowercase(), style));
+                continue;
+            }
+            let name = key.replace('_', "-");
+            match Type::NAMES.iter().find(|(n, _)| *n == name) {
+                Some((_, ty)) => {
+                    styles.types.insert(*ty, style);
+                }
+                None => styles.problems.push(format!(
+                    "[files] unknown type '{}' (use a type or \"*.ext\")",
+                    key
+                )),
+            }
+        }
+        styles
+    }
+
+    /// Lay `$LS_COLORS` over the built-in types. The parts this list has no
+    /// place for (setuid, sticky, …) and the ones that do not parse are
+    /// passed over, as `ls` would — it is the shell's setting, not ours.
+    fn apply_ls_colors(&mut self, spec: &str) {
+        for part in spec.split(':') {
+            let Some((key, codes)) = part.split_once('=') else {
+                continue;
+            };
+            // `ln=target` colours a link as what it points to, which would
+            // cost a stat per row; it keeps the plain link colour instead.
+            let Some(style) = sgr_style(codes) else {
+                continue;
+            };
+            if let Some(suffix) = key.strip_prefix('*') {
+                self.ls_suffixes.push((suffix.to_lowercase(), style));
+                continue;
+            }
+            let ty = match key {
+                "di" => Type::Dir,
+                "fi" => Type::File,
+                "ln" => Type::Symlink,
+                "or" => Type::BrokenSymlink,
+                "ex" => Type::Executable,
+                "so" => Type::Socket,
+                "pi" => Type::Fifo,
+                "bd" | "cd" => Type::Device,
+                _ => continue,
+            };
+            self.types.insert(ty, style);
+        }
+    }
+
+    /// The style for `entry`'s name, before the row's own focus and
+    /// selection are laid over it.
+    pub fn style(&self, entry: &FileEntry, ty: Type) -> Style {
+        if ty.by_extension() {
+            let name = entry.name.to_lowercase();
+            let suffix = |list: &[(String, Style)]| {
+                list.iter()
+                    .find(|(suffix, _)| name.ends_with(suffix.as_str()))
+                    .map(|(_, style)| *style)
+            };
+            if let Some(style) = suffix(&self.config_suffixes) {
+                return style;
+            }
+            if ty != Type::File {
+                if let Some(style) = self.types.get(&ty) {
+                    return *style;
+                }
+            }
+            if let Some(style) = suffix(&self.ls_suffixes) {
+                return style;
+            }
+            return self.types.get(&Type::File).copied().unwrap_or_default();
+        }
+        self.types.get(&ty).copied().unwrap_or_default()
+    }
+}
+
+/// A style as written in the config: a colour, with any of `bold`, `dim`,
+/// `italic` and `underline` before it, e.g. `"bold green"`.
+fn parse_style(value: &str) -> Option<Style> {
+    let mut style = Style::default();
+    let mut words = value.split_whitespace().peekable();
+    while let Some(word) = words.next() {
+        style = match word {
+            "bold" => style.add_modifier(Modifier::BOLD),
+            "dim" => style.add_modifier(Modifier::DIM),
+            "italic" => style.add_modifier(Modifier::ITALIC),
+            "underline" | "underlined" => style.add_modifier(Modifier::UNDERLINED),
+            color if words.peek().is_none() => style.fg(color.parse::<Color>().ok()?),
+            _ => return None,
+        };
+    }
+    (style != Style::default()).then_some(style)
+}
+
+/// The SGR codes of one `$LS_COLORS` entry, e.g. `01;38;5;208`, as a style.
+/// `None` when they do not parse.
+fn sgr_style(codes: &str) -> Option<Style> {
+    let numbers: Vec<u16> = codes
+        .split(';')
+        .map(|c| if c.is_empty() { Ok(0) } else { c.parse() })
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let mut style = Style::default();
+    let mut it = numbers.into_iter();
+    while let Some(code) = it.next() {
+        style = match code {
+            0 => Style::default(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            30..=37 => style.fg(Color::Indexed((code - 30) as u8)),
+            90..=97 => style.fg(Color::Indexed((code - 90 + 8) as u8)),
+            40..=47 => style.bg(Color::Indexed((code - 40) as u8)),
+            100..=107 => style.bg(Color::Indexed((code - 100 + 8) as u8)),
+            38 | 48 => {
+                let color = match it.next()? {
+                    5 => Color::Indexed(u8::try_from(it.next()?).ok()?),
+                    2 => Color::Rgb(
+                        u8::try_from(it.next()?).ok()?,
+                        u8::try_from(it.next()?).ok()?,
+                        u8::try_from(it.next()?).ok()?,
+                    ),
+                    _ => return None,
+                };
+                if code == 38 {
+                    style.fg(color)
+                } else {
+                    style.bg(color)
+                }
+            }
+            _ => style,
+        };
+    }
+    Some(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, kind: FileKind) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            path: format!("/x/{}", name),
+            is_dir: kind == FileKind::Dir,
+            size: 0,
+            modified: 0,
+            kind,
+            created: 0,
+            mode: 0,
+            uid: 0,
+            items: None,
+            contents_size: None,
+            git: None,
+        }
+    }
+
+    fn styles(colors: &[(&str, &str)], ls_colors: Option<&str>) -> FileStyles {
+        let config = FilesConfig {
+            colors: colors.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            ..FilesConfig::default()
+        };
+        FileStyles::build(&config, &Theme::default(), ls_colors)
+    }
+
+    #[test]
+    fn types_come_from_the_kind_and_then_the_extension() {
+        assert_eq!(Type::of(&entry("a.tar.gz", FileKind::File)), Type::Archive);
+        assert_eq!(Type::of(&entry("Photo.JPG", FileKind::File)), Type::Image);
+        assert_eq!(Type::of(&entry("run.sh", FileKind::Executable)), Type::Executable);
+        assert_eq!(Type::of(&entry("photos.png", FileKind::Dir)), Type::Dir);
+        assert_eq!(Type::of(&entry("Makefile", FileKind::File)), Type::File);
+    }
+
+    #[test]
+    fn sgr_codes_parse_to_styles() {
+        assert_eq!(
+            sgr_style("01;34"),
+            Some(Style::default().fg(Color::Indexed(4)).add_modifier(Modifier::BOLD))
+        );
+        assert_eq!(sgr_style("38;5;208"), Some(Style::default().fg(Color::Indexed(208))));
+        assert_eq!(sgr_style("38;2;1;2;3"), Some(Style::default().fg(Color::Rgb(1, 2, 3))));
+        assert_eq!(sgr_style("38;5"), None);
+        assert_eq!(sgr_style("target"), None);
+    }
+
+    #[test]
+    fn ls_colors_replace_the_built_in_extensions() {
+        let styles = styles(&[], Some("di=01;34:ex=31:*.zip=33:ln=target"));
+        let zip = entry("a.zip", FileKind::File);
+        assert_eq!(styles.style(&zip, Type::of(&zip)).fg, Some(Color::Indexed(3)));
+        // Not named by LS_COLORS, and the built-in red for archives is gone.
+        let tar = entry("a.tar", FileKind::File);
+        assert_eq!(styles.style(&tar, Type::of(&tar)), Style::default());
+        let exe = entry("run", FileKind::Executable);
+        assert_eq!(styles.style(&exe, Type::Executable).fg, Some(Color::Indexed(1)));
+        // `ln=target` is passed over, leaving the built-in link colour.
+        let link = entry("l", FileKind::Symlink);
+        assert_eq!(styles.style(&link, Type::Symlink).fg, Some(Color::Cyan));
+    }
+
+    #[test]
+    fn the_config_wins_over_ls_colors() {
+        let styles = styles(
+            &[("archive", "green"), ("*.rs", "bold #dea584"), ("executable", "blue")],
+            Some("*.zip=33:*.rs=31:ex=31"),
+        );
+        let zip = entry("a.zip", FileKind::File);
+        assert_eq!(styles.style(&zip, Type::of(&zip)).fg, Some(Color::Green));
+        let rs = entry("main.rs", FileKind::File);
+        assert_eq!(
+            styles.style(&rs, Type::of(&rs)),
+            Style::default().fg(Color::Rgb(0xde, 0xa5, 0x84)).add_modifier(Modifier::BOLD)
+        );
+        // A pattern does not recolour an executable, as in `ls`.
+        let exe = entry("build.rs", FileKind::Executable);
+        assert_eq!(styles.style(&exe, Type::Executable).fg, Some(Color::Blue));
+    }
+
+    #[test]
+    fn bad_entries_are_reported() {
+        let styles = styles(&[("sockets", "red"), ("image", "bold nope")], None);
+        assert_eq!(
+            styles.problems,
+            [
+                "[files] image: 'bold nope' is not a colour",
+                "[files] unknown type 'sockets' (use a type or \"*.ext\")",
+            ]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Git status for the panes, read on background threads. `git status` in a
+//! big checkout takes long enough to feel in a keypress, so a folder shows
+//! what was last known of its repository at once and is brought up to date
+//! when the fresh answer comes in.
+
+use panex_core::git::{GitStatus, RepoStatus};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+type Answer = (String, Result<Option<RepoStatus>, String>);
+
+pub struct GitStatuses {
+    enabled: bool,
+    /// Each folder asked about, and the root of the repository it is in —
+    /// `None` when it is in none.
+    roots: HashMap<String, Option<String>>,
+    /// By root.
+    repos: HashMap<String, RepoStatus>,
+    /// Folders being asked about.
+    pending: HashSet<String>,
+    tx: Sender<Answer>,
+    rx: Receiver<Answer>,
+}
+
+impl GitStatuses {
+    pub fn new(enabled: bool) -> Self {
+        let (tx, rx) = mpsc::channel();
+        GitStatuses {
+            enabled,
+            roots: HashMap::new(),
+            repos: HashMap::new(),
+            pending: HashSet::new(),
+            tx,
+            rx,
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Ask afresh about the repository `dir` is in, unless that is already
+    /// being asked.
+    pub fn request(&mut self, dir: &str) {
+        if !self.enabled || !self.pending.insert(dir.to_string()) {
+            return;
+        }
+        let (dir, tx) = (dir.to_string(), self.tx.clone());
+        thread::spawn(move || {
+            let result = panex_core::git::status(&dir);
+            let _ = tx.send((dir, result));
+        });
+    }
+
+    /// Take in the answers that have come since the last call. Returns true
+    /// if any did. A folder git could not be run in counts as outside any
+    /// repository: there is nothing to show for it either way.
+    pub fn drain(&mut self) -> bool {
+        let mut changed = false;
+        for (dir, result) in self.rx.try_iter() {
+            self.pending.remove(&dir);
+            changed = true;
+            match result {
+                Ok(Some(repo)) => {
+                    self.roots.insert(dir, Some(repo.root.clone()));
+                    self.repos.insert(repo.root.clone(), repo);
+                }
+                Ok(None) | Err(_) => {
+                    self.roots.insert(dir, None);
+                }
+            }
+        }
+        changed
+    }
+
+    /// The repository `dir` is in, as last known. A folder not asked about
+    /// yet takes the innermost known repository around it.
+    pub fn repo(&self, dir: &str) -> Option<&RepoStatus> {
+        match self.roots.get(dir) {
+            Some(root) => root.as_ref().and_then(|root| self.repos.get(root)),
+            None => self
+                .repos
+                .values()
+                .filter(|repo| repo.contains(dir))
+                .max_by_key(|repo| repo.root.len()),
+        }
+    }
+
+    pub fn status_of(&self, dir: &str, path: &str) -> Option<GitStatus> {
+        self.repo(dir).and_then(|repo| repo.status_of(path))
+    }
+}
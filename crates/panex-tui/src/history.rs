@@ -0,0 +1,159 @@
+//! Where the panes have been: each pane's back and forward stacks, and the
+//! folders visited lately across all of them.
+//!
+//! The stacks live with their pane and go when it does. The recent list
+//! outlives the run — `~/.panex/recent.json`, written on quit beside the
+//! session — since "that folder from yesterday" is what it is for.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// How far back a pane remembers.
+const HISTORY_LIMIT: usize = 100;
+
+/// How many folders the recent list keeps.
+const RECENT_LIMIT: usize = 50;
+
+/// A folder as it was left: where, and what the cursor was on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Visit {
+    pub path: String,
+    /// The entry under the cursor, by path — its row may have moved by the
+    /// time the folder is come back to.
+    pub focused: Option<String>,
+    /// The cursor's row, for when that entry has gone.
+    pub focus_index: i32,
+}
+
+#[derive(Default)]
+pub struct History {
+    back: Vec<Visit>,
+    forward: Vec<Visit>,
+}
+
+impl History {
+    /// Record leaving `from` for somewhere new. What was ahead is dropped,
+    /// as a browser drops it: it was ahead of a place no longer on the way.
+    pub fn leave(&mut self, from: Visit) {
+        if self.back.last().map(|v| &v.path) != Some(&from.path) {
+            self.back.push(from);
+        }
+        if self.back.len() > HISTORY_LIMIT {
+            self.back.remove(0);
+        }
+        self.forward.clear();
+    }
+
+    /// Step back from `here`, or forward, to the nearest visit `usable`
+    /// allows, dropping those it does not. `here` becomes the first step the
+    /// other way — unless there is nowhere to go, when nothing changes but
+    /// the dropping.
+    pub fn step(
+        &mut self,
+        forward: bool,
+        here: Visit,
+        usable: impl Fn(&Visit) -> bool,
+    ) -> Option<Visit> {
+        let (from, to) = if forward {
+            (&mut self.forward, &mut self.back)
+        } else {
+            (&mut self.back, &mut self.forward)
+        };
+        while let Some(visit) = from.pop() {
+            if usable(&visit) {
+                to.push(here);
+                return Some(visit);
+            }
+        }
+        None
+    }
+}
+
+/// The folders visited lately, the latest first.
+pub struct Recent {
+    pub paths: Vec<String>,
+}
+
+impl Recent {
+    pub fn path() -> Result<PathBuf, String> {
+        let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+        Ok(home.join(".panex").join("recent.json"))
+    }
+
+    /// The saved list. A missing or unreadable file is an empty list, the
+    /// same way a session is.
+    pub fn load() -> Self {
+        let paths = Self::path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Recent { paths }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&self.paths)
+            .map_err(|e| format!("Failed to serialize recent folders: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write recent folders: {}", e))
+    }
+
+    /// Put `path` at the top, once.
+    pub fn visit(&mut self, path: &str) {
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_string());
+        self.paths.truncate(RECENT_LIMIT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(path: &str) -> Visit {
+        Visit {
+            path: path.to_string(),
+            focused: None,
+            focus_index: 0,
+        }
+    }
+
+    #[test]
+    fn going_somewhere_new_drops_what_was_ahead() {
+        let mut history = History::default();
+        let any = |_: &Visit| true;
+        history.leave(at("/a"));
+        history.leave(at("/b"));
+        assert_eq!(history.step(false, at("/c"), any), Some(at("/b")));
+        assert_eq!(history.step(false, at("/b"), any), Some(at("/a")));
+        assert_eq!(history.step(false, at("/a"), any), None);
+        assert_eq!(history.step(true, at("/a"), any), Some(at("/b")));
+
+        history.leave(at("/b"));
+        assert_eq!(history.step(true, at("/d"), any), None);
+        assert_eq!(history.step(false, at("/d"), any), Some(at("/b")));
+    }
+
+    #[test]
+    fn a_folder_that_is_gone_is_stepped_over() {
+        let mut history = History::default();
+        history.leave(at("/a"));
+        history.leave(at("/gone"));
+        let to = history.step(false, at("/b"), |v| v.path != "/gone");
+        assert_eq!(to, Some(at("/a")));
+        assert_eq!(history.step(true, at("/a"), |_| true), Some(at("/b")));
+    }
+
+    #[test]
+    fn a_folder_visited_again_moves_to_the_top() {
+        let mut recent = Recent { paths: Vec::new() };
+        for path in ["/a", "/b", "/a"] {
+            recent.visit(path);
+        }
+        assert_eq!(recent.paths, ["/a", "/b"]);
+    }
+}
@@ -1,10 +1,14 @@
 use std::time::{Duration, Instant};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use panex_core::config::{Opener, RunIn};
+use ratatui::layout::Rect;
 
-use crate::app::{App, AppMode, ClipMode, ConfirmAction, FileClipboard, PromptAction};
+use crate::app::{App, AppMode, ClipMode, ConfirmAction, FileClipboard, PromptAction, Suspend};
+use crate::keys::{self, Action, Key, Lookup};
+use crate::palette::{Entry, Palette};
 use crate::layout::{self, SplitDirection, collect_leaf_ids, count_leaves};
-use crate::sort::apply_sort_and_filter;
+use crate::sort::{SortDirection, SortField};
 
 /// How long after a click a second one on the same row still counts as a
 /// double click. Matches the macOS default; slower than this reads as two
@@ -25,125 +29,200 @@ pub fn handle_key_event(app: &mut App, key: KeyEvent) {
         AppMode::Prompt { .. } => handle_prompt(app, key),
         AppMode::PathEdit { .. } => handle_path_edit(app, key),
         AppMode::FavoritesList { .. } => handle_favorites_list(app, key),
+        AppMode::Mark { .. } => handle_mark(app, key),
+        AppMode::RecentList { .. } => handle_recent_list(app, key),
+        AppMode::Jump { .. } => handle_jump(app, key),
+        AppMode::OpenWith { .. } => handle_open_with(app, key),
+        AppMode::SyncPlan { .. } => handle_sync_plan(app, key),
+        AppMode::Diff { .. } => handle_diff(app, key),
+        AppMode::BulkRename { .. } => handle_bulk_rename(app, key),
+        AppMode::LayoutPicker { .. } => handle_layout_picker(app, key),
+        AppMode::Palette { .. } => handle_palette(app, key),
+        AppMode::Usage => handle_usage(app, key),
+        AppMode::GitLog { .. } => handle_git_log(app, key),
     }
 }
 
 fn handle_normal(app: &mut App, key: KeyEvent) {
-    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
-    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
-
-    // The card pane has its own small keyboard. Beyond that only the keys
-    // that act on panes get through — the rest would act on the directory it
-    // carries for splitting, which it never draws, so the effect is invisible.
-    if app.oko_pane_id.as_deref() == Some(app.active_pane_id.as_str())
-        && (handle_oko_keys(app, key.code) || !acts_on_panes(key.code))
-    {
+    // The card pane has its own small keyboard, checked before the keymap so
+    // that `j`/`k` move between cards whatever the file list binds them to.
+    let on_oko = app.oko_pane_id.as_deref() == Some(app.active_pane_id.as_str());
+    if on_oko && app.pending_keys.is_empty() && handle_oko_keys(app, key.code) {
         return;
     }
 
-    match key.code {
-        // Quit
-        KeyCode::Char('q') if !ctrl => {
-            app.should_quit = true;
+    // Esc abandons a half-typed sequence rather than acting on its own.
+    if !app.pending_keys.is_empty() && key.code == KeyCode::Esc {
+        app.pending_keys.clear();
+        return;
+    }
+    app.pending_keys.push(Key::from_event(key));
+    let action = match app.keymap.lookup(&app.pending_keys) {
+        Lookup::Action(action) => action,
+        Lookup::Pending => {
+            app.set_status(format!("{} …", keys::describe(&app.pending_keys)));
+            return;
         }
-        KeyCode::Char('c') if ctrl => {
-            // Ctrl+C = copy to clipboard (not quit)
-            copy_to_clipboard(app, ClipMode::Copy);
+        Lookup::Unbound => {
+            app.pending_keys.clear();
+            return;
         }
+    };
+    app.pending_keys.clear();
+
+    // Beyond its own keys, only what acts on panes gets through to the card
+    // pane — the rest would act on the directory it carries for splitting,
+    // which it never draws, so the effect is invisible.
+    if on_oko && !action.acts_on_panes() {
+        return;
+    }
+    run_action(app, action);
+}
+
+fn run_action(app: &mut App, action: Action) {
+    match action {
+        Action::Quit => app.should_quit = true,
 
         // Navigation
-        KeyCode::Up | KeyCode::Char('k') if shift => {
+        Action::Up => move_focus(app, -1, Ends::Wrap),
+        Action::Down => move_focus(app, 1, Ends::Wrap),
+        Action::SelectUp => {
             move_focus(app, -1, Ends::Stop);
             toggle_selection_at_focus(app);
         }
-        KeyCode::Down | KeyCode::Char('j') if shift => {
+        Action::SelectDown => {
             move_focus(app, 1, Ends::Stop);
             toggle_selection_at_focus(app);
         }
-        KeyCode::Up | KeyCode::Char('k') => move_focus(app, -1, Ends::Wrap),
-        KeyCode::Down | KeyCode::Char('j') => move_focus(app, 1, Ends::Wrap),
-        KeyCode::PageUp => page_move(app, -1),
-        KeyCode::PageDown => page_move(app, 1),
-        KeyCode::Char('g') => focus_to(app, 0),
-        KeyCode::Char('G') => focus_to(app, i32::MAX),
-        KeyCode::Enter => open_focused(app),
-        KeyCode::Backspace => navigate_up(app),
-        KeyCode::Home | KeyCode::Char('~') => {
+        Action::PageUp => page_move(app, -1),
+        Action::PageDown => page_move(app, 1),
+        Action::Top => focus_to(app, 0),
+        Action::Bottom => focus_to(app, i32::MAX),
+        Action::Open => open_focused(app),
+        Action::Parent => navigate_up(app),
+        Action::Back | Action::Forward => {
+            let pane_id = app.active_pane_id.clone();
+            let forward = action == Action::Forward;
+            if !app.go_back(&pane_id, forward) && app.status_message.is_none() {
+                let way = if forward { "forward" } else { "back" };
+                app.set_status(format!("Nothing further {}", way));
+            }
+        }
+        Action::Recent => open_recent_list(app),
+        Action::Jump => {
+            let pane_id = app.active_pane_id.clone();
+            let matches = jump_matches(app, "");
+            app.mode = AppMode::Jump {
+                pane_id,
+                input: String::new(),
+                matches,
+                selected: 0,
+            };
+        }
+        Action::Home => {
             let home = app.home_path.clone();
             let pane_id = app.active_pane_id.clone();
             app.navigate_to(&pane_id, &home);
         }
 
-        // Refresh active pane
-        KeyCode::F(5) => {
+        Action::Refresh => {
             let pane_id = app.active_pane_id.clone();
             app.refresh_pane(&pane_id);
         }
+        Action::DirSizes => measure_folders(app),
+        Action::DiskUsage => open_disk_usage(app),
 
         // Pane management
-        KeyCode::Char('|') => split_active_pane(app, SplitDirection::Vertical),
-        KeyCode::Char('_') => split_active_pane(app, SplitDirection::Horizontal),
-        KeyCode::Char('W') => close_active_pane(app),
-        KeyCode::Tab => cycle_pane(app),
-
-        // Card view. Bound only when a usable oko is on PATH — otherwise this
-        // falls through unhandled, which is the point: no binding, no entry
-        // in the help overlay, nothing that looks broken when pressed.
-        KeyCode::Char('O') if app.oko_available => toggle_oko_pane(app),
+        Action::SplitVertical => split_active_pane(app, SplitDirection::Vertical),
+        Action::SplitHorizontal => split_active_pane(app, SplitDirection::Horizontal),
+        Action::ClosePane => close_active_pane(app),
+        Action::NextPane => cycle_pane(app),
+
+        // Card view. Does nothing unless a usable oko is on PATH — and is
+        // left out of the help overlay then too, so nothing looks broken.
+        Action::Oko => {
+            if app.oko_available {
+                toggle_oko_pane(app);
+            }
+        }
 
-        // Pane size — '=' is the unshifted twin of '+'
-        KeyCode::Char('+') | KeyCode::Char('=') if !ctrl => resize_active_pane(app, 1),
-        KeyCode::Char('-') if !ctrl => resize_active_pane(app, -1),
+        Action::Grow => resize_active_pane(app, 1),
+        Action::Shrink => resize_active_pane(app, -1),
 
         // File operations
-        KeyCode::Char('y') => copy_to_clipboard(app, ClipMode::Copy),
-        KeyCode::Char('x') => copy_to_clipboard(app, ClipMode::Cut),
-        KeyCode::Char('p') => paste_clipboard(app),
-        KeyCode::Char('v') if ctrl => paste_clipboard(app),
-        KeyCode::Char('r') | KeyCode::F(2) => start_rename(app),
-        KeyCode::Char('d') | KeyCode::Delete => start_delete(app),
-        KeyCode::Char('o') => open_in_default_app(app),
-        KeyCode::Char('t') => open_in_terminal(app),
-        KeyCode::Char('n') => start_new_file(app),
-        KeyCode::Char('N') => start_new_folder(app),
-        KeyCode::Char('a') if ctrl => select_all(app),
-        KeyCode::Esc => deselect_all(app),
-
-        // Help overlay
-        KeyCode::Char('?') => {
-            app.mode = AppMode::Help;
-        }
-
-        // Search
-        KeyCode::Char('/') => {
-            let pane_id = app.active_pane_id.clone();
-            app.mode = AppMode::Search { pane_id };
+        Action::Copy => copy_to_clipboard(app, ClipMode::Copy),
+        Action::Cut => copy_to_clipboard(app, ClipMode::Cut),
+        Action::Paste => paste_clipboard(app),
+        Action::Rename => start_rename(app),
+        Action::BulkRename => start_bulk_rename(app),
+        Action::EditNames => start_edit_names(app),
+        Action::Delete => start_delete(app),
+        Action::OpenDefault => open_in_default_app(app),
+        Action::OpenWith => start_open_with(app),
+        Action::OpenTerminal => open_in_terminal(app),
+        Action::Edit => open_here(app, &editor_command()),
+        Action::Page => open_here(app, &pager_command()),
+        Action::Shell => start_shell(app),
+        Action::GitStage => git_apply(app, panex_core::git::stage, "Staged"),
+        Action::GitUnstage => git_apply(app, panex_core::git::unstage, "Unstaged"),
+        Action::GitDiscard => start_git_discard(app),
+        Action::GitDiff => git_diff(app),
+        Action::GitLog => git_log(app),
+        Action::NewFile => start_new_file(app),
+        Action::NewFolder => start_new_folder(app),
+        Action::SelectAll => select_all(app),
+        Action::Deselect => deselect_all(app),
+
+        Action::Help => app.mode = AppMode::Help,
+        Action::Palette => {
+            app.mode = AppMode::Palette {
+                state: Palette::new(&app.recent_commands),
+            };
         }
-        KeyCode::Char('f') if ctrl => {
+
+        Action::Search => {
             let pane_id = app.active_pane_id.clone();
             app.mode = AppMode::Search { pane_id };
         }
 
-        // Sort
-        KeyCode::Char('s') if !shift => {
-            app.sort_field = app.sort_field.cycle();
+        Action::SortField => {
+            let columns = app.pane_columns(&app.active_pane_id);
+            app.sort_field = crate::columns::next_sort_field(columns, app.sort_field);
             refilter_all_panes(app);
         }
-        KeyCode::Char('S') => {
+        Action::SortDirection => {
             app.sort_direction = app.sort_direction.toggle();
             refilter_all_panes(app);
         }
 
-        // Hidden files
-        KeyCode::Char('.') => {
+        // Compare with the neighbouring pane, and sync what differs
+        Action::Compare => toggle_compare(app, false),
+        Action::CompareContent => toggle_compare(app, true),
+        Action::Sync => start_sync(app),
+        Action::Diff => start_diff(app),
+        Action::Layouts => {
+            if app.config.layouts.is_empty() {
+                app.set_status("No layouts — add [layouts.NAME] to ~/.panex/config.toml".to_string());
+            } else {
+                app.mode = AppMode::LayoutPicker { selected: 0 };
+            }
+        }
+
+        Action::ToggleHidden => {
             app.show_hidden = !app.show_hidden;
             refilter_all_panes(app);
         }
+        Action::ToggleIgnored => {
+            app.hide_ignored = !app.hide_ignored;
+            refilter_all_panes(app);
+            let state = if app.hide_ignored { "hidden" } else { "shown" };
+            app.set_status(format!("Git-ignored files {}", state));
+        }
 
         // Path edit — show favorites list first if any exist
-        KeyCode::Char('e') => {
+        Action::EditPath => {
             let pane_id = app.active_pane_id.clone();
-            if !app.config.favorites.paths.is_empty() {
+            if !app.config.favorites.entries.is_empty() {
                 app.mode = AppMode::FavoritesList {
                     pane_id,
                     selected: 0,
@@ -154,19 +233,18 @@ fn handle_normal(app: &mut App, key: KeyEvent) {
                     .get(&pane_id)
                     .map(|p| p.current_path.clone())
                     .unwrap_or_default();
-                app.mode = AppMode::PathEdit {
-                    pane_id,
-                    input: path.clone(),
-                    cursor: path.len(),
-                    completions: Vec::new(),
-                    completion_index: None,
-                    completion_prefix: String::new(),
-                };
+                open_path_edit(app, pane_id, path);
             }
         }
 
-        // Toggle current path as favorite
-        KeyCode::Char('f') if !ctrl => {
+        Action::Mark | Action::GotoMark => {
+            app.mode = AppMode::Mark {
+                pane_id: app.active_pane_id.clone(),
+                set: action == Action::Mark,
+            };
+        }
+
+        Action::ToggleFavorite => {
             let current_path = app
                 .pane_map
                 .get(&app.active_pane_id)
@@ -178,8 +256,6 @@ fn handle_normal(app: &mut App, key: KeyEvent) {
                 Err(e) => app.set_status(format!("Favorite error: {}", e)),
             }
         }
-
-        _ => {}
     }
 }
 
@@ -318,32 +394,250 @@ fn start_tab_rename(app: &mut App) {
     };
 }
 
-fn report_creation(app: &mut App, created: Result<(), String>, name: &str) {
-    match created {
-        Ok(()) => {
-            app.set_status(format!("Created {}", name));
-            let pane_id = app.active_pane_id.clone();
-            app.refresh_pane(&pane_id);
+/// Say what was made, and put the cursor on the first of it — or, when that
+/// is nested, on the folder here that leads to it.
+fn report_creation(app: &mut App, dir: &str, (created, errors): (Vec<String>, Vec<String>)) {
+    let pane_id = app.active_pane_id.clone();
+    if !created.is_empty() {
+        app.refresh_pane(&pane_id);
+        let first = std::path::Path::new(&created[0]);
+        let here = first
+            .strip_prefix(dir)
+            .ok()
+            .and_then(|rel| rel.components().next())
+            .map(|top| std::path::Path::new(dir).join(top));
+        if let Some(here) = here {
+            app.focus_path(&pane_id, &here.to_string_lossy());
+        }
+    }
+    let names: Vec<String> = created
+        .iter()
+        .map(|p| {
+            std::path::Path::new(p)
+                .strip_prefix(dir)
+                .map(|rel| rel.to_string_lossy().to_string())
+                .unwrap_or_else(|_| p.clone())
+        })
+        .collect();
+    app.set_status(match (names.is_empty(), errors.is_empty()) {
+        (false, true) => format!("Created {}", names.join(", ")),
+        (true, _) => format!("Create failed: {}", errors.join(", ")),
+        (false, false) => format!(
+            "Created {} — failed: {}",
+            names.join(", "),
+            errors.join(", ")
+        ),
+    });
+}
+
+/// Compare the active pane with its neighbour, or stop comparing. `C` asks for
+/// file contents to be compared too, which reads every same-sized pair in full
+/// — slow on large files, so it is the one you ask for rather than the default.
+fn toggle_compare(app: &mut App, by_content: bool) {
+    if let Some(existing) = &app.comparison {
+        // The other key on a running comparison switches what it compares by,
+        // rather than making you turn it off to turn it on differently.
+        if existing.by_content == by_content {
+            app.comparison = None;
+            app.set_status("Compare off".to_string());
+            return;
+        }
+    }
+    let Some((left, right)) = compare_pair(app) else {
+        app.set_status("Nothing to compare with — split first with | or _".to_string());
+        return;
+    };
+    app.compare_panes(&left, &right, by_content);
+    if let Some(comparison) = &app.comparison {
+        // By content the answer comes later, and the status bar counts it.
+        let status = if comparison.is_busy() {
+            "Comparing by content…".to_string()
+        } else {
+            match comparison.diffs.len() {
+                0 => "No differences".to_string(),
+                n => format!("{} difference(s)", n),
+            }
+        };
+        app.set_status(status);
+    }
+}
+
+/// The active pane and the one it is compared with, in layout order. The
+/// partner is the next file pane along — or the previous one, from the last —
+/// so in the usual two-pane layout it is simply the other pane.
+fn compare_pair(app: &App) -> Option<(String, String)> {
+    let ids: Vec<String> = collect_leaf_ids(&app.layout_root)
+        .into_iter()
+        .filter(|id| app.oko_pane_id.as_deref() != Some(id.as_str()))
+        .collect();
+    let at = ids.iter().position(|id| *id == app.active_pane_id)?;
+    let partner = if at + 1 < ids.len() {
+        at + 1
+    } else {
+        at.checked_sub(1)?
+    };
+    let (first, second) = (at.min(partner), at.max(partner));
+    Some((ids[first].clone(), ids[second].clone()))
+}
+
+fn start_sync(app: &mut App) {
+    let Some(comparison) = &app.comparison else {
+        app.set_status("Compare panes first with c".to_string());
+        return;
+    };
+    if comparison.is_busy() {
+        app.set_status("Still comparing — sync once it is done".to_string());
+        return;
+    }
+    let direction = panex_core::compare::SyncDirection::LeftToRight;
+    let Some(plan) = plan_for(app, direction) else {
+        return;
+    };
+    app.mode = AppMode::SyncPlan {
+        direction,
+        plan,
+        offset: 0,
+    };
+}
+
+fn plan_for(
+    app: &App,
+    direction: panex_core::compare::SyncDirection,
+) -> Option<panex_core::compare::SyncPlan> {
+    let comparison = app.comparison.as_ref()?;
+    let left = &app.pane_map.get(&comparison.left)?.current_path;
+    let right = &app.pane_map.get(&comparison.right)?.current_path;
+    Some(panex_core::compare::plan_sync(
+        left,
+        right,
+        &comparison.diffs,
+        direction,
+    ))
+}
+
+fn handle_sync_plan(app: &mut App, key: KeyEvent) {
+    let AppMode::SyncPlan { direction, plan, .. } = &app.mode else {
+        return;
+    };
+    let direction = *direction;
+    let lines = plan.steps.len() + plan.skipped.len();
+
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {
+            app.mode = AppMode::Normal;
+        }
+        // The direction is chosen here, with the plan for it in view, rather
+        // than up front — what each one would do is the thing to choose by.
+        KeyCode::Tab | KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
+            let direction = direction.cycle();
+            if let Some(plan) = plan_for(app, direction) {
+                app.mode = AppMode::SyncPlan {
+                    direction,
+                    plan,
+                    offset: 0,
+                };
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            if let AppMode::SyncPlan { offset, .. } = &mut app.mode {
+                *offset = (*offset + 1).min(lines.saturating_sub(1));
+            }
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            if let AppMode::SyncPlan { offset, .. } = &mut app.mode {
+                *offset = offset.saturating_sub(1);
+            }
+        }
+        KeyCode::Enter | KeyCode::Char('y') => {
+            let AppMode::SyncPlan { plan, .. } = std::mem::replace(&mut app.mode, AppMode::Normal)
+            else {
+                return;
+            };
+            if plan.steps.is_empty() {
+                app.set_status("Nothing to sync".to_string());
+                return;
+            }
+            let (done, errors) = panex_core::compare::apply_sync(&plan);
+            if errors.is_empty() {
+                app.set_status(format!("Synced {} item(s)", done));
+            } else {
+                app.set_status(format!("Sync errors: {}", errors.join(", ")));
+            }
+            // Refreshing either compared pane runs the comparison again, so
+            // what is left marked afterwards is what the sync did not settle.
+            let pane_ids: Vec<String> = app.pane_map.keys().cloned().collect();
+            for pid in pane_ids {
+                app.refresh_pane(&pid);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Diff the file focused in the active pane against the one focused in its
+/// neighbour — the same pairing `c` compares.
+fn start_diff(app: &mut App) {
+    let Some((left, right)) = compare_pair(app) else {
+        app.set_status("Nothing to diff against — split first with | or _".to_string());
+        return;
+    };
+    let focused_file = |pane_id: &str| {
+        let pane = app.pane_map.get(pane_id)?;
+        let entry = pane.entries.get(usize::try_from(pane.focus_index).ok()?)?;
+        (!entry.is_dir).then(|| entry.path.clone())
+    };
+    let (Some(left), Some(right)) = (focused_file(&left), focused_file(&right)) else {
+        app.set_status("Focus a file in both panes to diff".to_string());
+        return;
+    };
+    if left == right {
+        app.set_status("Both panes are on the same file".to_string());
+        return;
+    }
+
+    if let Some(tool) = app.config.diff.tool.clone() {
+        open_with(app, Opener::new(&tool), vec![left, right]);
+        return;
+    }
+
+    match panex_core::diff::diff_files(&left, &right) {
+        Ok(lines) if lines.iter().all(|l| l.change == panex_core::diff::LineChange::Same) => {
+            app.set_status("Files are identical".to_string());
+        }
+        Ok(lines) => {
+            app.mode = AppMode::Diff {
+                view: crate::diff::DiffView::new(left, right, lines),
+            };
         }
-        Err(e) => app.set_status(format!("Create failed: {}", e)),
+        Err(e) => app.set_status(format!("Error: {}", e)),
     }
 }
 
-/// Keys that mean the same thing whatever a pane is showing.
-fn acts_on_panes(key: KeyCode) -> bool {
-    matches!(
-        key,
-        KeyCode::Char('q')
-            | KeyCode::Char('O')
-            | KeyCode::Char('W')
-            | KeyCode::Char('|')
-            | KeyCode::Char('_')
-            | KeyCode::Char('+')
-            | KeyCode::Char('=')
-            | KeyCode::Char('-')
-            | KeyCode::Char('?')
-            | KeyCode::Tab
-    )
+fn handle_diff(app: &mut App, key: KeyEvent) {
+    let AppMode::Diff { view } = &mut app.mode else {
+        return;
+    };
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => app.mode = AppMode::Normal,
+        KeyCode::Down | KeyCode::Char('j') => view.scroll(1),
+        KeyCode::Up | KeyCode::Char('k') => view.scroll(-1),
+        KeyCode::PageDown | KeyCode::Char(' ') => view.scroll_page(1),
+        KeyCode::PageUp => view.scroll_page(-1),
+        KeyCode::Char('g') | KeyCode::Home => view.offset = 0,
+        KeyCode::Char('G') | KeyCode::End => view.scroll(isize::MAX),
+        KeyCode::Char('n') | KeyCode::Char('N') => {
+            let (moved, way) = if key.code == KeyCode::Char('n') {
+                (view.next_change(), "below")
+            } else {
+                (view.previous_change(), "above")
+            };
+            if !moved {
+                app.set_status(format!("No more changes {}", way));
+            }
+        }
+        KeyCode::Tab | KeyCode::Char('u') => view.toggle_layout(),
+        _ => {}
+    }
 }
 
 fn handle_help(app: &mut App, key: KeyEvent) {
@@ -454,6 +748,9 @@ fn click_at(app: &mut App, x: u16, y: u16) -> bool {
         Some(v) => v.list_area,
         None => return changed,
     };
+    if list.height > 0 && y + 1 == list.y {
+        return sort_by_header(app, &pane_id, list, x) || changed;
+    }
     if y < list.y || y >= list.y + list.height {
         return changed;
     }
@@ -492,6 +789,31 @@ fn click_at(app: &mut App, x: u16, y: u16) -> bool {
     changed
 }
 
+/// A click on the column header sorts by the column under it; a second
+/// click on the same one turns the order round.
+fn sort_by_header(app: &mut App, pane_id: &str, list: Rect, x: u16) -> bool {
+    let header = Rect { y: list.y - 1, height: 1, ..list };
+    let columns = app.pane_columns(pane_id);
+    let areas = crate::columns::areas(columns, header);
+    let Some(field) = columns
+        .iter()
+        .zip(areas.iter())
+        .find(|(_, area)| x >= area.x && x < area.x + area.width)
+        .map(|(spec, _)| spec.column.sort_field())
+    else {
+        return false;
+    };
+    if field == app.sort_field {
+        app.sort_direction = app.sort_direction.toggle();
+    } else {
+        app.sort_field = field;
+        app.sort_direction = SortDirection::Asc;
+    }
+    app.last_click = None;
+    refilter_all_panes(app);
+    true
+}
+
 /// A click inside the card pane: it selects the card under the cursor, and a
 /// second one on the same card jumps to that tab — the same pair of gestures a
 /// file row answers to, so the pane is not a second mouse to learn.
@@ -685,6 +1007,31 @@ fn confirm_execute(app: &mut App, action: ConfirmAction) {
                 app.refresh_pane(&pid);
             }
         }
+        ConfirmAction::DeleteUsage(id) => {
+            let Some(usage) = &mut app.usage else {
+                return;
+            };
+            let path = usage.path(id);
+            let name = usage.nodes[id].name.clone();
+            match panex_core::delete_entry(&path, false) {
+                Ok(()) => {
+                    usage.remove(id);
+                    app.set_status(format!("Deleted {}", name));
+                }
+                Err(e) => app.set_status(format!("Error: {}", e)),
+            }
+            let pane_ids: Vec<String> = app.pane_map.keys().cloned().collect();
+            for pid in pane_ids {
+                app.refresh_pane(&pid);
+            }
+        }
+        ConfirmAction::GitDiscard(paths) => {
+            match panex_core::git::discard(&paths, false) {
+                Ok(()) => app.set_status(format!("Discarded changes to {} item(s)", paths.len())),
+                Err(e) => app.set_status(format!("Error: {}", e)),
+            }
+            refresh_all_panes(app);
+        }
     }
 }
 
@@ -692,11 +1039,18 @@ fn handle_confirm(app: &mut App, key: KeyEvent) {
     let (action, selected) = if let AppMode::Confirm { action, selected, .. } = &app.mode {
         let a = match action {
             ConfirmAction::Delete(paths) => ConfirmAction::Delete(paths.clone()),
+            ConfirmAction::DeleteUsage(id) => ConfirmAction::DeleteUsage(*id),
+            ConfirmAction::GitDiscard(paths) => ConfirmAction::GitDiscard(paths.clone()),
         };
         (a, *selected)
     } else {
         return;
     };
+    // A delete from the disk usage view goes back to it, done or not.
+    let after = match action {
+        ConfirmAction::DeleteUsage(_) => AppMode::Usage,
+        ConfirmAction::Delete(_) | ConfirmAction::GitDiscard(_) => AppMode::Normal,
+    };
 
     match key.code {
         KeyCode::Left | KeyCode::Right | KeyCode::Char('h') | KeyCode::Char('l') => {
@@ -707,16 +1061,16 @@ fn handle_confirm(app: &mut App, key: KeyEvent) {
         }
         KeyCode::Char('y') => {
             confirm_execute(app, action);
-            app.mode = AppMode::Normal;
+            app.mode = after;
         }
         KeyCode::Enter => {
             if selected == 0 {
                 confirm_execute(app, action);
             }
-            app.mode = AppMode::Normal;
+            app.mode = after;
         }
         KeyCode::Char('n') | KeyCode::Esc => {
-            app.mode = AppMode::Normal;
+            app.mode = after;
         }
         _ => {}
     }
@@ -754,13 +1108,28 @@ fn handle_prompt(app: &mut App, key: KeyEvent) {
                         (Err(e), _) => format!("Rename failed: {}", e),
                     });
                 }
+                PromptAction::FavoriteLabel(path)
+                | PromptAction::FavoriteGroup(path)
+                | PromptAction::FavoriteKey(path) => {
+                    let path = path.clone();
+                    update_favorite(app, &path, &input, &action);
+                    return;
+                }
+                PromptAction::Shell(dir) if !input.trim().is_empty() => {
+                    let chosen = chosen_paths(app);
+                    let command = expand_placeholders(&input, focused_path(app).as_deref(), &chosen, dir);
+                    app.suspend = Some(Suspend::Shell {
+                        command,
+                        dir: dir.clone(),
+                    });
+                }
                 PromptAction::NewFile(dir) if !input.is_empty() => {
-                    let created = panex_core::create_file(dir, &input);
-                    report_creation(app, created, &input);
+                    let outcome = panex_core::create_entries(dir, &input, false);
+                    report_creation(app, dir, outcome);
                 }
                 PromptAction::NewFolder(dir) if !input.is_empty() => {
-                    let created = panex_core::create_folder(dir, &input);
-                    report_creation(app, created, &input);
+                    let outcome = panex_core::create_entries(dir, &input, true);
+                    report_creation(app, dir, outcome);
                 }
                 _ => {}
             }
@@ -812,80 +1181,45 @@ fn handle_prompt(app: &mut App, key: KeyEvent) {
     }
 }
 
-fn path_edit_set(app: &mut App, pane_id: String, input: String, cursor: usize) {
+/// Open the path prompt on `path`, with nothing offered until something is
+/// typed or Tab is pressed: the folder the pane is in is not a question.
+fn open_path_edit(app: &mut App, pane_id: String, path: String) {
     app.mode = AppMode::PathEdit {
         pane_id,
-        input,
-        cursor,
+        cursor: path.len(),
+        input: path,
         completions: Vec::new(),
         completion_index: None,
-        completion_prefix: String::new(),
+        partial: false,
     };
 }
 
-fn compute_completions(input: &str, home_path: &str) -> (String, Vec<String>) {
-    let expanded = if input.starts_with('~') {
-        input.replacen('~', home_path, 1)
-    } else {
-        input.to_string()
+/// Put `input` in the path prompt, with what it completes to.
+fn path_edit_set(app: &mut App, pane_id: String, input: String, cursor: usize) {
+    let base = app.pane_map.get(&pane_id).map(|p| p.current_path.clone()).unwrap_or_default();
+    let found = crate::complete::complete(&input, &base, &app.home_path, app.show_hidden);
+    app.mode = AppMode::PathEdit {
+        pane_id,
+        input,
+        cursor,
+        completions: found.candidates,
+        completion_index: None,
+        partial: found.partial,
     };
-    let path = std::path::Path::new(&expanded);
-
-    // Split into parent dir and the prefix being typed
-    let (dir, prefix) = if expanded.ends_with('/') {
-        (expanded.as_str().to_string(), String::new())
-    } else {
-        let parent = path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
-        let file_part = path.file_name().map(|f| f.to_string_lossy().to_string()).unwrap_or_default();
-        (parent, file_part)
-    };
-
-    let prefix_lower = prefix.to_lowercase();
-    let mut matches = Vec::new();
-    if let Ok(entries) = std::fs::read_dir(&dir) {
-        for entry in entries.flatten() {
-            let name = entry.file_name().to_string_lossy().to_string();
-            if name.to_lowercase().starts_with(&prefix_lower) {
-                let full = if dir.ends_with('/') {
-                    format!("{}{}", dir, name)
-                } else {
-                    format!("{}/{}", dir, name)
-                };
-                // Add trailing slash for directories
-                let full = if entry.path().is_dir() {
-                    format!("{}/", full)
-                } else {
-                    full
-                };
-                // Convert back to ~ if original used it
-                let full = if input.starts_with('~') {
-                    full.replacen(home_path, "~", 1)
-                } else {
-                    full
-                };
-                matches.push(full);
-            }
-        }
-    }
-    matches.sort_by_key(|a| a.to_lowercase());
-    (prefix, matches)
 }
 
 fn handle_path_edit(app: &mut App, key: KeyEvent) {
-    let (pane_id, mut input, mut cursor, completions, completion_index, completion_prefix) =
-        if let AppMode::PathEdit {
-            pane_id,
-            input,
-            cursor,
-            completions,
-            completion_index,
-            completion_prefix,
-        } = &app.mode
-        {
-            (pane_id.clone(), input.clone(), *cursor, completions.clone(), *completion_index, completion_prefix.clone())
-        } else {
-            return;
-        };
+    let AppMode::PathEdit {
+        pane_id,
+        input,
+        cursor,
+        completions,
+        ..
+    } = &app.mode
+    else {
+        return;
+    };
+    let (pane_id, mut input, mut cursor) = (pane_id.clone(), input.clone(), *cursor);
 
     let shift = key.modifiers.contains(KeyModifiers::SHIFT);
 
@@ -894,13 +1228,14 @@ fn handle_path_edit(app: &mut App, key: KeyEvent) {
             app.mode = AppMode::Normal;
         }
         KeyCode::Enter => {
-            let target = if input.starts_with('~') {
-                input.replacen('~', &app.home_path, 1)
-            } else {
-                input.clone()
+            let base = app.pane_map.get(&pane_id).map(|p| p.current_path.clone()).unwrap_or_default();
+            let target = crate::complete::resolve(&input, &base, &app.home_path);
+            let target = match target.strip_suffix('/') {
+                Some(inside) if !inside.is_empty() => inside.to_string(),
+                _ => target,
             };
-            app.navigate_to(&pane_id, &target);
             app.mode = AppMode::Normal;
+            app.navigate_to(&pane_id, &target);
         }
         KeyCode::Backspace => {
             // If cursor is at end, remove last path segment
@@ -922,135 +1257,325 @@ fn handle_path_edit(app: &mut App, key: KeyEvent) {
             }
             path_edit_set(app, pane_id, input, cursor);
         }
-        KeyCode::Tab | KeyCode::BackTab => {
-            let backward = shift || key.code == KeyCode::BackTab;
-
-            if completions.is_empty() || completion_index.is_none() {
-                // First Tab press: compute completions
-                let (prefix, matches) = compute_completions(&input, &app.home_path);
-                if matches.is_empty() {
-                    return;
-                }
-                let idx = 0;
-                let new_input = matches[idx].clone();
-                let new_cursor = new_input.len();
-                app.mode = AppMode::PathEdit {
-                    pane_id,
-                    input: new_input,
-                    cursor: new_cursor,
-                    completions: matches,
-                    completion_index: Some(idx),
-                    completion_prefix: prefix,
-                };
-            } else {
-                // Cycle through existing completions
-                let len = completions.len();
-                let cur = completion_index.unwrap_or(0);
-                let next = if backward {
-                    if cur == 0 { len - 1 } else { cur - 1 }
-                } else {
-                    (cur + 1) % len
-                };
-                let new_input = completions[next].clone();
-                let new_cursor = new_input.len();
-                app.mode = AppMode::PathEdit {
-                    pane_id,
-                    input: new_input,
-                    cursor: new_cursor,
-                    completions,
-                    completion_index: Some(next),
-                    completion_prefix,
-                };
+        KeyCode::Tab | KeyCode::BackTab | KeyCode::Down | KeyCode::Up => {
+            let backward = shift || matches!(key.code, KeyCode::BackTab | KeyCode::Up);
+            if completions.is_empty() {
+                path_edit_set(app, pane_id, input, cursor);
+            }
+            let AppMode::PathEdit {
+                input,
+                cursor,
+                completions,
+                completion_index,
+                ..
+            } = &mut app.mode
+            else {
+                return;
+            };
+            let len = completions.len();
+            if len == 0 {
+                app.set_status("Nothing to complete".to_string());
+                return;
             }
+            // Step through the candidates, the input showing the one on.
+            let next = match *completion_index {
+                None if backward => len - 1,
+                None => 0,
+                Some(cur) if backward => (cur + len - 1) % len,
+                Some(cur) => (cur + 1) % len,
+            };
+            *completion_index = Some(next);
+            *input = completions[next].path.clone();
+            *cursor = input.len();
         }
         KeyCode::Left => {
             cursor = cursor.saturating_sub(1);
-            path_edit_set(app, pane_id, input, cursor);
+            if let AppMode::PathEdit { cursor: at, .. } = &mut app.mode {
+                *at = cursor;
+            }
         }
         KeyCode::Right => {
             if cursor < input.len() {
                 cursor += 1;
             }
-            path_edit_set(app, pane_id, input, cursor);
+            if let AppMode::PathEdit { cursor: at, .. } = &mut app.mode {
+                *at = cursor;
+            }
         }
         KeyCode::Char(c) => {
             input.insert(cursor, c);
             cursor += 1;
-            // Reset completions when typing
             path_edit_set(app, pane_id, input, cursor);
         }
         _ => {}
     }
 }
 
-fn handle_favorites_list(app: &mut App, key: KeyEvent) {
-    let (pane_id, selected) = if let AppMode::FavoritesList { pane_id, selected } = &app.mode {
-        (pane_id.clone(), *selected)
-    } else {
+fn handle_layout_picker(app: &mut App, key: KeyEvent) {
+    let AppMode::LayoutPicker { selected } = app.mode else {
         return;
     };
-
-    let count = app.config.favorites.paths.len();
+    let count = app.config.layouts.len();
     if count == 0 {
         app.mode = AppMode::Normal;
         return;
     }
-
     match key.code {
-        KeyCode::Esc => {
-            app.mode = AppMode::Normal;
-        }
+        KeyCode::Esc | KeyCode::Char('q') => app.mode = AppMode::Normal,
         KeyCode::Up | KeyCode::Char('k') => {
-            let new_sel = if selected == 0 { count - 1 } else { selected - 1 };
-            app.mode = AppMode::FavoritesList {
-                pane_id,
-                selected: new_sel,
+            app.mode = AppMode::LayoutPicker {
+                selected: (selected + count - 1) % count,
             };
         }
         KeyCode::Down | KeyCode::Char('j') => {
-            let new_sel = (selected + 1) % count;
-            app.mode = AppMode::FavoritesList {
-                pane_id,
-                selected: new_sel,
+            app.mode = AppMode::LayoutPicker {
+                selected: (selected + 1) % count,
             };
         }
         KeyCode::Enter => {
-            let fav_path = app.config.favorites.paths[selected].clone();
-            // Expand ~ to home
-            let target = if fav_path.starts_with('~') {
-                fav_path.replacen('~', &app.home_path, 1)
-            } else {
-                fav_path
-            };
-            app.navigate_to(&pane_id, &target);
             app.mode = AppMode::Normal;
+            if let Some(name) = app.config.layouts.keys().nth(selected).cloned() {
+                if let Err(e) = app.open_layout(&name) {
+                    app.set_status(e);
+                }
+            }
         }
-        // 'e' again or '/' switches to path edit mode (type a path manually)
-        KeyCode::Char('e') | KeyCode::Char('/') => {
-            let path = app
-                .pane_map
-                .get(&pane_id)
-                .map(|p| p.current_path.clone())
-                .unwrap_or_default();
-            app.mode = AppMode::PathEdit {
-                pane_id,
-                input: path.clone(),
-                cursor: path.len(),
-                completions: Vec::new(),
-                completion_index: None,
-                completion_prefix: String::new(),
-            };
-        }
-        // 'd' deletes the selected favorite
-        KeyCode::Char('d') => {
-            let fav_path = app.config.favorites.paths[selected].clone();
+        _ => {}
+    }
+}
+
+fn handle_palette(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let AppMode::Palette { state } = &mut app.mode else {
+        return;
+    };
+    let recent = &app.recent_commands;
+    match key.code {
+        KeyCode::Esc => app.mode = AppMode::Normal,
+        KeyCode::Up => state.move_selection(-1),
+        KeyCode::Down => state.move_selection(1),
+        KeyCode::Char('p') if ctrl => state.move_selection(-1),
+        KeyCode::Char('n') if ctrl => state.move_selection(1),
+        KeyCode::Char('u') if ctrl => {
+            state.input.clear();
+            state.refresh(recent);
+        }
+        KeyCode::Tab => state.complete(recent),
+        KeyCode::Backspace => state.backspace(recent),
+        KeyCode::Char(c) if !ctrl => state.insert(c, recent),
+        KeyCode::Enter => {
+            let typed = state.input.trim().to_string();
+            // With an argument typed, that is what runs; a bare word runs
+            // the highlighted entry, which is what the word picked out.
+            let line = match state.matches.get(state.selected) {
+                _ if typed.contains(' ') => typed,
+                Some(Entry::Command(command)) => {
+                    // Needs its argument yet — ask for it in place.
+                    state.input = format!("{} ", command.name);
+                    state.refresh(recent);
+                    return;
+                }
+                Some(entry) => entry.name().to_string(),
+                None => typed,
+            };
+            app.mode = AppMode::Normal;
+            if line.is_empty() {
+                return;
+            }
+            match run_command(app, &line) {
+                Ok(()) => crate::palette::remember(&mut app.recent_commands, &line),
+                Err(e) => app.set_status(e),
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Run a palette line: an action by name, or a command and its argument.
+fn run_command(app: &mut App, line: &str) -> Result<(), String> {
+    let (name, arg) = match line.split_once(' ') {
+        Some((name, arg)) => (name, arg.trim()),
+        None => (line, ""),
+    };
+    let dir = app
+        .pane_map
+        .get(&app.active_pane_id)
+        .map(|p| p.current_path.clone())
+        .unwrap_or_default();
+    match name {
+        "cd" => {
+            let target = resolve_dir(&dir, &app.home_path, arg);
+            if !target.is_dir() {
+                return Err(format!("Not a folder: {}", target.display()));
+            }
+            let pane_id = app.active_pane_id.clone();
+            app.navigate_to(&pane_id, &target.to_string_lossy());
+        }
+        "import-dirs" => {
+            let file = if arg.starts_with('~') {
+                arg.replacen('~', &app.home_path, 1)
+            } else {
+                arg.to_string()
+            };
+            import_dirs(app, &file)?;
+        }
+        "mkdir" | "touch" if arg.is_empty() => return Err(format!("{}: give it a name", name)),
+        "mkdir" => report_creation(app, &dir, panex_core::create_entries(&dir, arg, true)),
+        "touch" => report_creation(app, &dir, panex_core::create_entries(&dir, arg, false)),
+        "columns" => {
+            let columns = if arg.is_empty() || arg == "default" {
+                Vec::new()
+            } else {
+                crate::columns::parse_list(arg.split_whitespace())?
+            };
+            let pane_id = app.active_pane_id.clone();
+            if let Some(pane) = app.pane_map.get_mut(&pane_id) {
+                pane.columns = columns;
+            }
+            app.refilter_pane(&pane_id);
+        }
+        "sort" => {
+            let mut words = arg.split_whitespace();
+            let usage = || "Usage: sort FIELD [asc|desc], FIELD a column's name".to_string();
+            let field = words.next().and_then(SortField::parse).ok_or_else(usage)?;
+            let direction = match words.next() {
+                Some(word) => SortDirection::parse(word).ok_or_else(usage)?,
+                None => app.sort_direction,
+            };
+            if words.next().is_some() {
+                return Err(usage());
+            }
+            app.sort_field = field;
+            app.sort_direction = direction;
+            refilter_all_panes(app);
+        }
+        name => match Action::from_name(name) {
+            Some(action) if arg.is_empty() => run_action(app, action),
+            Some(_) => return Err(format!("{} takes no argument", name)),
+            None => return Err(format!("Unknown command: {}", name)),
+        },
+    }
+    Ok(())
+}
+
+/// Where `cd ARG` goes from `dir`: `~` is home, a relative path is taken
+/// from `dir`, and `.`/`..` are worked out by name rather than by asking the
+/// filesystem, so `..` out of a symlinked folder goes back the way it came.
+fn resolve_dir(dir: &str, home: &str, arg: &str) -> std::path::PathBuf {
+    use std::path::{Component, Path, PathBuf};
+    let arg = match arg {
+        "" | "~" => home.to_string(),
+        _ => match arg.strip_prefix("~/") {
+            Some(rest) => format!("{}/{}", home, rest),
+            None => arg.to_string(),
+        },
+    };
+    let mut out = PathBuf::new();
+    for component in Path::new(dir).join(arg).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+fn handle_favorites_list(app: &mut App, key: KeyEvent) {
+    let (pane_id, selected) = if let AppMode::FavoritesList { pane_id, selected } = &app.mode {
+        (pane_id.clone(), *selected)
+    } else {
+        return;
+    };
+
+    let count = app.config.favorites.entries.len();
+    if count == 0 {
+        app.mode = AppMode::Normal;
+        return;
+    }
+    let favorite = app.config.favorites.entries[selected].clone();
+
+    match key.code {
+        KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            let new_sel = if selected == 0 { count - 1 } else { selected - 1 };
+            app.mode = AppMode::FavoritesList {
+                pane_id,
+                selected: new_sel,
+            };
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            let new_sel = (selected + 1) % count;
+            app.mode = AppMode::FavoritesList {
+                pane_id,
+                selected: new_sel,
+            };
+        }
+        // Shifted, the favorite moves rather than the cursor.
+        KeyCode::Char('K') | KeyCode::Char('J') => {
+            match app.config.move_favorite(selected, key.code == KeyCode::Char('K')) {
+                Ok(to) => {
+                    app.mode = AppMode::FavoritesList {
+                        pane_id,
+                        selected: to,
+                    }
+                }
+                Err(e) => app.set_status(format!("Error: {}", e)),
+            }
+        }
+        KeyCode::Enter => {
+            app.mode = AppMode::Normal;
+            go_to_favorite(app, &pane_id, &favorite.path);
+        }
+        KeyCode::Tab => open_recent_list(app),
+        // 'e' again or '/' switches to path edit mode (type a path manually)
+        KeyCode::Char('e') | KeyCode::Char('/') => {
+            let path = app
+                .pane_map
+                .get(&pane_id)
+                .map(|p| p.current_path.clone())
+                .unwrap_or_default();
+            open_path_edit(app, pane_id, path);
+        }
+        KeyCode::Char(c @ ('n' | 'g' | 'b')) => {
+            let (title, current, action) = match c {
+                'n' => (
+                    "Label",
+                    favorite.label.unwrap_or_default(),
+                    PromptAction::FavoriteLabel(favorite.path),
+                ),
+                'g' => (
+                    "Group",
+                    favorite.group.unwrap_or_default(),
+                    PromptAction::FavoriteGroup(favorite.path),
+                ),
+                _ => (
+                    "Key (one character)",
+                    favorite.key.map(String::from).unwrap_or_default(),
+                    PromptAction::FavoriteKey(favorite.path),
+                ),
+            };
+            app.mode = AppMode::Prompt {
+                title: title.to_string(),
+                cursor: current.len(),
+                input: current,
+                action,
+            };
+        }
+        // 'd' deletes the selected favorite
+        KeyCode::Char('d') => {
+            let fav_path = favorite.path;
             match app.config.remove_favorite(&fav_path) {
                 Ok(()) => {
                     app.set_status(format!("Removed favorite: {}", fav_path));
-                    if app.config.favorites.paths.is_empty() {
+                    if app.config.favorites.entries.is_empty() {
                         app.mode = AppMode::Normal;
                     } else {
-                        let new_sel = selected.min(app.config.favorites.paths.len() - 1);
+                        let new_sel = selected.min(app.config.favorites.entries.len() - 1);
                         app.mode = AppMode::FavoritesList {
                             pane_id,
                             selected: new_sel,
@@ -1064,6 +1589,229 @@ fn handle_favorites_list(app: &mut App, key: KeyEvent) {
     }
 }
 
+fn go_to_favorite(app: &mut App, pane_id: &str, path: &str) {
+    // Expand ~ to home
+    let target = if path.starts_with('~') {
+        path.replacen('~', &app.home_path, 1)
+    } else {
+        path.to_string()
+    };
+    app.navigate_to(pane_id, &target);
+}
+
+/// Finish a prompt about a favorite, and go back to the list on it.
+fn update_favorite(app: &mut App, path: &str, input: &str, action: &PromptAction) {
+    let Some(mut favorite) = app.config.favorites.get(path).cloned() else {
+        return;
+    };
+    let value = (!input.trim().is_empty()).then(|| input.trim().to_string());
+    match action {
+        PromptAction::FavoriteLabel(_) => favorite.label = value,
+        PromptAction::FavoriteGroup(_) => favorite.group = value,
+        _ => {
+            let mut chars = input.chars();
+            favorite.key = match (chars.next(), chars.next()) {
+                (Some(c), None) if !c.is_whitespace() => Some(c),
+                (None, _) => None,
+                _ => {
+                    app.set_status("A favorite's key is a single character".to_string());
+                    return;
+                }
+            };
+        }
+    }
+    if let Err(e) = app.config.update_favorite(favorite) {
+        app.set_status(format!("Error: {}", e));
+    }
+    let selected = app
+        .config
+        .favorites
+        .entries
+        .iter()
+        .position(|f| f.path == path)
+        .unwrap_or(0);
+    app.mode = AppMode::FavoritesList {
+        pane_id: app.active_pane_id.clone(),
+        selected,
+    };
+}
+
+/// The key after `m` or `'`.
+fn handle_mark(app: &mut App, key: KeyEvent) {
+    let AppMode::Mark { pane_id, set } = std::mem::replace(&mut app.mode, AppMode::Normal) else {
+        return;
+    };
+    let KeyCode::Char(c) = key.code else {
+        return;
+    };
+    if !set {
+        match app.config.favorites.for_key(c).map(|f| f.path.clone()) {
+            Some(path) => go_to_favorite(app, &pane_id, &path),
+            None => app.set_status(format!("Nothing is under '{}", c)),
+        }
+        return;
+    }
+    let Some(here) = app.pane_map.get(&pane_id).map(|p| p.current_path.clone()) else {
+        return;
+    };
+    // Marking a folder makes it a favorite, if it was not one.
+    let favorite = app.config.favorites.get(&here).cloned().unwrap_or_else(|| {
+        panex_core::config::Favorite::new(&here)
+    });
+    let favorite = panex_core::config::Favorite {
+        key: Some(c),
+        ..favorite
+    };
+    match app.config.update_favorite(favorite) {
+        Ok(()) => app.set_status(format!("★ {} is under '{}", here, c)),
+        Err(e) => app.set_status(format!("Error: {}", e)),
+    }
+}
+
+/// How many folders the `z` prompt lists.
+const JUMP_LIMIT: usize = 50;
+
+/// The folders `query` finds, best first — less the one the pane is in,
+/// which is nowhere to jump to.
+fn jump_matches(app: &App, query: &str) -> Vec<String> {
+    let here = app.pane_map.get(&app.active_pane_id).map(|p| p.current_path.as_str());
+    app.frecency
+        .query(query, panex_core::frecency::now())
+        .into_iter()
+        .map(|d| d.path.clone())
+        .filter(|p| Some(p.as_str()) != here)
+        .take(JUMP_LIMIT)
+        .collect()
+}
+
+fn handle_jump(app: &mut App, key: KeyEvent) {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let AppMode::Jump { input, matches, selected, .. } = &mut app.mode else {
+        return;
+    };
+    let count = matches.len();
+    let typed = match key.code {
+        KeyCode::Esc => {
+            app.mode = AppMode::Normal;
+            return;
+        }
+        KeyCode::Up => {
+            *selected = selected.saturating_sub(1);
+            return;
+        }
+        KeyCode::Down | KeyCode::Tab => {
+            *selected = (*selected + 1).min(count.saturating_sub(1));
+            return;
+        }
+        KeyCode::Enter => {
+            let AppMode::Jump { pane_id, matches, selected, .. } =
+                std::mem::replace(&mut app.mode, AppMode::Normal)
+            else {
+                return;
+            };
+            match matches.get(selected) {
+                Some(path) => app.navigate_to(&pane_id, path),
+                None => app.set_status("No folder matches".to_string()),
+            }
+            return;
+        }
+        KeyCode::Backspace => {
+            input.pop();
+            input.clone()
+        }
+        KeyCode::Char('u') if ctrl => {
+            input.clear();
+            String::new()
+        }
+        KeyCode::Char(c) if !ctrl => {
+            input.push(c);
+            input.clone()
+        }
+        _ => return,
+    };
+    let found = jump_matches(app, &typed);
+    if let AppMode::Jump { matches, selected, .. } = &mut app.mode {
+        *matches = found;
+        *selected = 0;
+    }
+}
+
+/// Bring zoxide's or autojump's history into `z`'s: from `file`, or from
+/// wherever either keeps it.
+fn import_dirs(app: &mut App, file: &str) -> Result<(), String> {
+    let files = if file.is_empty() {
+        panex_core::frecency::import_sources()
+    } else {
+        vec![std::path::PathBuf::from(file)]
+    };
+    if files.is_empty() {
+        return Err("No zoxide or autojump database found — give its file".to_string());
+    }
+    let mut count = 0;
+    for file in &files {
+        count += app.frecency.import(&file.to_string_lossy())?;
+    }
+    app.set_status(format!("Imported {} folder(s)", count));
+    Ok(())
+}
+
+/// The recent list, with the cursor on the folder before this one — the
+/// top is where the pane already is.
+fn open_recent_list(app: &mut App) {
+    let here = app
+        .pane_map
+        .get(&app.active_pane_id)
+        .map(|p| p.current_path.clone())
+        .unwrap_or_default();
+    app.recent.paths.retain(|p| std::path::Path::new(p).is_dir());
+    if app.recent.paths.is_empty() {
+        app.set_status("No recent folders".to_string());
+        return;
+    }
+    let selected = usize::from(app.recent.paths[0] == here && app.recent.paths.len() > 1);
+    app.mode = AppMode::RecentList {
+        pane_id: app.active_pane_id.clone(),
+        selected,
+    };
+}
+
+fn handle_recent_list(app: &mut App, key: KeyEvent) {
+    let AppMode::RecentList { pane_id, selected } = &mut app.mode else {
+        return;
+    };
+    let count = app.recent.paths.len();
+    if count == 0 {
+        app.mode = AppMode::Normal;
+        return;
+    }
+    match key.code {
+        KeyCode::Esc => app.mode = AppMode::Normal,
+        KeyCode::Up | KeyCode::Char('k') => *selected = (*selected + count - 1) % count,
+        KeyCode::Down | KeyCode::Char('j') => *selected = (*selected + 1) % count,
+        KeyCode::Enter => {
+            let (pane_id, path) = (pane_id.clone(), app.recent.paths[*selected].clone());
+            app.mode = AppMode::Normal;
+            app.navigate_to(&pane_id, &path);
+        }
+        KeyCode::Char('d') => {
+            let path = app.recent.paths.remove(*selected);
+            *selected = (*selected).min(count.saturating_sub(2));
+            if app.recent.paths.is_empty() {
+                app.mode = AppMode::Normal;
+            }
+            app.set_status(format!("Forgot {}", path));
+        }
+        // Tab goes over to the favorites, as it came from them.
+        KeyCode::Tab if !app.config.favorites.entries.is_empty() => {
+            app.mode = AppMode::FavoritesList {
+                pane_id: pane_id.clone(),
+                selected: 0,
+            };
+        }
+        _ => {}
+    }
+}
+
 // --- Helper functions ---
 
 /// What the first and last row do to a step that would leave the list.
@@ -1132,6 +1880,9 @@ fn toggle_selection_at_focus(app: &mut App) {
 }
 
 fn open_focused(app: &mut App) {
+    if app.pick.is_some() && pick(app) {
+        return;
+    }
     let pane_id = app.active_pane_id.clone();
     let entry = {
         let pane = match app.pane_map.get(&pane_id) {
@@ -1147,8 +1898,34 @@ fn open_focused(app: &mut App) {
     if entry.is_dir {
         app.navigate_to(&pane_id, &entry.path);
     } else {
-        open_file_with_config(app, &entry.path);
+        open_files(app, std::slice::from_ref(&entry.path));
+    }
+}
+
+/// Enter in pick mode: the selection, or else the focused file, is picked
+/// and the app quits. A focused folder is not picked but gone into, as
+/// Enter always does — a folder is picked by selecting it. Returns whether
+/// anything was picked.
+fn pick(app: &mut App) -> bool {
+    let Some(pane) = app.pane_map.get(&app.active_pane_id) else {
+        return false;
+    };
+    let picked = if pane.selected_paths.is_empty() {
+        let focused = usize::try_from(pane.focus_index).ok().and_then(|i| pane.entries.get(i));
+        match focused {
+            Some(entry) if !entry.is_dir => vec![entry.path.clone()],
+            _ => return false,
+        }
+    } else {
+        chosen_paths(app)
+    };
+    if app.pick == Some(crate::app::Pick::One) && picked.len() > 1 {
+        app.set_status("Pick one: --pick takes a single path".to_string());
+        return true;
     }
+    app.picked = picked;
+    app.should_quit = true;
+    true
 }
 
 fn navigate_up(app: &mut App) {
@@ -1162,6 +1939,97 @@ fn navigate_up(app: &mut App) {
     if let Some(parent) = std::path::Path::new(&current).parent() {
         let parent_str = parent.to_string_lossy().to_string();
         app.navigate_to(&pane_id, &parent_str);
+        // On the folder just left, so that up-then-down is a step back.
+        app.focus_path(&pane_id, &current);
+    }
+}
+
+/// Measure the selected folders in the active pane, or all of them if none
+/// is selected, in the background. Each size shows up in place as it comes.
+fn measure_folders(app: &mut App) {
+    let Some(pane) = app.pane_map.get(&app.active_pane_id) else {
+        return;
+    };
+    let folders = |selected_only: bool| -> Vec<(String, u64)> {
+        pane.entries
+            .iter()
+            .filter(|e| e.is_dir && (!selected_only || pane.selected_paths.contains(&e.path)))
+            .map(|e| (e.path.clone(), e.modified))
+            .collect()
+    };
+    let mut targets = folders(true);
+    if targets.is_empty() {
+        targets = folders(false);
+    }
+    if targets.is_empty() {
+        app.set_status("No folders here to measure".to_string());
+        return;
+    }
+    app.dir_sizes.request(targets);
+    let progress = app.dir_sizes.progress();
+    app.set_status(progress);
+}
+
+/// Open the disk usage view on the active pane's folder, and start its scan.
+fn open_disk_usage(app: &mut App) {
+    let Some(path) = app.pane_map.get(&app.active_pane_id).map(|p| p.current_path.clone()) else {
+        return;
+    };
+    match crate::usage::Usage::start(&path, app.config.sizes.options()) {
+        Ok(usage) => {
+            app.usage = Some(usage);
+            app.mode = AppMode::Usage;
+        }
+        Err(e) => app.set_status(format!("Error: {}", e)),
+    }
+}
+
+fn handle_usage(app: &mut App, key: KeyEvent) {
+    let Some(usage) = &mut app.usage else {
+        app.mode = AppMode::Normal;
+        return;
+    };
+    match key.code {
+        // The first Esc stops the scan and keeps what it found; the next
+        // closes the view.
+        KeyCode::Esc if usage.is_scanning() => {
+            usage.cancel();
+            app.set_status("Scan cancelled".to_string());
+        }
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.usage = None;
+            app.mode = AppMode::Normal;
+        }
+        KeyCode::Down | KeyCode::Char('j') => usage.move_by(1),
+        KeyCode::Up | KeyCode::Char('k') => usage.move_by(-1),
+        KeyCode::PageDown => usage.move_page(1),
+        KeyCode::PageUp => usage.move_page(-1),
+        KeyCode::Char('g') | KeyCode::Home => usage.selected = usage.rows().first().copied(),
+        KeyCode::Char('G') | KeyCode::End => usage.move_by(isize::MAX),
+        KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+            usage.enter();
+        }
+        KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+            usage.leave();
+        }
+        KeyCode::Char('d') | KeyCode::Delete => {
+            let Some(id) = usage.selected else {
+                return;
+            };
+            let node = &usage.nodes[id];
+            let message = format!(
+                "Move \"{}\" ({}) to trash?",
+                node.name,
+                crate::columns::format_size(node.size)
+            );
+            app.mode = AppMode::Confirm {
+                title: "Delete".to_string(),
+                message,
+                action: ConfirmAction::DeleteUsage(id),
+                selected: 0,
+            };
+        }
+        _ => {}
     }
 }
 
@@ -1181,16 +2049,16 @@ fn split_active_pane(app: &mut App, direction: SplitDirection) {
     );
 
     let mut new_pane = crate::app::PaneState::new(&current_path);
-    match panex_core::read_directory(&current_path) {
+    // The new pane shows what the one it came from does.
+    new_pane.columns = app
+        .pane_map
+        .get(&app.active_pane_id)
+        .map(|p| p.columns.clone())
+        .unwrap_or_default();
+    let active = app.active_pane_id.clone();
+    match app.read_entries(&active, &current_path) {
         Ok(raw) => {
-            let filtered = apply_sort_and_filter(
-                &raw,
-                app.show_hidden,
-                "",
-                app.sort_field,
-                app.sort_direction,
-            );
-            new_pane.entries = filtered;
+            new_pane.entries = app.visible(&raw, "");
             app.raw_entries_map.insert(new_id.clone(), raw);
         }
         Err(e) => {
@@ -1280,6 +2148,9 @@ fn close_pane(app: &mut App, pane_id: &str) {
         app.layout_root = new_root;
         app.pane_map.remove(pane_id);
         app.raw_entries_map.remove(pane_id);
+        if app.comparison.as_ref().is_some_and(|c| c.involves(pane_id)) {
+            app.comparison = None;
+        }
         let returning = if app.oko_pane_id.as_deref() == Some(pane_id) {
             detach_oko(app);
             app.oko_return_to.take()
@@ -1452,6 +2323,16 @@ fn paste_clipboard(app: &mut App) {
 
 fn start_rename(app: &mut App) {
     let pane_id = app.active_pane_id.clone();
+    // With several selected, renaming one of them — the focused one, which
+    // may not even be among them — is never what was meant.
+    if app
+        .pane_map
+        .get(&pane_id)
+        .is_some_and(|p| p.selected_paths.len() > 1)
+    {
+        start_bulk_rename(app);
+        return;
+    }
     if let Some(pane) = app.pane_map.get(&pane_id) {
         if pane.focus_index >= 0 && (pane.focus_index as usize) < pane.entries.len() {
             let entry = &pane.entries[pane.focus_index as usize];
@@ -1467,6 +2348,307 @@ fn start_rename(app: &mut App) {
     }
 }
 
+/// Rename the selection — or, with nothing selected, the focused entry — by
+/// a rule.
+fn start_bulk_rename(app: &mut App) {
+    let pane_id = app.active_pane_id.clone();
+    let Some(pane) = app.pane_map.get(&pane_id) else {
+        return;
+    };
+    let paths: Vec<String> = if pane.selected_paths.is_empty() {
+        let focused = usize::try_from(pane.focus_index)
+            .ok()
+            .and_then(|i| pane.entries.get(i));
+        match focused {
+            Some(entry) => vec![entry.path.clone()],
+            None => return,
+        }
+    } else {
+        // Listing order rather than the set's, so `{n}` counts down the
+        // pane as it is shown.
+        pane.entries
+            .iter()
+            .filter(|e| pane.selected_paths.contains(&e.path))
+            .map(|e| e.path.clone())
+            .collect()
+    };
+    app.mode = AppMode::BulkRename {
+        state: crate::rename::BulkRename::new(pane_id, paths),
+    };
+}
+
+fn handle_bulk_rename(app: &mut App, key: KeyEvent) {
+    let AppMode::BulkRename { state } = &mut app.mode else {
+        return;
+    };
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    match key.code {
+        KeyCode::Esc => app.mode = AppMode::Normal,
+        KeyCode::Enter => apply_bulk_rename(app),
+        KeyCode::Tab => state.switch_field(1),
+        KeyCode::BackTab => state.switch_field(-1),
+        KeyCode::Char('r') if ctrl => state.toggle_regex(),
+        KeyCode::Char('t') if ctrl => state.cycle_case(),
+        KeyCode::Char(c) if !ctrl => state.insert(c),
+        KeyCode::Backspace => state.backspace(),
+        KeyCode::Left => state.move_cursor(-1),
+        KeyCode::Right => state.move_cursor(1),
+        KeyCode::Home => state.cursor = 0,
+        KeyCode::End => state.cursor_to_end(),
+        KeyCode::Down => state.offset = (state.offset + 1).min(state.paths.len().saturating_sub(1)),
+        KeyCode::Up => state.offset = state.offset.saturating_sub(1),
+        _ => {}
+    }
+}
+
+fn apply_bulk_rename(app: &mut App) {
+    let AppMode::BulkRename { state } = &app.mode else {
+        return;
+    };
+    let previews = match &state.preview {
+        Ok(previews) => previews,
+        Err(e) => {
+            app.set_status(e.clone());
+            return;
+        }
+    };
+    let (changed, problems) = state.counts();
+    if problems > 0 {
+        // Stays open: the problems are listed in the preview, and the rule
+        // that caused them is right there to fix.
+        app.set_status(format!("{} name(s) cannot be used — nothing renamed", problems));
+        return;
+    }
+    if changed == 0 {
+        app.set_status("Nothing to rename".to_string());
+        return;
+    }
+    let pane_id = state.pane_id.clone();
+    let result = panex_core::rename::apply_renames(previews);
+    app.mode = AppMode::Normal;
+    match result {
+        Ok(n) => {
+            app.set_status(format!("Renamed {} item(s)", n));
+            if let Some(pane) = app.pane_map.get_mut(&pane_id) {
+                pane.selected_paths.clear();
+            }
+        }
+        Err(e) => app.set_status(format!("Rename failed, rolled back: {}", e)),
+    }
+    app.refresh_pane(&pane_id);
+}
+
+/// Rename in `$EDITOR`: the selection — or, with nothing selected, everything
+/// in the pane — as one name per line.
+fn start_edit_names(app: &mut App) {
+    let pane_id = app.active_pane_id.clone();
+    let Some(pane) = app.pane_map.get(&pane_id) else {
+        return;
+    };
+    let paths: Vec<String> = pane
+        .entries
+        .iter()
+        .filter(|e| pane.selected_paths.is_empty() || pane.selected_paths.contains(&e.path))
+        .map(|e| e.path.clone())
+        .collect();
+    if paths.is_empty() {
+        app.set_status("Nothing to rename".to_string());
+        return;
+    }
+    app.suspend = Some(Suspend::EditNames { pane_id, paths });
+}
+
+/// Carry out work the event loop has handed the terminal over for.
+pub fn run_suspended(app: &mut App, job: Suspend) {
+    match job {
+        Suspend::EditNames { pane_id, paths } => {
+            edit_names(app, &pane_id, &paths, &editor_command());
+        }
+        Suspend::Open { runs } => {
+            for (opener, paths) in runs {
+                run_here(app, &opener, &paths);
+            }
+            // Whatever ran may have changed what the pane lists.
+            let pane_id = app.active_pane_id.clone();
+            app.refresh_pane(&pane_id);
+        }
+        Suspend::Shell { command, dir } => {
+            run_shell(app, &command, &dir);
+            refresh_all_panes(app);
+        }
+    }
+}
+
+/// Run a `!` command and leave what it printed up until Enter: it would be
+/// gone the moment the screen came back.
+fn run_shell(app: &mut App, command: &str, dir: &str) {
+    use std::io::{IsTerminal, Write};
+    let status = panex_core::shell_command(command, &[]).current_dir(dir).status();
+    match &status {
+        Ok(s) if s.success() => {}
+        Ok(s) => app.set_status(match s.code() {
+            Some(code) => format!("Command exited with {}", code),
+            None => "Command was stopped by a signal".to_string(),
+        }),
+        Err(e) => app.set_status(format!("Failed to run command: {}", e)),
+    }
+    if std::io::stdin().is_terminal() {
+        eprint!("\n[press Enter to return to panex]");
+        let _ = std::io::stderr().flush();
+        let _ = std::io::stdin().read_line(&mut String::new());
+    }
+}
+
+/// `$PAGER`, or less.
+fn pager_command() -> String {
+    std::env::var("PAGER")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string())
+}
+
+/// Run `opener` on `paths` in this terminal and wait for it. What it
+/// printed is gone once the TUI is back, so a failure is told in the
+/// status line.
+fn run_here(app: &mut App, opener: &Opener, paths: &[String]) {
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+    let runs: Vec<&[&str]> = if opener.single {
+        paths.chunks(1).collect()
+    } else {
+        vec![&paths]
+    };
+    let dir = app
+        .pane_map
+        .get(&app.active_pane_id)
+        .map(|p| p.current_path.clone())
+        .unwrap_or_default();
+    for run in runs {
+        let mut command = panex_core::shell_command(&opener.command, run);
+        if !dir.is_empty() {
+            command.current_dir(&dir);
+        }
+        match command.status() {
+            Ok(s) if s.success() => {}
+            Ok(_) => {
+                app.set_status(format!("{} exited with an error", opener.label()));
+                return;
+            }
+            Err(e) => {
+                app.set_status(format!("Failed to run {}: {}", opener.label(), e));
+                return;
+            }
+        }
+    }
+}
+
+/// `$VISUAL`, then `$EDITOR`, then vi — the order git and most shells use.
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// A new file in the temp folder holding `text`, readable by no one else.
+/// The folder is shared, so the name is one nobody has taken — not even a
+/// link planted there ahead of us — and a taken one is passed over for the
+/// next rather than written through.
+fn write_private_temp(prefix: &str, text: &str) -> std::io::Result<std::path::PathBuf> {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    for _ in 0..100 {
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let file = std::env::temp_dir().join(format!("{}-{}-{}.txt", prefix, std::process::id(), n));
+        match options.open(&file) {
+            Ok(mut handle) => {
+                handle.write_all(text.as_bytes())?;
+                return Ok(file);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        "no free name in the temp folder",
+    ))
+}
+
+fn edit_names(app: &mut App, pane_id: &str, paths: &[String], editor: &str) {
+    let names: Vec<String> = paths
+        .iter()
+        .map(|p| {
+            std::path::Path::new(p)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+        .collect();
+    let file = match write_private_temp("panex-names", &(names.join("\n") + "\n")) {
+        Ok(file) => file,
+        Err(e) => {
+            app.set_status(format!("Failed to write names: {}", e));
+            return;
+        }
+    };
+
+    // The editor may carry its own arguments (`code --wait`), so it goes
+    // through the shell, as `e` does, rather than being taken as one name.
+    let status = panex_core::shell_command(editor, &[&file.to_string_lossy()]).status();
+    let edited = std::fs::read_to_string(&file);
+    let _ = std::fs::remove_file(&file);
+
+    match status {
+        Ok(s) if s.success() => {}
+        Ok(_) => {
+            app.set_status(format!("{} exited with an error — nothing renamed", editor));
+            return;
+        }
+        Err(e) => {
+            app.set_status(format!("Failed to run {}: {}", editor, e));
+            return;
+        }
+    }
+    let previews = match edited
+        .map_err(|e| format!("Failed to read names: {}", e))
+        .and_then(|text| panex_core::rename::preview_from_lines(paths, &text))
+    {
+        Ok(previews) => previews,
+        Err(e) => {
+            app.set_status(format!("{} — nothing renamed", e));
+            return;
+        }
+    };
+    if let Some(p) = previews.iter().find(|p| p.problem.is_some()) {
+        app.set_status(format!(
+            "{}: {} — nothing renamed",
+            p.new_name,
+            p.problem.as_deref().unwrap_or_default()
+        ));
+        return;
+    }
+    if !previews.iter().any(|p| p.changes()) {
+        app.set_status("No names changed".to_string());
+        return;
+    }
+    match panex_core::rename::apply_renames(&previews) {
+        Ok(n) => {
+            app.set_status(format!("Renamed {} item(s)", n));
+            if let Some(pane) = app.pane_map.get_mut(pane_id) {
+                pane.selected_paths.clear();
+            }
+        }
+        Err(e) => app.set_status(format!("Rename failed, rolled back: {}", e)),
+    }
+    app.refresh_pane(pane_id);
+}
+
 fn start_delete(app: &mut App) {
     let pane_id = app.active_pane_id.clone();
     let paths: Vec<String> = if let Some(pane) = app.pane_map.get(&pane_id) {
@@ -1501,35 +2683,210 @@ fn start_delete(app: &mut App) {
     };
 }
 
+/// The selection, or the focused entry, each with what it opens with.
 fn open_in_default_app(app: &mut App) {
-    let pane_id = app.active_pane_id.clone();
-    if let Some(pane) = app.pane_map.get(&pane_id) {
-        if pane.focus_index >= 0 && (pane.focus_index as usize) < pane.entries.len() {
-            let path = pane.entries[pane.focus_index as usize].path.clone();
-            open_file_with_config(app, &path);
+    let paths = chosen_paths(app);
+    open_files(app, &paths);
+}
+
+/// Open each of `paths` with the first opener configured for it, the files
+/// that share one in a single run; the rest with the system default.
+fn open_files(app: &mut App, paths: &[String]) {
+    let mut runs: Vec<(Opener, Vec<String>)> = Vec::new();
+    let mut unclaimed = Vec::new();
+    for path in paths {
+        match app.config.tui_openers(std::slice::from_ref(path)).into_iter().next() {
+            Some(opener) => match runs.iter_mut().find(|(o, _)| *o == opener) {
+                Some((_, group)) => group.push(path.clone()),
+                None => runs.push((opener, vec![path.clone()])),
+            },
+            None => unclaimed.push(path.clone()),
         }
     }
+    for (opener, group) in runs {
+        open_with(app, opener, group);
+    }
+    open_by_default(app, &unclaimed);
 }
 
-/// Open a file using TUI config. Terminal commands (hx, nvim, etc.) open in a new terminal tab.
-fn open_file_with_config(app: &mut App, path: &str) {
-    let custom_app = panex_core::get_extension(path)
-        .and_then(|ext| app.config.get_tui_app(&ext).cloned());
+/// Where a program runs when nothing says: a new terminal where one can be
+/// shown, and this one over SSH, in tmux or without a desktop.
+fn default_run() -> RunIn {
+    if panex_core::can_open_terminal_window() {
+        RunIn::Terminal
+    } else {
+        RunIn::Current
+    }
+}
 
-    if let Some(cmd) = custom_app {
-        // Open in a new terminal tab
-        if let Err(e) = panex_core::open_in_terminal_with_command(&cmd, &[path]) {
-            app.set_status(format!("Open failed: {}", e));
+/// Run `opener` on `paths` where it says, or where `default_run` does.
+/// This terminal is the event loop's to hand over, once the key is handled.
+fn open_with(app: &mut App, opener: Opener, paths: Vec<String>) {
+    let run = opener.run.unwrap_or_else(default_run);
+    if run == RunIn::Current {
+        match &mut app.suspend {
+            Some(Suspend::Open { runs }) => runs.push((opener, paths)),
+            _ => app.suspend = Some(Suspend::Open { runs: vec![(opener, paths)] }),
         }
-    } else {
-        // Fall back to OS default
+        return;
+    }
+    if let Err(e) = panex_core::apps::launch(&opener, &paths, run) {
+        app.set_status(format!("Open failed: {}", e));
+    }
+}
+
+/// The chosen files in `program` — the editor or the pager — in this
+/// terminal, which is there wherever PanEx is.
+fn open_here(app: &mut App, program: &str) {
+    let paths: Vec<String> = chosen_paths(app)
+        .into_iter()
+        .filter(|p| !std::path::Path::new(p).is_dir())
+        .collect();
+    if paths.is_empty() {
+        app.set_status("No file to open".to_string());
+        return;
+    }
+    let opener = Opener {
+        run: Some(RunIn::Current),
+        ..Opener::new(program)
+    };
+    open_with(app, opener, paths);
+}
+
+/// `!`: a command for the shell, run in this folder.
+fn start_shell(app: &mut App) {
+    let dir = app
+        .pane_map
+        .get(&app.active_pane_id)
+        .map(|p| p.current_path.clone())
+        .unwrap_or_default();
+    app.mode = AppMode::Prompt {
+        title: "Shell (%f file, %s selection, %d folder)".to_string(),
+        input: String::new(),
+        cursor: 0,
+        action: PromptAction::Shell(dir),
+    };
+}
+
+/// A `!` command with `%f` made the focused entry, `%s` the selection (or,
+/// with none, the focused entry) and `%d` the folder, each quoted for the
+/// shell. `%%` is a percent sign; anything else after `%` is left alone.
+fn expand_placeholders(line: &str, focused: Option<&str>, chosen: &[String], dir: &str) -> String {
+    let mut out = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let expansion = match (c, chars.peek()) {
+            ('%', Some('f')) => focused.map(panex_core::quote_arg).unwrap_or_default(),
+            ('%', Some('s')) => chosen
+                .iter()
+                .map(|p| panex_core::quote_arg(p))
+                .collect::<Vec<_>>()
+                .join(" "),
+            ('%', Some('d')) => panex_core::quote_arg(dir),
+            ('%', Some('%')) => "%".to_string(),
+            _ => {
+                out.push(c);
+                continue;
+            }
+        };
+        chars.next();
+        out.push_str(&expansion);
+    }
+    out
+}
+
+/// The system's own choice — `xdg-open`, `open`, `start` — a file at a time.
+fn open_by_default(app: &mut App, paths: &[String]) {
+    for path in paths {
         if let Err(e) = panex_core::open_entry(path) {
             app.set_status(format!("Open failed: {}", e));
         }
     }
 }
 
+/// `w`: what the chosen entries can be opened with — the configured
+/// openers, in the order Enter tries them, then the desktop's, then the
+/// system default.
+fn start_open_with(app: &mut App) {
+    let paths = chosen_paths(app);
+    if paths.is_empty() {
+        app.set_status("Nothing to open".to_string());
+        return;
+    }
+    let mut configured = app.config.tui_openers(&paths);
+    // Shown, and changed with Tab, as where each will run.
+    for opener in &mut configured {
+        opener.run.get_or_insert_with(default_run);
+    }
+    let mut choices: Vec<Option<Opener>> = panex_core::apps::open_with_choices(configured, &paths)
+        .into_iter()
+        .map(Some)
+        .collect();
+    choices.push(None);
+    app.mode = AppMode::OpenWith {
+        paths,
+        choices,
+        selected: 0,
+    };
+}
+
+fn handle_open_with(app: &mut App, key: KeyEvent) {
+    let AppMode::OpenWith { choices, selected, .. } = &mut app.mode else {
+        return;
+    };
+    let count = choices.len();
+    let chosen = match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.mode = AppMode::Normal;
+            return;
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            *selected = (*selected + count - 1) % count;
+            return;
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            *selected = (*selected + 1) % count;
+            return;
+        }
+        // Where the highlighted program runs, for this once.
+        KeyCode::Tab => {
+            if let Some(Some(opener)) = choices.get_mut(*selected) {
+                opener.run = Some(match opener.run.unwrap_or(RunIn::Terminal) {
+                    RunIn::Terminal => RunIn::Current,
+                    RunIn::Current => RunIn::Detached,
+                    RunIn::Detached => RunIn::Terminal,
+                });
+            }
+            return;
+        }
+        KeyCode::Enter => *selected,
+        KeyCode::Char(c @ '1'..='9') if (c as usize - '1' as usize) < count => {
+            c as usize - '1' as usize
+        }
+        _ => return,
+    };
+    let AppMode::OpenWith { paths, mut choices, .. } =
+        std::mem::replace(&mut app.mode, AppMode::Normal)
+    else {
+        return;
+    };
+    match choices.swap_remove(chosen) {
+        Some(opener) => open_with(app, opener, paths),
+        None => open_by_default(app, &paths),
+    }
+}
+
 fn open_in_terminal(app: &mut App) {
+    // Where no window can be opened, the shell runs here instead, in the
+    // pane's folder; exiting it comes back.
+    if default_run() == RunIn::Current {
+        let shell = std::env::var("SHELL")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| "sh".to_string());
+        open_with(app, Opener::new(&shell), Vec::new());
+        return;
+    }
     let pane_id = app.active_pane_id.clone();
     if let Some(pane) = app.pane_map.get(&pane_id) {
         if let Err(e) = panex_core::open_in_terminal(&pane.current_path) {
@@ -1546,7 +2903,7 @@ fn start_new_file(app: &mut App) {
         .unwrap_or_default();
     let default = "untitled.txt".to_string();
     app.mode = AppMode::Prompt {
-        title: "New File".to_string(),
+        title: "New File (a/b.txt, {x,y}.rs, several, names)".to_string(),
         input: default.clone(),
         cursor: default.len(),
         action: PromptAction::NewFile(dir),
@@ -1561,7 +2918,7 @@ fn start_new_folder(app: &mut App) {
         .unwrap_or_default();
     let default = "New Folder".to_string();
     app.mode = AppMode::Prompt {
-        title: "New Folder".to_string(),
+        title: "New Folder (a/b, {x,y}, several, names)".to_string(),
         input: default.clone(),
         cursor: default.len(),
         action: PromptAction::NewFolder(dir),
@@ -1582,6 +2939,138 @@ fn deselect_all(app: &mut App) {
     }
 }
 
+/// Re-read every pane — which asks git afresh, too, about each pane's repository.
+fn refresh_all_panes(app: &mut App) {
+    let pane_ids: Vec<String> = app.pane_map.keys().cloned().collect();
+    for pid in pane_ids {
+        app.refresh_pane(&pid);
+    }
+}
+
+/// The selection in the active pane, in listing order — or, with nothing
+/// selected, the focused entry.
+fn chosen_paths(app: &App) -> Vec<String> {
+    let Some(pane) = app.pane_map.get(&app.active_pane_id) else {
+        return Vec::new();
+    };
+    if pane.selected_paths.is_empty() {
+        let focused = usize::try_from(pane.focus_index)
+            .ok()
+            .and_then(|i| pane.entries.get(i));
+        focused.map(|e| vec![e.path.clone()]).unwrap_or_default()
+    } else {
+        pane.entries
+            .iter()
+            .filter(|e| pane.selected_paths.contains(&e.path))
+            .map(|e| e.path.clone())
+            .collect()
+    }
+}
+
+fn focused_path(app: &App) -> Option<String> {
+    let pane = app.pane_map.get(&app.active_pane_id)?;
+    let entry = pane.entries.get(usize::try_from(pane.focus_index).ok()?)?;
+    Some(entry.path.clone())
+}
+
+/// Stage or unstage the chosen paths.
+fn git_apply(app: &mut App, apply: fn(&[String]) -> Result<(), String>, done: &str) {
+    let paths = chosen_paths(app);
+    if paths.is_empty() {
+        return;
+    }
+    match apply(&paths) {
+        Ok(()) => app.set_status(format!("{} {} item(s)", done, paths.len())),
+        Err(e) => app.set_status(format!("Error: {}", e)),
+    }
+    refresh_all_panes(app);
+}
+
+fn start_git_discard(app: &mut App) {
+    let paths = chosen_paths(app);
+    let what = match paths.as_slice() {
+        [] => return,
+        [path] => format!(
+            "\"{}\"",
+            std::path::Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        ),
+        _ => format!("{} items", paths.len()),
+    };
+    app.mode = AppMode::Confirm {
+        title: "Discard changes".to_string(),
+        message: format!("Put {} back as HEAD has it? Changed files go to the trash.", what),
+        action: ConfirmAction::GitDiscard(paths),
+        // A discard is the one git action here that loses work; it should
+        // take a deliberate choice, not a stray Enter.
+        selected: 1,
+    };
+}
+
+/// The focused file against HEAD, in the diff viewer.
+fn git_diff(app: &mut App) {
+    let Some(path) = focused_path(app) else {
+        return;
+    };
+    match panex_core::git::diff(&path) {
+        Ok(lines) if lines.iter().all(|l| l.change == panex_core::diff::LineChange::Same) => {
+            app.set_status("No changes against HEAD".to_string());
+        }
+        Ok(lines) => {
+            app.mode = AppMode::Diff {
+                view: crate::diff::DiffView::new(format!("{}@HEAD", path), path, lines),
+            };
+        }
+        Err(e) => app.set_status(format!("Error: {}", e)),
+    }
+}
+
+/// How many commits the log view reads. It is for a glance back, not for
+/// digging; `git log` in a terminal is there for that.
+const GIT_LOG_LIMIT: usize = 200;
+
+/// The history of the focused entry — or, in an empty folder, of the folder
+/// itself.
+fn git_log(app: &mut App) {
+    let Some(path) = focused_path(app).or_else(|| {
+        app.pane_map
+            .get(&app.active_pane_id)
+            .map(|p| p.current_path.clone())
+    }) else {
+        return;
+    };
+    match panex_core::git::log(&path, GIT_LOG_LIMIT) {
+        Ok(commits) if commits.is_empty() => app.set_status("No commits touch this".to_string()),
+        Ok(commits) => {
+            app.mode = AppMode::GitLog {
+                path,
+                commits,
+                offset: 0,
+            };
+        }
+        Err(e) => app.set_status(format!("Error: {}", e)),
+    }
+}
+
+fn handle_git_log(app: &mut App, key: KeyEvent) {
+    let AppMode::GitLog { commits, offset, .. } = &mut app.mode else {
+        return;
+    };
+    let last = commits.len().saturating_sub(1);
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => app.mode = AppMode::Normal,
+        KeyCode::Down | KeyCode::Char('j') => *offset = (*offset + 1).min(last),
+        KeyCode::Up | KeyCode::Char('k') => *offset = offset.saturating_sub(1),
+        KeyCode::PageDown | KeyCode::Char(' ') => *offset = (*offset + 10).min(last),
+        KeyCode::PageUp => *offset = offset.saturating_sub(10),
+        KeyCode::Char('g') | KeyCode::Home => *offset = 0,
+        KeyCode::Char('G') | KeyCode::End => *offset = last,
+        _ => {}
+    }
+}
+
 fn refilter_all_panes(app: &mut App) {
     let pane_ids: Vec<String> = app.pane_map.keys().cloned().collect();
     for pid in pane_ids {
@@ -1652,17 +3141,19 @@ mod tests {
 /// Drives real mouse events through the whole path — hit-test, focus, open —
 /// rather than just the pairing predicate above.
 #[cfg(test)]
-mod click_tests {
+pub(crate) mod click_tests {
     use super::*;
+    use panex_core::config::{LayoutPreset, PresetSplit};
     use ratatui::backend::TestBackend;
     use ratatui::Terminal;
+    use std::collections::BTreeMap;
     use std::path::PathBuf;
 
     /// A directory under the system temp dir, removed when the test ends.
-    struct TempDir(PathBuf);
+    pub(crate) struct TempDir(pub(crate) PathBuf);
 
     impl TempDir {
-        fn new(tag: &str) -> Self {
+        pub(crate) fn new(tag: &str) -> Self {
             let path = std::env::temp_dir().join(format!("panex-{}-{}", tag, std::process::id()));
             let _ = std::fs::remove_dir_all(&path);
             std::fs::create_dir_all(&path).unwrap();
@@ -1782,10 +3273,19 @@ mod click_tests {
         assert_eq!(app.pane_map[&pane_id].focus_index, 2);
     }
 
-    fn press(app: &mut App, code: KeyCode) {
+    pub(super) fn press(app: &mut App, code: KeyCode) {
         handle_key_event(app, KeyEvent::from(code));
     }
 
+    /// `line` typed into the command palette and run.
+    pub(super) fn run(app: &mut App, line: &str) {
+        press(app, KeyCode::Char(':'));
+        for c in line.chars() {
+            press(app, KeyCode::Char(c));
+        }
+        press(app, KeyCode::Enter);
+    }
+
     /// The window has to expire, or a click resting on a row from minutes ago
     /// would open it.
     #[test]
@@ -1803,19 +3303,925 @@ mod click_tests {
 
         assert_eq!(app.pane_map[&pane_id].current_path, tmp.0.to_string_lossy());
     }
+
+    #[test]
+    fn clicking_a_header_sorts_by_it_and_again_reverses() {
+        let tmp = TempDir::new("header");
+        std::fs::write(tmp.0.join("a.txt"), vec![0u8; 64 * 1024]).unwrap();
+        std::fs::write(tmp.0.join("b.txt"), b"").unwrap();
+        let (mut app, pane_id, x, y) = pane_showing(&tmp);
+        let list = app.pane_views[&pane_id].list_area;
+        let areas = crate::columns::areas(app.pane_columns(&pane_id), list);
+        let size = app
+            .pane_columns(&pane_id)
+            .iter()
+            .position(|s| s.column == crate::columns::Column::Size)
+            .unwrap();
+        let size_x = areas[size].x + 1;
+
+        click(&mut app, size_x, y - 1);
+        assert!(app.sort_field == SortField::Size && app.sort_direction == SortDirection::Asc);
+        assert_eq!(app.pane_map[&pane_id].entries[0].name, "b.txt");
+        click(&mut app, size_x, y - 1);
+        assert!(app.sort_direction == SortDirection::Desc);
+        assert_eq!(app.pane_map[&pane_id].entries[0].name, "a.txt");
+        // The name column is to the left of everything else.
+        click(&mut app, x, y - 1);
+        assert!(app.sort_field == SortField::Name && app.sort_direction == SortDirection::Asc);
+    }
+
+    #[test]
+    fn a_git_column_shows_the_status_and_its_header_sorts_by_it() {
+        use panex_core::git::GitStatus;
+        let tmp = TempDir::new("git-column");
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(tmp.0.join(name), b"").unwrap();
+        }
+        let (mut app, pane_id, _, _) = pane_showing(&tmp);
+        app.pane_map.get_mut(&pane_id).unwrap().columns =
+            crate::columns::parse_list(["name", "git"]).unwrap();
+        let statuses = [
+            ("a.txt", None),
+            ("b.txt", Some(GitStatus::Modified)),
+            ("c.txt", Some(GitStatus::Untracked)),
+        ];
+        for entry in app.raw_entries_map.get_mut(&pane_id).unwrap() {
+            entry.git = statuses.iter().find(|(n, _)| *n == entry.name).unwrap().1;
+        }
+        app.refilter_pane(&pane_id);
+        let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+        terminal.draw(|frame| crate::ui::draw(frame, &mut app)).unwrap();
+        let list = app.pane_views[&pane_id].list_area;
+        let git_x = crate::columns::areas(app.pane_columns(&pane_id), list)[1].x;
+        let names = |app: &App| -> Vec<String> {
+            app.pane_map[&pane_id].entries.iter().map(|e| e.name.clone()).collect()
+        };
+
+        click(&mut app, git_x, list.y - 1);
+        assert!(app.sort_field == SortField::Git && app.sort_direction == SortDirection::Asc);
+        assert_eq!(names(&app), ["a.txt", "c.txt", "b.txt"]);
+        click(&mut app, git_x, list.y - 1);
+        assert_eq!(names(&app), ["b.txt", "c.txt", "a.txt"]);
+        let text = |column, entry| app.columns.cell(column, entry, 0);
+        let entries = &app.pane_map[&pane_id].entries;
+        assert_eq!(text(crate::columns::Column::Git, &entries[0]), "M");
+        assert_eq!(text(crate::columns::Column::Git, &entries[2]), "");
+    }
+
+    #[test]
+    fn a_pane_can_have_its_own_columns() {
+        let tmp = TempDir::new("columns");
+        std::fs::create_dir_all(tmp.0.join("full/one")).unwrap();
+        std::fs::write(tmp.0.join("full/two"), b"").unwrap();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+
+        run(&mut app, "columns items:4 owner");
+        let columns: Vec<&str> =
+            app.pane_columns(&pane_id).iter().map(|s| s.column.name()).collect();
+        assert_eq!(columns, ["name", "items", "owner"]);
+        // The count is read once it is shown.
+        assert_eq!(app.pane_map[&pane_id].entries[0].items, Some(2));
+
+        run(&mut app, "columns nope");
+        assert!(app.status_message.as_deref().unwrap().contains("unknown column"));
+        run(&mut app, "columns default");
+        assert_eq!(app.pane_columns(&pane_id), app.columns.default.as_slice());
+    }
+
+    // Comparing two panes and syncing them, driven through the keyboard.
+
+    /// Two panes side by side, `left` active and showing `a`, the other `b`.
+    fn side_by_side(a: &TempDir, b: &TempDir) -> (App, String, String) {
+        let mut app = App::new().unwrap();
+        let left = app.active_pane_id.clone();
+        app.navigate_to(&left, &a.0.to_string_lossy());
+        press(&mut app, KeyCode::Char('|'));
+        let right = collect_leaf_ids(&app.layout_root)
+            .into_iter()
+            .find(|id| *id != left)
+            .unwrap();
+        app.navigate_to(&right, &b.0.to_string_lossy());
+        (app, left, right)
+    }
+
+    #[test]
+    fn c_marks_the_rows_that_differ() {
+        let (a, b) = (TempDir::new("cmp-a"), TempDir::new("cmp-b"));
+        std::fs::write(a.0.join("only-here.txt"), "x").unwrap();
+        std::fs::write(a.0.join("both.txt"), "x").unwrap();
+        std::fs::copy(a.0.join("both.txt"), b.0.join("both.txt")).unwrap();
+        let (mut app, _, _) = side_by_side(&a, &b);
+
+        press(&mut app, KeyCode::Char('c'));
+
+        let marks = &app.comparison.as_ref().expect("no comparison").marks;
+        let only = a.0.join("only-here.txt").to_string_lossy().to_string();
+        assert_eq!(marks.get(&only), Some(&crate::compare::RowMark::Unique));
+
+        press(&mut app, KeyCode::Char('c'));
+        assert!(app.comparison.is_none(), "c again should stop comparing");
+    }
+
+    /// Reading the files is left to another thread; the marks come in later.
+    #[test]
+    fn c_by_content_compares_in_the_background() {
+        let (a, b) = (TempDir::new("content-a"), TempDir::new("content-b"));
+        std::fs::write(a.0.join("same.txt"), "one").unwrap();
+        std::fs::write(a.0.join("changed.txt"), "one").unwrap();
+        std::fs::write(b.0.join("changed.txt"), "two").unwrap();
+        std::fs::copy(a.0.join("same.txt"), b.0.join("same.txt")).unwrap();
+        let (mut app, _, _) = side_by_side(&a, &b);
+
+        press(&mut app, KeyCode::Char('C'));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.comparison.as_ref().unwrap().is_busy() && Instant::now() < deadline {
+            app.pump_compare();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let marks = &app.comparison.as_ref().unwrap().marks;
+        let path = |name: &str| a.0.join(name).to_string_lossy().to_string();
+        assert!(marks.contains_key(&path("changed.txt")));
+        assert!(!marks.contains_key(&path("same.txt")));
+
+        // Refreshing starts it again, and a sync waits for it.
+        let left = app.active_pane_id.clone();
+        app.refresh_pane(&left);
+        assert!(app.comparison.as_ref().unwrap().is_busy());
+        press(&mut app, KeyCode::Char('Y'));
+        assert!(app.mode == AppMode::Normal);
+    }
+
+    /// The plan is shown first and nothing is written until it is accepted.
+    #[test]
+    fn y_shows_the_plan_and_enter_carries_it_out() {
+        let (a, b) = (TempDir::new("sync-a"), TempDir::new("sync-b"));
+        std::fs::write(a.0.join("new.txt"), "x").unwrap();
+        let (mut app, _, _) = side_by_side(&a, &b);
+
+        press(&mut app, KeyCode::Char('c'));
+        press(&mut app, KeyCode::Char('Y'));
+        match &app.mode {
+            AppMode::SyncPlan { plan, .. } => assert_eq!(plan.steps.len(), 1),
+            _ => panic!("no sync plan shown"),
+        }
+        assert!(!b.0.join("new.txt").exists(), "written before it was accepted");
+
+        press(&mut app, KeyCode::Enter);
+        assert!(b.0.join("new.txt").exists(), "the sync did not copy");
+        assert!(
+            app.comparison.as_ref().unwrap().diffs.is_empty(),
+            "the comparison should have been run again after the sync"
+        );
+    }
+
+    #[test]
+    fn d_diffs_the_files_focused_in_both_panes() {
+        let (a, b) = (TempDir::new("diff-a"), TempDir::new("diff-b"));
+        std::fs::write(a.0.join("app.toml"), "port = 1\n").unwrap();
+        std::fs::write(b.0.join("app.toml"), "port = 2\n").unwrap();
+        let (mut app, _, _) = side_by_side(&a, &b);
+
+        press(&mut app, KeyCode::Char('D'));
+        let AppMode::Diff { view } = &app.mode else {
+            panic!("no diff shown: {:?}", app.status_message);
+        };
+        assert!(view.left_path.starts_with(&*a.0.to_string_lossy()));
+        assert_eq!(view.change_count(), 1);
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.mode == AppMode::Normal);
+    }
+
+    #[test]
+    fn identical_files_are_reported_not_shown() {
+        let (a, b) = (TempDir::new("same-a"), TempDir::new("same-b"));
+        std::fs::write(a.0.join("f.txt"), "same\n").unwrap();
+        std::fs::write(b.0.join("f.txt"), "same\n").unwrap();
+        let (mut app, _, _) = side_by_side(&a, &b);
+
+        press(&mut app, KeyCode::Char('D'));
+        assert!(app.mode == AppMode::Normal);
+        assert_eq!(app.status_message.as_deref(), Some("Files are identical"));
+    }
+
+    #[test]
+    fn a_lone_pane_has_nothing_to_compare_with() {
+        let mut app = App::new().unwrap();
+        press(&mut app, KeyCode::Char('c'));
+        assert!(app.comparison.is_none());
+    }
+
+    // Renaming a selection by a rule, from the keyboard.
+
+    fn ctrl(app: &mut App, c: char) {
+        handle_key_event(app, KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+    }
+
+    fn app_in(dir: &TempDir, names: &[&str]) -> App {
+        for name in names {
+            std::fs::write(dir.0.join(name), name).unwrap();
+        }
+        let mut app = App::new().unwrap();
+        let pane = app.active_pane_id.clone();
+        app.navigate_to(&pane, &dir.0.to_string_lossy());
+        app
+    }
+
+    #[test]
+    fn r_with_a_selection_renames_all_of_it_by_a_rule() {
+        let dir = TempDir::new("bulk");
+        let mut app = app_in(&dir, &["a.txt", "b.txt"]);
+        ctrl(&mut app, 'a');
+        press(&mut app, KeyCode::Char('r'));
+        assert!(matches!(app.mode, AppMode::BulkRename { .. }));
+
+        press(&mut app, KeyCode::Tab);
+        type_text(&mut app, "note-{n:02}");
+        ctrl(&mut app, 't');
+        ctrl(&mut app, 't'); // lower, then UPPER
+        press(&mut app, KeyCode::Enter);
+
+        assert!(app.mode == AppMode::Normal, "{:?}", app.status_message);
+        assert!(dir.0.join("NOTE-01.txt").exists());
+        assert!(dir.0.join("NOTE-02.txt").exists());
+    }
+
+    /// `E` only asks for the terminal; what the editor changes is applied
+    /// once the event loop has run it. `sed` stands in for the editor.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn e_renames_whatever_lines_the_editor_changed() {
+        let dir = TempDir::new("editor");
+        let mut app = app_in(&dir, &["draft.md", "keep.md"]);
+        press(&mut app, KeyCode::Char('E'));
+        let Some(Suspend::EditNames { pane_id, paths }) = app.suspend.take() else {
+            panic!("E should hand over to the editor");
+        };
+        assert_eq!(paths.len(), 2);
+
+        edit_names(&mut app, &pane_id, &paths, "sed -i s/draft/final/");
+        assert!(dir.0.join("final.md").exists(), "{:?}", app.status_message);
+        assert!(dir.0.join("keep.md").exists());
+
+        // A deleted line leaves nothing to match names by, so nothing moves.
+        let paths: Vec<String> = ["keep.md", "final.md"]
+            .iter()
+            .map(|n| dir.0.join(n).to_string_lossy().to_string())
+            .collect();
+        edit_names(&mut app, &pane_id, &paths, "sed -i 1d");
+        assert!(dir.0.join("keep.md").exists() && dir.0.join("final.md").exists());
+        assert!(app.status_message.as_deref().unwrap().contains("nothing renamed"));
+    }
+
+    /// A rule that would give two entries one name is refused, with the
+    /// dialog left open to fix it and nothing touched on disk.
+    #[test]
+    fn a_collision_keeps_the_dialog_open() {
+        let dir = TempDir::new("bulk-collide");
+        let mut app = app_in(&dir, &["x1.md", "x2.md"]);
+        ctrl(&mut app, 'a');
+        press(&mut app, KeyCode::Char('R'));
+        ctrl(&mut app, 'r');
+        type_text(&mut app, r"\d");
+        press(&mut app, KeyCode::Enter);
+
+        assert!(matches!(app.mode, AppMode::BulkRename { .. }));
+        assert!(dir.0.join("x1.md").exists() && dir.0.join("x2.md").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn the_names_file_is_new_and_private_each_time() {
+        use std::os::unix::fs::PermissionsExt;
+        let one = write_private_temp("panex-names-test", "a\n").unwrap();
+        let two = write_private_temp("panex-names-test", "b\n").unwrap();
+        assert_ne!(one, two);
+        assert_eq!(std::fs::read_to_string(&one).unwrap(), "a\n");
+        let mode = std::fs::metadata(&one).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = std::fs::remove_file(one);
+        let _ = std::fs::remove_file(two);
+    }
+
+    /// Type `text` over the prompt's default name and press Enter.
+    fn answer(app: &mut App, text: &str) {
+        if let AppMode::Prompt { input, cursor, .. } = &mut app.mode {
+            input.clear();
+            *cursor = 0;
+        }
+        for c in text.chars() {
+            press(app, KeyCode::Char(c));
+        }
+        press(app, KeyCode::Enter);
+    }
+
+    #[test]
+    fn n_creates_nested_and_expanded_paths_and_focuses_the_first() {
+        let dir = TempDir::new("create");
+        std::fs::write(dir.0.join("aaa.txt"), "").unwrap();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &dir.0.to_string_lossy());
+
+        press(&mut app, KeyCode::Char('n'));
+        answer(&mut app, "src/{lib,main}.rs, notes.md");
+
+        assert!(dir.0.join("src/lib.rs").is_file());
+        assert!(dir.0.join("src/main.rs").is_file());
+        assert!(dir.0.join("notes.md").is_file());
+        let pane = &app.pane_map[&pane_id];
+        assert_eq!(pane.entries[pane.focus_index as usize].name, "src");
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("Created src/lib.rs, src/main.rs, notes.md")
+        );
+    }
+
+    #[test]
+    fn p_opens_a_preset_from_the_picker() {
+        let tmp = std::env::temp_dir().to_string_lossy().to_string();
+        let gone = format!("{}/panex-no-such-dir/deeper", tmp);
+        let mut app = App::new().unwrap();
+        app.config.layouts.clear();
+        app.config.layouts.insert(
+            "deploy".to_string(),
+            LayoutPreset::Split {
+                split: PresetSplit::Vertical,
+                ratio: None,
+                panes: vec![
+                    LayoutPreset::Path(tmp.clone()),
+                    LayoutPreset::Path("/".to_string()),
+                    LayoutPreset::Path(gone.clone()),
+                ],
+            },
+        );
+
+        press(&mut app, KeyCode::Char('P'));
+        assert!(app.mode == AppMode::LayoutPicker { selected: 0 });
+        press(&mut app, KeyCode::Enter);
+
+        let leaves = collect_leaf_ids(&app.layout_root);
+        let paths: Vec<&str> = leaves
+            .iter()
+            .map(|id| app.pane_map[id].current_path.as_str())
+            .collect();
+        assert_eq!(paths, [tmp.as_str(), "/", tmp.as_str()]);
+        assert_eq!(app.active_pane_id, leaves[0]);
+        assert!(
+            app.status_message.as_deref().unwrap().contains(&gone),
+            "the missing folder should be named: {:?}",
+            app.status_message
+        );
+        // Three even columns: a third, then half of what is left.
+        let crate::layout::LayoutNode::Split { ratio, .. } = &app.layout_root else {
+            panic!("expected a split");
+        };
+        assert!((ratio - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    /// `gg` waits on the first `g`, acts on the second, and the `G` it did
+    /// not rebind still goes to the bottom.
+    #[test]
+    fn a_sequence_binding_replaces_the_single_key() {
+        let tmp = TempDir::new("keymap");
+        for name in ["a", "b", "c"] {
+            std::fs::create_dir(tmp.0.join(name)).unwrap();
+        }
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+        let config = BTreeMap::from([("gg".to_string(), "top".to_string())]);
+        app.keymap = crate::keys::Keymap::new(&config);
+
+        press(&mut app, KeyCode::Char('G'));
+        assert_eq!(app.pane_map[&pane_id].focus_index, 2);
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.pane_map[&pane_id].focus_index, 2, "one g is not enough");
+        assert_eq!(app.status_message.as_deref(), Some("g …"));
+        press(&mut app, KeyCode::Char('g'));
+        assert_eq!(app.pane_map[&pane_id].focus_index, 0);
+        assert_eq!(app.status_message, None, "the sequence is done");
+
+        // A sequence that goes nowhere takes its hint with it too.
+        press(&mut app, KeyCode::Char('g'));
+        press(&mut app, KeyCode::Char('Q'));
+        assert!(app.pending_keys.is_empty());
+        assert_eq!(app.status_message, None);
+
+        // Esc drops a half-typed sequence without clearing the selection.
+        app.pane_map.get_mut(&pane_id).unwrap().selected_paths.insert("x".to_string());
+        press(&mut app, KeyCode::Char('g'));
+        press(&mut app, KeyCode::Esc);
+        assert!(app.pending_keys.is_empty());
+        assert!(!app.pane_map[&pane_id].selected_paths.is_empty());
+    }
+
+    #[test]
+    fn commands_take_arguments() {
+        let tmp = TempDir::new("palette");
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+
+        run(&mut app, "mkdir inner");
+        assert!(tmp.0.join("inner").is_dir());
+        run(&mut app, "cd inner");
+        assert_eq!(
+            app.pane_map[&pane_id].current_path,
+            tmp.0.join("inner").to_string_lossy()
+        );
+        run(&mut app, "cd ..");
+        assert_eq!(app.pane_map[&pane_id].current_path, tmp.0.to_string_lossy());
+
+        run(&mut app, "sort size desc");
+        assert!(app.sort_field == SortField::Size && app.sort_direction == SortDirection::Desc);
+        assert_eq!(app.recent_commands[0], "sort size desc");
+        assert!(app.mode == AppMode::Normal);
+
+        run(&mut app, "sort sideways");
+        assert!(app.status_message.as_deref().unwrap().starts_with("Usage"));
+        assert_eq!(app.recent_commands.len(), 4, "a failed line is not remembered");
+    }
+
+    /// A bare word runs the entry it picked out, fuzzily.
+    #[test]
+    fn a_word_runs_the_best_match() {
+        let mut app = App::new().unwrap();
+        run(&mut app, "splitv");
+        assert_eq!(collect_leaf_ids(&app.layout_root).len(), 2);
+        assert_eq!(app.recent_commands[0], "split-vertical");
+    }
+
+    /// Each entry is drawn with its key, which is what makes the palette a
+    /// way to learn the shortcuts as well as to skip them.
+    #[test]
+    fn entries_show_their_keys() {
+        use ratatui::backend::TestBackend;
+        use ratatui::Terminal;
+
+        let mut app = App::new().unwrap();
+        press(&mut app, KeyCode::Char(':'));
+        for c in "copy".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| crate::ui::draw(frame, &mut app)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("y / Ctrl+c"), "{}", screen);
+    }
+
+    #[test]
+    fn measured_folders_show_their_size_and_sort_by_it() {
+        let tmp = TempDir::new("measure");
+        std::fs::create_dir_all(tmp.0.join("big/deep")).unwrap();
+        std::fs::write(tmp.0.join("big/deep/data"), vec![1u8; 256 * 1024]).unwrap();
+        std::fs::create_dir(tmp.0.join("a-small")).unwrap();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+        run(&mut app, "sort size desc");
+
+        press(&mut app, KeyCode::Char('u'));
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.dir_sizes.is_busy() && Instant::now() < deadline {
+            app.pump_dir_sizes();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let entries = &app.pane_map[&pane_id].entries;
+        assert_eq!(entries[0].name, "big");
+        assert!(entries[0].contents_size.unwrap() >= 256 * 1024);
+        assert!(app.status_message.as_deref().unwrap().starts_with("Measured 2"));
+
+        // Coming back, the sizes are already there.
+        app.navigate_to(&pane_id, &tmp.0.join("big").to_string_lossy());
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+        assert!(app.pane_map[&pane_id].entries[0].contents_size.is_some());
+    }
+
+    #[test]
+    fn the_disk_usage_view_walks_the_tree_and_asks_before_deleting() {
+        let tmp = TempDir::new("usage");
+        std::fs::create_dir_all(tmp.0.join("big/deep")).unwrap();
+        std::fs::write(tmp.0.join("big/deep/data"), vec![1u8; 256 * 1024]).unwrap();
+        std::fs::write(tmp.0.join("small"), b"x").unwrap();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+
+        press(&mut app, KeyCode::Char('U'));
+        assert!(app.mode == AppMode::Usage);
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.usage.as_ref().unwrap().is_scanning() && Instant::now() < deadline {
+            app.pump_usage();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let name = |app: &App| {
+            let usage = app.usage.as_ref().unwrap();
+            usage.nodes[usage.selected.unwrap()].name.clone()
+        };
+        assert_eq!(name(&app), "big");
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(name(&app), "deep");
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Char('j'));
+        assert_eq!(name(&app), "small");
+
+        // Saying no goes back to the view, with nothing gone.
+        press(&mut app, KeyCode::Char('d'));
+        assert!(matches!(app.mode, AppMode::Confirm { .. }));
+        press(&mut app, KeyCode::Char('n'));
+        assert!(app.mode == AppMode::Usage);
+        assert!(tmp.0.join("small").exists());
+
+        press(&mut app, KeyCode::Esc);
+        assert!(app.mode == AppMode::Normal && app.usage.is_none());
+    }
+
+    #[test]
+    fn git_status_comes_in_and_ignored_files_can_be_hidden() {
+        let tmp = TempDir::new("git");
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&tmp.0)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?}: {:?}", args, output);
+        };
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(tmp.0.join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(tmp.0.join("kept.txt"), "one").unwrap();
+        git(&["add", "."]);
+        let who = ["-c", "user.name=PanEx", "-c", "user.email=panex@example.com"];
+        git(&[&who[..], &["-c", "commit.gpgsign=false", "commit", "-qm", "first"]].concat());
+        std::fs::write(tmp.0.join("kept.txt"), "two").unwrap();
+        std::fs::write(tmp.0.join("new.txt"), "new").unwrap();
+        std::fs::write(tmp.0.join("run.log"), "log").unwrap();
+
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.show_hidden = true;
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.git.is_busy() && Instant::now() < deadline {
+            app.pump_git();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        let status = |app: &App, name: &str| {
+            let entries = &app.pane_map[&pane_id].entries;
+            entries.iter().find(|e| e.name == name).map(|e| e.git)
+        };
+        use panex_core::git::GitStatus;
+        assert_eq!(status(&app, "kept.txt"), Some(Some(GitStatus::Modified)));
+        assert_eq!(status(&app, "new.txt"), Some(Some(GitStatus::Untracked)));
+        assert_eq!(status(&app, "run.log"), Some(Some(GitStatus::Ignored)));
+        assert_eq!(status(&app, ".gitignore"), Some(None));
+        let repo = app.git.repo(&tmp.0.to_string_lossy()).unwrap();
+        assert_eq!(repo.describe(), "main");
+
+        press(&mut app, KeyCode::Char('i'));
+        assert_eq!(status(&app, "run.log"), None, "hidden");
+        assert!(status(&app, "new.txt").is_some());
+        press(&mut app, KeyCode::Char('i'));
+        assert!(status(&app, "run.log").is_some());
+    }
+
+    #[test]
+    fn the_focused_file_can_be_staged_diffed_and_its_history_read() {
+        let tmp = TempDir::new("git-actions");
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&tmp.0)
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success(), "git {:?}: {:?}", args, output);
+        };
+        git(&["init", "-q", "-b", "main"]);
+        std::fs::write(tmp.0.join("kept.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        let who = ["-c", "user.name=PanEx", "-c", "user.email=panex@example.com"];
+        git(&[&who[..], &["-c", "commit.gpgsign=false", "commit", "-qm", "first"]].concat());
+        std::fs::write(tmp.0.join("kept.txt"), "two\n").unwrap();
+
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &tmp.0.to_string_lossy());
+        app.pane_map.get_mut(&pane_id).unwrap().focus_index = 0;
+        let status = |app: &mut App| {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while app.git.is_busy() && Instant::now() < deadline {
+                app.pump_git();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            app.pane_map[&pane_id].entries[0].git
+        };
+        use panex_core::git::GitStatus;
+        assert_eq!(status(&mut app), Some(GitStatus::Modified));
+
+        press(&mut app, KeyCode::Char('v'));
+        press(&mut app, KeyCode::Char('a'));
+        assert_eq!(status(&mut app), Some(GitStatus::Staged));
+        press(&mut app, KeyCode::Char('v'));
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(status(&mut app), Some(GitStatus::Modified));
+
+        press(&mut app, KeyCode::Char('v'));
+        press(&mut app, KeyCode::Char('d'));
+        let AppMode::Diff { view } = &app.mode else {
+            panic!("no diff view");
+        };
+        assert_eq!(view.change_count(), 1);
+        press(&mut app, KeyCode::Esc);
+
+        press(&mut app, KeyCode::Char('v'));
+        press(&mut app, KeyCode::Char('l'));
+        let AppMode::GitLog { commits, .. } = &app.mode else {
+            panic!("no log view");
+        };
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].subject, "first");
+        press(&mut app, KeyCode::Esc);
+
+        // Discarding waits for a deliberate yes: Enter alone is "no".
+        press(&mut app, KeyCode::Char('v'));
+        press(&mut app, KeyCode::Char('x'));
+        assert!(matches!(app.mode, AppMode::Confirm { .. }));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(std::fs::read_to_string(tmp.0.join("kept.txt")).unwrap(), "two\n");
+    }
+
+    #[test]
+    fn back_and_forward_retrace_a_pane_and_recent_folders_can_be_picked() {
+        let tmp = TempDir::new("history");
+        std::fs::create_dir_all(tmp.0.join("a/inner")).unwrap();
+        std::fs::create_dir(tmp.0.join("b")).unwrap();
+        std::fs::write(tmp.0.join("file"), b"").unwrap();
+        let path = |name: &str| {
+            let path = if name.is_empty() { tmp.0.clone() } else { tmp.0.join(name) };
+            path.to_string_lossy().to_string()
+        };
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        let here = |app: &App| app.pane_map[&pane_id].current_path.clone();
+        let focused = |app: &App| {
+            let pane = &app.pane_map[&pane_id];
+            pane.entries[pane.focus_index as usize].path.clone()
+        };
+        app.navigate_to(&pane_id, &path(""));
+
+        app.focus_path(&pane_id, &path("a"));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(here(&app), path("a"));
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(focused(&app), path("a"), "going up lands on the folder left");
+
+        app.focus_path(&pane_id, &path("file"));
+        run(&mut app, &format!("cd {}", path("b")));
+        assert_eq!(here(&app), path("b"));
+        press(&mut app, KeyCode::Char('H'));
+        assert_eq!(here(&app), path(""));
+        assert_eq!(focused(&app), path("file"), "the cursor is where it was left");
+        press(&mut app, KeyCode::Char('H'));
+        assert_eq!(here(&app), path("a"));
+        press(&mut app, KeyCode::Char('L'));
+        run(&mut app, "forward");
+        assert_eq!(here(&app), path("b"));
+        run(&mut app, "forward");
+        assert_eq!(app.status_message.as_deref(), Some("Nothing further forward"));
+
+        assert_eq!(app.recent.paths[..3], [path("b"), path(""), path("a")]);
+        handle_key_event(&mut app, KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert!(matches!(app.mode, AppMode::RecentList { selected: 1, .. }));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(here(&app), path("a"));
+    }
+
+    #[test]
+    fn z_jumps_to_a_visited_folder_by_a_few_letters() {
+        let tmp = TempDir::new("jump");
+        std::fs::create_dir_all(tmp.0.join("projects/zebra-app")).unwrap();
+        std::fs::create_dir_all(tmp.0.join("imported")).unwrap();
+        let path = |name: &str| tmp.0.join(name).to_string_lossy().to_string();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &path("projects/zebra-app"));
+        app.navigate_to(&pane_id, &path(""));
+
+        press(&mut app, KeyCode::Char('z'));
+        for c in "panex-jump zebra".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert!(matches!(&app.mode, AppMode::Jump { matches, .. } if matches[0] == path("projects/zebra-app")));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.pane_map[&pane_id].current_path, path("projects/zebra-app"));
+
+        let db = tmp.0.join("autojump.txt");
+        std::fs::write(&db, format!("12.5\t{}\n", path("imported"))).unwrap();
+        run(&mut app, &format!("import-dirs {}", db.display()));
+        assert_eq!(app.status_message.as_deref(), Some("Imported 1 folder(s)"));
+        assert_eq!(jump_matches(&app, "panex-jump imported"), [path("imported")]);
+    }
+
+    #[test]
+    fn the_path_prompt_completes_loosely_as_it_is_typed() {
+        let tmp = TempDir::new("path-edit");
+        std::fs::create_dir_all(tmp.0.join("dev/PanEx/crates")).unwrap();
+        std::fs::create_dir_all(tmp.0.join("dev/.hidden-cr")).unwrap();
+        let path = |name: &str| tmp.0.join(name).to_string_lossy().to_string();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.show_hidden = false;
+        app.navigate_to(&pane_id, &path(""));
+
+        open_path_edit(&mut app, pane_id.clone(), String::new());
+        for c in "d/pa/c".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        let AppMode::PathEdit { completions, .. } = &app.mode else {
+            panic!("the prompt closed");
+        };
+        assert_eq!(completions.len(), 1, "hidden folders are not offered");
+        press(&mut app, KeyCode::Tab);
+        assert!(matches!(&app.mode, AppMode::PathEdit { input, .. } if *input == format!("{}/", path("dev/PanEx/crates"))));
+        for c in "../..".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.pane_map[&pane_id].current_path, path("dev"));
+    }
+
+    /// Only going: marking writes the config, which a test must not.
+    #[test]
+    fn a_quote_and_a_key_go_to_the_favorite_under_it() {
+        let tmp = TempDir::new("marks");
+        let path = tmp.0.to_string_lossy().to_string();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        // Whatever the real config has under these keys is set aside.
+        app.config.favorites.entries.retain(|f| !matches!(f.key, Some('1' | '§')));
+        app.config.favorites.entries.push(panex_core::config::Favorite {
+            key: Some('1'),
+            label: Some("scratch".to_string()),
+            ..panex_core::config::Favorite::new(&path)
+        });
+
+        press(&mut app, KeyCode::Char('\''));
+        assert!(matches!(app.mode, AppMode::Mark { set: false, .. }));
+        press(&mut app, KeyCode::Char('1'));
+        assert_eq!(app.pane_map[&pane_id].current_path, path);
+
+        press(&mut app, KeyCode::Char('\''));
+        press(&mut app, KeyCode::Char('§'));
+        assert_eq!(app.status_message.as_deref(), Some("Nothing is under '§"));
+    }
+
+    #[test]
+    fn in_pick_mode_enter_picks_files_and_goes_into_folders() {
+        let tmp = TempDir::new("pick");
+        std::fs::create_dir_all(tmp.0.join("dir/inner")).unwrap();
+        std::fs::write(tmp.0.join("dir/a.txt"), b"").unwrap();
+        std::fs::write(tmp.0.join("dir/b.txt"), b"").unwrap();
+        let path = |name: &str| tmp.0.join(name).to_string_lossy().to_string();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.pick = Some(crate::app::Pick::One);
+        app.navigate_to(&pane_id, &path(""));
+
+        app.focus_path(&pane_id, &path("dir"));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.pane_map[&pane_id].current_path, path("dir"));
+        assert!(!app.should_quit);
+
+        handle_key_event(&mut app, KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+        press(&mut app, KeyCode::Enter);
+        assert!(!app.should_quit, "--pick takes one");
+
+        app.pick = Some(crate::app::Pick::Many);
+        press(&mut app, KeyCode::Enter);
+        assert!(app.should_quit);
+        let mut picked = app.picked.clone();
+        picked.sort();
+        assert_eq!(picked, [path("dir/a.txt"), path("dir/b.txt"), path("dir/inner")]);
+    }
+
+    #[test]
+    fn folders_from_the_command_line_open_a_pane_each() {
+        let tmp = TempDir::new("folders");
+        std::fs::create_dir_all(tmp.0.join("a")).unwrap();
+        std::fs::create_dir_all(tmp.0.join("b")).unwrap();
+        std::fs::write(tmp.0.join("b/.env"), b"").unwrap();
+        let path = |name: &str| tmp.0.join(name).to_string_lossy().to_string();
+        let mut app = App::new().unwrap();
+        app.show_hidden = false;
+
+        app.open_folders(&[path("a"), path("b")], Some(panex_core::config::PresetSplit::Horizontal));
+        assert!(matches!(
+            app.layout_root,
+            layout::LayoutNode::Split { direction: SplitDirection::Horizontal, .. }
+        ));
+        let panes = collect_leaf_ids(&app.layout_root);
+        assert_eq!(app.pane_map[&panes[1]].current_path, path("b"));
+
+        assert!(app.select_file(&path("b/.env")), "a dotfile is shown to be focused");
+        assert_eq!(app.active_pane_id, panes[1]);
+        assert_eq!(focused_path(&app).as_deref(), Some(path("b/.env").as_str()));
+    }
+
+    #[test]
+    fn open_with_runs_the_chosen_program_on_every_selected_file() {
+        let tmp = TempDir::new("open-with");
+        std::fs::write(tmp.0.join("a.log"), b"").unwrap();
+        std::fs::write(tmp.0.join("b.log"), b"").unwrap();
+        let path = |name: &str| tmp.0.join(name).to_string_lossy().to_string();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.config.open.tui = [(
+            ".log".to_string(),
+            vec![
+                Opener::new("less"),
+                Opener {
+                    run: Some(RunIn::Terminal),
+                    ..Opener::new("rm")
+                },
+            ],
+        )]
+        .into();
+        app.navigate_to(&pane_id, &path(""));
+        handle_key_event(&mut app, KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+
+        press(&mut app, KeyCode::Char('w'));
+        let AppMode::OpenWith { paths, choices, .. } = &app.mode else {
+            panic!("no picker");
+        };
+        assert_eq!(paths, &[path("a.log"), path("b.log")]);
+        assert_eq!(choices[0].as_ref().map(|o| o.command.as_str()), Some("less"));
+        assert_eq!(choices.last(), Some(&None), "the system default comes last");
+
+        // rm, told to run here rather than in a new terminal: once, on both.
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Tab);
+        press(&mut app, KeyCode::Enter);
+        assert!(matches!(app.mode, AppMode::Normal));
+        let job = app.suspend.take().expect("this terminal is asked for");
+        assert!(matches!(&job, Suspend::Open { runs } if runs.len() == 1 && runs[0].1.len() == 2));
+        run_suspended(&mut app, job);
+        assert!(!tmp.0.join("a.log").exists() && !tmp.0.join("b.log").exists());
+    }
+
+    /// Run by the shell here rather than through `run_suspended`, which
+    /// waits for Enter on a terminal.
+    #[test]
+    fn a_bang_command_gets_the_selection_quoted_for_the_shell() {
+        let tmp = TempDir::new("bang");
+        std::fs::write(tmp.0.join("it's a.txt"), b"one\n").unwrap();
+        std::fs::write(tmp.0.join("b.txt"), b"two\n").unwrap();
+        let path = |name: &str| tmp.0.join(name).to_string_lossy().to_string();
+        let mut app = App::new().unwrap();
+        let pane_id = app.active_pane_id.clone();
+        app.navigate_to(&pane_id, &path(""));
+        handle_key_event(&mut app, KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+
+        press(&mut app, KeyCode::Char('!'));
+        for c in "cat %s > %d/out; echo 100%% >> out".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        let Some(Suspend::Shell { command, dir }) = app.suspend.take() else {
+            panic!("the terminal is not asked for");
+        };
+        assert_eq!(dir, path(""));
+        let status = panex_core::shell_command(&command, &[]).current_dir(&dir).status();
+        assert!(status.unwrap().success(), "{}", command);
+        assert_eq!(std::fs::read_to_string(tmp.0.join("out")).unwrap(), "two\none\n100%\n");
+    }
 }
 
 /// The card pane's place in the layout, and what the rest of the keyboard
 /// does while it is the active one.
 #[cfg(test)]
 mod oko_pane_tests {
+    use super::click_tests::press;
     use super::*;
     use crate::app::App;
 
-    fn press(app: &mut App, code: KeyCode) {
-        handle_key_event(app, KeyEvent::from(code));
-    }
-
     #[test]
     fn the_card_pane_opens_on_the_left() {
         let mut app = App::new().unwrap();
@@ -0,0 +1,552 @@
+//! Key bindings: what the file list does, kept apart from which keys do it.
+//!
+//! Every normal-mode key goes through a `Keymap` — the defaults below with the
+//! `[keys]` section of the config laid over them:
+//!
+//! ```toml
+//! [keys]
+//! gg = "top"
+//! dd = "delete"
+//! "alt+p" = "paste"
+//! d = "none"        # unbind a default
+//! ```
+//!
+//! A chord is one key with optional `ctrl+`, `alt+` and `shift+` in front
+//! (`ctrl+f`, `shift+up`, `f2`, `enter`), or a sequence of them: a run of
+//! plain characters (`gg`), or keys separated by spaces (`g enter`). The
+//! dialogs keep their own fixed keys — typing into a text field is not
+//! something to rebind.
+
+use std::collections::BTreeMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Section {
+    Navigation,
+    Panes,
+    Selection,
+    Files,
+    Git,
+    View,
+    Other,
+}
+
+impl Section {
+    pub const ALL: [Section; 7] = [
+        Section::Navigation,
+        Section::Panes,
+        Section::Selection,
+        Section::Files,
+        Section::Git,
+        Section::View,
+        Section::Other,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Section::Navigation => "Navigation",
+            Section::Panes => "Panes",
+            Section::Selection => "Selection",
+            Section::Files => "Files",
+            Section::Git => "Git",
+            Section::View => "View",
+            Section::Other => "Other",
+        }
+    }
+}
+
+/// Defines `Action` together with the table that names, describes and binds
+/// each one, so that adding an action is one line and cannot leave it out of
+/// the help or the config names.
+macro_rules! actions {
+    ($($action:ident, $name:literal, $section:ident, $help:literal, [$($key:literal),*];)*) => {
+        #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+        pub enum Action {
+            $($action,)*
+        }
+
+        impl Action {
+            /// In the order the help lists them.
+            pub const ALL: &'static [Action] = &[$(Action::$action,)*];
+
+            /// What it is called in `[keys]`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Action::$action => $name,)*
+                }
+            }
+
+            pub fn help(self) -> &'static str {
+                match self {
+                    $(Action::$action => $help,)*
+                }
+            }
+
+            pub fn section(self) -> Section {
+                match self {
+                    $(Action::$action => Section::$section,)*
+                }
+            }
+
+            fn default_keys(self) -> &'static [&'static str] {
+                match self {
+                    $(Action::$action => &[$($key),*],)*
+                }
+            }
+        }
+    };
+}
+
+actions! {
+    Up, "up", Navigation, "move focus up", ["k", "up"];
+    Down, "down", Navigation, "move focus down", ["j", "down"];
+    Open, "open", Navigation, "open / enter folder", ["enter"];
+    Parent, "parent", Navigation, "up one directory", ["backspace"];
+    Back, "back", Navigation, "back to the previous folder", ["alt+left", "H"];
+    Forward, "forward", Navigation, "forward again", ["alt+right", "L"];
+    Home, "home", Navigation, "go to home", ["~", "home"];
+    Top, "top", Navigation, "jump to top", ["g"];
+    Bottom, "bottom", Navigation, "jump to bottom", ["G"];
+    PageUp, "page-up", Navigation, "page up", ["pgup"];
+    PageDown, "page-down", Navigation, "page down", ["pgdn"];
+    NextPane, "next-pane", Navigation, "next pane", ["tab"];
+    EditPath, "edit-path", Navigation, "edit path / favorites", ["e"];
+    ToggleFavorite, "toggle-favorite", Navigation, "toggle favorite", ["f"];
+    Mark, "mark", Navigation, "put this folder under a key", ["m"];
+    GotoMark, "goto-mark", Navigation, "go to the folder under a key", ["'"];
+    Recent, "recent", Navigation, "recently visited folders", ["ctrl+r"];
+    Jump, "jump", Navigation, "jump to a frequent folder", ["z"];
+    SplitVertical, "split-vertical", Panes, "split vertical", ["|"];
+    SplitHorizontal, "split-horizontal", Panes, "split horizontal", ["_"];
+    Grow, "grow", Panes, "grow pane 25%", ["+", "="];
+    Shrink, "shrink", Panes, "shrink pane 25%", ["-"];
+    ClosePane, "close-pane", Panes, "close pane", ["W"];
+    Compare, "compare", Panes, "compare with neighbour", ["c"];
+    CompareContent, "compare-content", Panes, "compare by content", ["C"];
+    Sync, "sync", Panes, "sync compared panes", ["Y"];
+    Diff, "diff", Panes, "diff focused files", ["D"];
+    Layouts, "layouts", Panes, "open a saved layout", ["P"];
+    Oko, "oko", Panes, "tab cards (oko)", ["O"];
+    SelectUp, "select-up", Selection, "extend selection up", ["shift+up", "K"];
+    SelectDown, "select-down", Selection, "extend selection down", ["shift+down", "J"];
+    SelectAll, "select-all", Selection, "select all", ["ctrl+a"];
+    Deselect, "deselect", Selection, "clear selection", ["esc"];
+    Copy, "copy", Files, "copy", ["y", "ctrl+c"];
+    Cut, "cut", Files, "cut", ["x"];
+    Paste, "paste", Files, "paste", ["p", "ctrl+v"];
+    Rename, "rename", Files, "rename", ["r", "f2"];
+    BulkRename, "bulk-rename", Files, "rename by pattern", ["R"];
+    EditNames, "edit-names", Files, "rename in $EDITOR", ["E"];
+    Delete, "delete", Files, "delete (trash)", ["d", "del"];
+    NewFile, "new-file", Files, "new file", ["n"];
+    NewFolder, "new-folder", Files, "new folder", ["N"];
+    OpenDefault, "open-default", Files, "open in default app", ["o"];
+    OpenWith, "open-with", Files, "open with…", ["w"];
+    OpenTerminal, "open-terminal", Files, "open in terminal", ["t"];
+    Edit, "edit", Files, "edit in $EDITOR, here", ["f4"];
+    Page, "page", Files, "view in $PAGER, here", ["f3", "V"];
+    Shell, "shell", Files, "run a shell command", ["!"];
+    GitStage, "git-stage", Git, "stage", ["va"];
+    GitUnstage, "git-unstage", Git, "unstage", ["vu"];
+    GitDiscard, "git-discard", Git, "discard changes", ["vx"];
+    GitDiff, "git-diff", Git, "diff against HEAD", ["vd"];
+    GitLog, "git-log", Git, "history of the focused entry", ["vl"];
+    Search, "search", View, "search", ["/", "ctrl+f"];
+    SortField, "sort-field", View, "sort by next column", ["s"];
+    SortDirection, "sort-direction", View, "sort direction", ["S"];
+    ToggleHidden, "toggle-hidden", View, "show hidden files", ["."];
+    ToggleIgnored, "toggle-ignored", View, "hide git-ignored files", ["i"];
+    Refresh, "refresh", View, "refresh", ["f5"];
+    DirSizes, "dir-sizes", View, "measure folder sizes", ["u"];
+    DiskUsage, "disk-usage", View, "disk usage below this folder", ["U"];
+    Palette, "palette", Other, "command palette", [":", "ctrl+p"];
+    Help, "help", Other, "toggle this help", ["?"];
+    Quit, "quit", Other, "quit", ["q"];
+}
+
+impl Action {
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::ALL.iter().copied().find(|a| a.name() == name)
+    }
+
+    /// Means the same whatever a pane is showing, so it still works in the
+    /// card pane, which has no files to act on.
+    pub fn acts_on_panes(self) -> bool {
+        matches!(
+            self,
+            Action::Quit
+                | Action::Oko
+                | Action::ClosePane
+                | Action::SplitVertical
+                | Action::SplitHorizontal
+                | Action::Grow
+                | Action::Shrink
+                | Action::Help
+                | Action::NextPane
+        )
+    }
+}
+
+/// One key press, as a binding sees it. Shift is folded into the character
+/// for printable keys — the terminal already sent `G` rather than `g` — so
+/// `shift+g` and `G` are the same key however the terminal reports it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let mut code = code;
+        if let KeyCode::Char(c) = code {
+            if modifiers.contains(KeyModifiers::SHIFT) {
+                code = KeyCode::Char(c.to_uppercase().next().unwrap_or(c));
+            }
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        if code == KeyCode::BackTab {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Key { code, modifiers }
+    }
+
+    pub fn from_event(event: KeyEvent) -> Self {
+        Key::new(event.code, event.modifiers)
+    }
+
+    fn plain_char(&self) -> Option<char> {
+        match self.code {
+            KeyCode::Char(c) if self.modifiers.is_empty() && c != ' ' => Some(c),
+            _ => None,
+        }
+    }
+}
+
+const NAMED_KEYS: &[(&str, KeyCode)] = &[
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("enter", KeyCode::Enter),
+    ("return", KeyCode::Enter),
+    ("backspace", KeyCode::Backspace),
+    ("bksp", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("del", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("ins", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pgup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("pgdn", KeyCode::PageDown),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("esc", KeyCode::Esc),
+    ("escape", KeyCode::Esc),
+    ("space", KeyCode::Char(' ')),
+];
+
+fn named_key(name: &str) -> Option<KeyCode> {
+    let lower = name.to_ascii_lowercase();
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        return (1..=24).contains(&n).then_some(KeyCode::F(n));
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|(key, _)| *key == lower)
+        .map(|(_, code)| *code)
+}
+
+/// One space-separated part of a chord: a key with modifiers, a named key,
+/// or a run of plain characters.
+fn parse_token(token: &str) -> Result<Vec<Key>, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = token;
+    loop {
+        let lower = rest.to_ascii_lowercase();
+        let stripped = [
+            ("ctrl+", KeyModifiers::CONTROL),
+            ("control+", KeyModifiers::CONTROL),
+            ("alt+", KeyModifiers::ALT),
+            ("shift+", KeyModifiers::SHIFT),
+        ]
+        .into_iter()
+        .find(|(prefix, _)| lower.starts_with(prefix) && rest.len() > prefix.len());
+        match stripped {
+            Some((prefix, modifier)) => {
+                modifiers |= modifier;
+                rest = &rest[prefix.len()..];
+            }
+            None => break,
+        }
+    }
+
+    if rest.chars().count() > 1 {
+        if let Some(code) = named_key(rest) {
+            return Ok(vec![Key::new(code, modifiers)]);
+        }
+        if !modifiers.is_empty() {
+            return Err(format!("'{}' is not a key", rest));
+        }
+        return Ok(rest
+            .chars()
+            .map(|c| Key::new(KeyCode::Char(c), KeyModifiers::NONE))
+            .collect());
+    }
+    let c = rest.chars().next().ok_or("empty key")?;
+    Ok(vec![Key::new(KeyCode::Char(c), modifiers)])
+}
+
+/// `"gg"`, `"ctrl+f"`, `"g enter"` — see the module docs.
+pub fn parse_chord(chord: &str) -> Result<Vec<Key>, String> {
+    let mut keys = Vec::new();
+    for token in chord.split_whitespace() {
+        keys.extend(parse_token(token)?);
+    }
+    if keys.is_empty() {
+        return Err("empty key".to_string());
+    }
+    Ok(keys)
+}
+
+fn describe_key(key: &Key) -> String {
+    let mut out = String::new();
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        out.push_str("Ctrl+");
+    }
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        out.push_str("Alt+");
+    }
+    if key.modifiers.contains(KeyModifiers::SHIFT) {
+        out.push_str("Shift+");
+    }
+    match key.code {
+        KeyCode::Char(' ') => out.push_str("Space"),
+        KeyCode::Char(c) => out.push(c),
+        KeyCode::Up => out.push('↑'),
+        KeyCode::Down => out.push('↓'),
+        KeyCode::Left => out.push('←'),
+        KeyCode::Right => out.push('→'),
+        KeyCode::Backspace => out.push_str("Bksp"),
+        KeyCode::Delete => out.push_str("Del"),
+        KeyCode::Insert => out.push_str("Ins"),
+        KeyCode::PageUp => out.push_str("PgUp"),
+        KeyCode::PageDown => out.push_str("PgDn"),
+        KeyCode::BackTab => out.push_str("Shift+Tab"),
+        KeyCode::F(n) => out.push_str(&format!("F{}", n)),
+        other => out.push_str(&format!("{:?}", other)),
+    }
+    out
+}
+
+/// How a chord is shown: `gg` for plain characters, spaced otherwise.
+pub fn describe(chord: &[Key]) -> String {
+    if chord.len() > 1 && chord.iter().all(|k| k.plain_char().is_some()) {
+        return chord.iter().filter_map(Key::plain_char).collect();
+    }
+    chord.iter().map(describe_key).collect::<Vec<_>>().join(" ")
+}
+
+pub enum Lookup {
+    Action(Action),
+    /// The keys so far begin a longer binding; wait for the next.
+    Pending,
+    Unbound,
+}
+
+pub struct Keymap {
+    bindings: Vec<(Vec<Key>, Action)>,
+    /// What was wrong with `[keys]`, for reporting at startup.
+    pub problems: Vec<String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(&BTreeMap::new())
+    }
+}
+
+/// Neither chord can be told apart from the other as keys arrive: they are
+/// the same, or one is where the other starts.
+fn overlaps(a: &[Key], b: &[Key]) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+impl Keymap {
+    /// The defaults with `config` (the `[keys]` section) laid over them. A
+    /// configured chord takes the place of any default it overlaps, so
+    /// binding `gg` gives up the default `g` rather than being shadowed by it.
+    pub fn new(config: &BTreeMap<String, String>) -> Self {
+        let mut problems = Vec::new();
+        // `None` is an unbinding: it takes a default's place and does nothing.
+        let mut configured: Vec<(Vec<Key>, Option<Action>, &str)> = Vec::new();
+        for (chord, name) in config {
+            let keys = match parse_chord(chord) {
+                Ok(keys) => keys,
+                Err(e) => {
+                    problems.push(format!("[keys] \"{}\": {}", chord, e));
+                    continue;
+                }
+            };
+            let action = match name.as_str() {
+                "none" | "" => None,
+                name => match Action::from_name(name) {
+                    Some(action) => Some(action),
+                    None => {
+                        problems.push(format!("[keys] \"{}\": unknown action '{}'", chord, name));
+                        continue;
+                    }
+                },
+            };
+            if let Some((_, _, earlier)) = configured.iter().find(|(k, _, _)| overlaps(k, &keys)) {
+                problems.push(if parse_chord(earlier).ok().as_deref() == Some(&keys) {
+                    format!("[keys] \"{}\" and \"{}\" are the same key", earlier, chord)
+                } else {
+                    format!(
+                        "[keys] \"{}\" and \"{}\" conflict: one starts the other",
+                        earlier, chord
+                    )
+                });
+                continue;
+            }
+            configured.push((keys, action, chord));
+        }
+
+        let mut bindings: Vec<(Vec<Key>, Action)> = Vec::new();
+        for &action in Action::ALL {
+            for chord in action.default_keys() {
+                let keys = parse_chord(chord).expect("default key bindings parse");
+                if !configured.iter().any(|(k, _, _)| overlaps(k, &keys)) {
+                    bindings.push((keys, action));
+                }
+            }
+        }
+        bindings.extend(
+            configured
+                .into_iter()
+                .filter_map(|(keys, action, _)| Some((keys, action?))),
+        );
+        Keymap { bindings, problems }
+    }
+
+    pub fn lookup(&self, keys: &[Key]) -> Lookup {
+        let mut pending = false;
+        for (chord, action) in &self.bindings {
+            if chord.as_slice() == keys {
+                return Lookup::Action(*action);
+            }
+            pending |= chord.starts_with(keys);
+        }
+        if pending {
+            Lookup::Pending
+        } else {
+            Lookup::Unbound
+        }
+    }
+
+    /// Every chord bound to `action`, as shown to the user: defaults first,
+    /// then the configured ones.
+    pub fn keys_for(&self, action: Action) -> Vec<String> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| *a == action)
+            .map(|(keys, _)| describe(keys))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(pairs: &[(&str, &str)]) -> Keymap {
+        let config = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Keymap::new(&config)
+    }
+
+    fn press(keymap: &Keymap, chord: &str) -> Option<Action> {
+        match keymap.lookup(&parse_chord(chord).unwrap()) {
+            Lookup::Action(action) => Some(action),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn the_defaults_do_not_overlap() {
+        let keymap = Keymap::default();
+        assert!(keymap.problems.is_empty());
+        for (i, (a, _)) in keymap.bindings.iter().enumerate() {
+            for (b, _) in &keymap.bindings[i + 1..] {
+                assert!(!overlaps(a, b), "{} and {}", describe(a), describe(b));
+            }
+        }
+    }
+
+    #[test]
+    fn chords_parse() {
+        let ctrl_f = Key::new(KeyCode::Char('f'), KeyModifiers::CONTROL);
+        assert_eq!(parse_chord("ctrl+f").unwrap(), [ctrl_f]);
+        assert_eq!(parse_chord("Ctrl+f").unwrap(), [ctrl_f]);
+        assert_eq!(parse_chord("shift+g").unwrap(), parse_chord("G").unwrap());
+        assert_eq!(parse_chord("F2").unwrap(), [Key::new(KeyCode::F(2), KeyModifiers::NONE)]);
+        assert_eq!(parse_chord("gg").unwrap().len(), 2);
+        assert_eq!(parse_chord("g enter").unwrap()[1].code, KeyCode::Enter);
+        assert_eq!(parse_chord("ctrl++").unwrap()[0].code, KeyCode::Char('+'));
+        assert!(parse_chord("ctrl+nope").is_err());
+        assert!(parse_chord("").is_err());
+    }
+
+    /// What the terminal sends for Shift+j is `J` with SHIFT set, and some
+    /// terminals send `j` with SHIFT set; both are the `J` a binding names.
+    #[test]
+    fn shift_folds_into_the_character() {
+        let event = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT);
+        assert_eq!(Key::from_event(event('J')), parse_chord("J").unwrap()[0]);
+        assert_eq!(Key::from_event(event('j')), parse_chord("J").unwrap()[0]);
+    }
+
+    #[test]
+    fn sequences_wait_for_the_next_key() {
+        let keymap = map(&[("gg", "top"), ("dd", "delete")]);
+        assert!(keymap.problems.is_empty());
+        assert!(matches!(keymap.lookup(&parse_chord("g").unwrap()), Lookup::Pending));
+        assert_eq!(press(&keymap, "gg"), Some(Action::Top));
+        assert_eq!(press(&keymap, "dd"), Some(Action::Delete));
+        // The defaults they replaced are gone, the others are not.
+        assert_eq!(keymap.keys_for(Action::Delete), ["Del", "dd"]);
+        assert_eq!(press(&keymap, "G"), Some(Action::Bottom));
+    }
+
+    #[test]
+    fn none_unbinds_a_default() {
+        let keymap = map(&[("d", "none")]);
+        assert_eq!(press(&keymap, "d"), None);
+        assert_eq!(keymap.keys_for(Action::Delete), ["Del"]);
+    }
+
+    #[test]
+    fn problems_are_reported() {
+        let keymap = map(&[
+            ("ctrl+p", "paste"),
+            ("Ctrl+p", "copy"),
+            ("z", "frobnicate"),
+            ("alt+nope", "quit"),
+            ("x", "cut"),
+            ("xy", "copy"),
+        ]);
+        assert_eq!(keymap.problems.len(), 4, "{:?}", keymap.problems);
+        // Of two spellings, the one that sorts first is kept.
+        assert_eq!(press(&keymap, "ctrl+p"), Some(Action::Copy));
+        assert!(keymap.problems.iter().any(|p| p.contains("same key")));
+        assert!(keymap.problems.iter().any(|p| p.contains("frobnicate")));
+    }
+}
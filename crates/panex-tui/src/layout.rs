@@ -1,10 +1,12 @@
-#[derive(Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum SplitDirection {
     Vertical,   // left | right
     Horizontal, // top / bottom
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum LayoutNode {
     Leaf {
         pane_id: String,
@@ -1,11 +1,26 @@
 mod app;
+mod args;
+mod columns;
+mod complete;
+mod compare;
+mod diff;
+mod dirsize;
+mod filetype;
+mod gitstatus;
+mod history;
 mod input;
+mod keys;
 mod layout;
 mod oko;
+mod palette;
+mod rename;
+mod session;
 mod sort;
+mod theme;
 mod ui;
+mod usage;
 
-use std::io;
+use std::io::{self, IsTerminal, Write};
 use std::time::Duration;
 
 use crossterm::event::{self, DisableMouseCapture, EnableMouseCapture, Event};
@@ -22,50 +37,139 @@ fn print_help() {
 A multi-pane terminal file explorer.
 
 USAGE:
-    panex [OPTIONS]
+    panex [OPTIONS] [DIR...]
+
+    Each DIR opens in a pane of its own, side by side.
 
 OPTIONS:
     -h, -H, --help       Print this help
     -V, -v, --version    Print version
+    --no-restore         Start fresh instead of restoring the last session
+    --session NAME       Use the named session instead of the default one
+    --layout NAME        Open a layout from [layouts.NAME] in the config
+    --choosedir FILE     On quit, write the active pane's folder to FILE
+    --pick               Pick a file: Enter prints its path and quits
+    --pick-multi         Pick files: Enter prints the selection, a path a line
+    --split WAY          Lay the folders out vertical (side by side, the
+                         default) or horizontal (one above the other)
+    --select FILE        Start with FILE focused, in its folder's pane
+    --config FILE        Read and save FILE instead of ~/.panex/config.toml
 
 CONFIG:
-    ~/.panex/config.toml — favorites, and per-extension openers
-    under [open.tui], e.g. \".md\" = \"nvim\".
+    ~/.panex/config.toml — favorites, and openers by extension,
+    MIME type or pattern under [open.tui], e.g. \".md\" = \"nvim\".
+
+SESSIONS:
+    The layout and each pane's folder are saved on quit to
+    ~/.panex/session.json (or ~/.panex/sessions/NAME.json) and
+    restored on the next start.
+
+SHELL:
+    To stay in the folder PanEx was left in, wrap it in a function
+    that reads --choosedir, e.g. for bash or zsh:
+
+        p() {{
+            local dir=\"$(mktemp)\"
+            panex --choosedir \"$dir\" \"$@\"
+            [ -s \"$dir\" ] && cd -- \"$(cat \"$dir\")\"
+            rm -f -- \"$dir\"
+        }}
 
-Opens in the current directory. Press ? inside the app for keyboard shortcuts.",
+    When picking, the screen is drawn on the terminal, so the paths
+    alone go to stdout: file=\"$(panex --pick)\". Nothing picked exits 1.
+
+With no DIR, opens in the current directory. Press ? inside the app for keyboard shortcuts.",
         version = env!("CARGO_PKG_VERSION"),
     );
 }
 
-/// Handles `--help`/`--version` and rejects anything else. The app takes no
-/// arguments, so whatever comes first is terminal either way — every branch
-/// exits the process. Must run before the terminal is put into raw mode.
-fn parse_args() {
-    let Some(arg) = std::env::args().nth(1) else {
-        return;
-    };
-    match arg.as_str() {
-        "-h" | "-H" | "--help" => {
+fn usage_error(message: &str) -> ! {
+    eprintln!("panex: {message}");
+    eprintln!("Try 'panex --help' for usage.");
+    std::process::exit(2);
+}
+
+/// Handles `--help`/`--version`, which exit, and collects the rest. Anything
+/// unrecognised exits with a usage error. Must run before the terminal is put
+/// into raw mode, so that what it prints can be read.
+fn parse_args() -> args::Options {
+    let mut options = match args::parse(std::env::args_os().skip(1)) {
+        Ok(args::Parsed::Run(options)) => options,
+        Ok(args::Parsed::Help) => {
             print_help();
             std::process::exit(0);
         }
-        "-V" | "-v" | "--version" => {
+        Ok(args::Parsed::Version) => {
             println!("panex {}", env!("CARGO_PKG_VERSION"));
             std::process::exit(0);
         }
-        other => {
-            eprintln!("panex: unrecognized argument '{other}'");
-            eprintln!("Try 'panex --help' for usage.");
-            std::process::exit(2);
+        Err(e) => usage_error(&e),
+    };
+    let cwd = std::env::current_dir()
+        .unwrap_or_else(|e| usage_error(&format!("Failed to read current directory: {}", e)));
+    if let Err(e) = args::check_paths(&mut options, &cwd) {
+        usage_error(&e);
+    }
+    // Before anything reads the config, so that all of it is this one.
+    if let Some(file) = &options.config {
+        if let Err(e) = panex_core::config::PanexConfig::read(file) {
+            usage_error(&format!("{}: {}", file.display(), e));
+        }
+        panex_core::config::PanexConfig::use_config_file(file.clone());
+    }
+    // Checked here, while an error can still be printed and read, rather
+    // than found out once the screen has been taken over.
+    if let Some(name) = &options.layout {
+        let config = panex_core::config::PanexConfig::load();
+        if !config.layouts.contains_key(name) {
+            let known: Vec<&str> = config.layouts.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                usage_error(&format!("no layout named '{name}': the config has no [layouts]"));
+            }
+            usage_error(&format!("no layout named '{name}' (have: {})", known.join(", ")));
         }
     }
+    if let Some(name) = &options.session {
+        if let Err(e) = session::check_name(name) {
+            usage_error(&e);
+        }
+    }
+    options
+}
+
+type Screen = Terminal<CrosstermBackend<Box<dyn Write>>>;
+
+/// Where the screen is drawn: stdout, unless that is being captured — as
+/// it is for `$(panex --pick)` — when it is the terminal itself, leaving
+/// stdout to the paths picked.
+fn screen_output() -> Box<dyn Write> {
+    if io::stdout().is_terminal() {
+        return Box::new(io::stdout());
+    }
+    match std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(tty) => Box::new(tty),
+        Err(_) => Box::new(io::stderr()),
+    }
 }
 
 /// Put the terminal back the way we found it. Safe to call more than once.
 fn restore_terminal() {
     let _ = disable_raw_mode();
-    let _ = execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen);
-    let _ = execute!(io::stdout(), crossterm::cursor::Show);
+    let mut out = screen_output();
+    let _ = execute!(out, DisableMouseCapture, LeaveAlternateScreen);
+    let _ = execute!(out, crossterm::cursor::Show);
+}
+
+/// Hand the terminal over to a foreground program — the ratatui screen steps
+/// aside exactly as it does on exit — and take it back once `run` returns.
+fn suspend(terminal: &mut Screen, run: impl FnOnce()) -> io::Result<()> {
+    restore_terminal();
+    run();
+    enable_raw_mode()?;
+    execute!(terminal.backend_mut(), EnterAlternateScreen, EnableMouseCapture)?;
+    // What the program left on the screen is not what ratatui thinks is
+    // there, so the next frame has to be drawn in full.
+    terminal.clear()
 }
 
 /// Without this, a panic unwinds straight past the cleanup at the end of
@@ -80,15 +184,15 @@ fn install_panic_hook() {
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    parse_args();
+    let options = parse_args();
     install_panic_hook();
 
     // Setup terminal
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let mut output = screen_output();
+    execute!(output, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(output);
+    let mut terminal: Screen = Terminal::new(backend)?;
 
     // Create app
     let mut app = match app::App::new() {
@@ -102,6 +206,38 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             std::process::exit(1);
         }
     };
+    app.pick = options.pick;
+    // A layout asked for by name is a fresh start in its own right, as
+    // are folders named on the command line; the saved session is not
+    // restored underneath either. Nor is a picker's: it is asked for from
+    // where it is run.
+    if let Some(name) = &options.layout {
+        let _ = app.open_layout(name);
+    } else if !options.folders.is_empty() {
+        let folders: Vec<String> = options
+            .folders
+            .iter()
+            .map(|f| f.to_string_lossy().to_string())
+            .collect();
+        app.open_folders(&folders, options.split);
+        if let Some(file) = &options.select {
+            app.select_file(&file.to_string_lossy());
+        }
+    } else if options.restore && options.pick.is_none() {
+        if let Some(saved) = session::Session::load(options.session.as_deref()) {
+            saved.restore(&mut app);
+        }
+    }
+    // Shown for the first few seconds; the help overlay lists them all.
+    match app.config_problems().as_slice() {
+        [] => {}
+        [only] => app.set_status(format!("{} — ignored", only)),
+        [first, rest @ ..] => app.set_status(format!(
+            "{} — ignored, and {} more (see ?)",
+            first,
+            rest.len()
+        )),
+    }
 
     // Event loop — render on demand only. Idle = no CPU.
     let status_ttl = Duration::from_secs(3);
@@ -109,9 +245,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     terminal.draw(|frame| ui::draw(frame, &mut app))?;
 
     // Crossterm's poll waits on terminal input and cannot be woken by another
-    // thread, so while the card view is open the loop wakes on its own to
-    // collect what oko has written. Only while it is open: idle stays idle.
-    let oko_tick = Duration::from_millis(150);
+    // thread, so while the card view is open, folders are being measured,
+    // scanned or compared, or git is being asked, the loop wakes on its own
+    // to collect what the other threads have produced. Only then: idle stays
+    // idle.
+    let tick = Duration::from_millis(150);
 
     loop {
         // Wake either for the next event or when the status message is due to expire.
@@ -119,8 +257,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             Some(at) => status_ttl.saturating_sub(at.elapsed()),
             None => idle_timeout,
         };
-        if app.oko_pane_id.is_some() {
-            timeout = timeout.min(oko_tick);
+        let scanning = app.usage.as_ref().is_some_and(|u| u.is_scanning());
+        let comparing = app.comparison.as_ref().is_some_and(|c| c.is_busy());
+        let busy = app.dir_sizes.is_busy() || app.git.is_busy() || scanning || comparing;
+        if app.oko_pane_id.is_some() || busy {
+            timeout = timeout.min(tick);
         }
 
         let mut dirty = false;
@@ -134,10 +275,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         input::handle_key_event(&mut app, key);
                         dirty = true;
                     }
-                    Event::Mouse(mouse) => {
-                        if input::handle_mouse_event(&mut app, mouse) {
-                            dirty = true;
-                        }
+                    Event::Mouse(mouse) if input::handle_mouse_event(&mut app, mouse) => {
+                        dirty = true;
                     }
                     Event::Resize(_, _) => dirty = true,
                     _ => {}
@@ -148,9 +287,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
+        if let Some(job) = app.suspend.take() {
+            suspend(&mut terminal, || input::run_suspended(&mut app, job))?;
+            dirty = true;
+        }
+
         if app.pump_oko() {
             dirty = true;
         }
+        if app.pump_dir_sizes() {
+            dirty = true;
+        }
+        if app.pump_usage() {
+            dirty = true;
+        }
+        if app.pump_git() {
+            dirty = true;
+        }
+        if app.pump_compare() {
+            dirty = true;
+        }
 
         // Auto-clear status message after 3 seconds
         if let Some(at) = app.status_message_at {
@@ -175,5 +331,34 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     execute!(terminal.backend_mut(), DisableMouseCapture, LeaveAlternateScreen)?;
     terminal.show_cursor()?;
 
+    // After the terminal is back, so a failure can be read. A picker leaves
+    // the session alone: a script's quick choice is not where you were.
+    if options.pick.is_none() {
+        if let Err(e) = session::Session::capture(&app).save(options.session.as_deref()) {
+            eprintln!("panex: session not saved: {}", e);
+        }
+    }
+    if let Err(e) = app.recent.save() {
+        eprintln!("panex: recent folders not saved: {}", e);
+    }
+    if let Err(e) = app.frecency.save() {
+        eprintln!("panex: folder ranking not saved: {}", e);
+    }
+    if let Some(file) = &options.choosedir {
+        let here = app.pane_map.get(&app.active_pane_id).map(|p| p.current_path.as_str());
+        if let Err(e) = std::fs::write(file, here.unwrap_or_default()) {
+            eprintln!("panex: could not write {}: {}", file.display(), e);
+        }
+    }
+    if options.pick.is_some() {
+        if app.picked.is_empty() {
+            std::process::exit(1);
+        }
+        let mut out = io::stdout().lock();
+        for path in &app.picked {
+            writeln!(out, "{}", path)?;
+        }
+    }
+
     Ok(())
 }
@@ -0,0 +1,245 @@
+//! The command palette (`:` or `Ctrl+P`): every action from the keymap's
+//! registry by name, with whatever key it is bound to, plus the few commands
+//! that take an argument and so have no key of their own.
+//!
+//! Running them is input.rs's business; this is the list and its matching.
+
+use crate::keys::Action;
+
+/// A command that needs an argument, e.g. `cd ~/dev`.
+#[derive(PartialEq, Debug)]
+pub struct Command {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub help: &'static str,
+}
+
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "cd",
+        usage: "cd DIR",
+        help: "go to a folder",
+    },
+    Command {
+        name: "mkdir",
+        usage: "mkdir NAMES",
+        help: "new folders",
+    },
+    Command {
+        name: "touch",
+        usage: "touch NAMES",
+        help: "new files",
+    },
+    Command {
+        name: "sort",
+        usage: "sort FIELD [asc|desc]",
+        help: "by name/ext/size/modified",
+    },
+    Command {
+        name: "import-dirs",
+        usage: "import-dirs [FILE]",
+        help: "add zoxide/autojump history to z",
+    },
+    Command {
+        name: "columns",
+        usage: "columns NAME[:WIDTH]... | default",
+        help: "this pane's columns",
+    },
+];
+
+/// How many command lines are remembered.
+pub const RECENT_LIMIT: usize = 50;
+
+#[derive(PartialEq, Debug)]
+pub enum Entry {
+    /// A command line run before, most recent first.
+    Recent(String),
+    Action(Action),
+    Command(&'static Command),
+}
+
+impl Entry {
+    /// What the entry runs as, or begins, when typed.
+    pub fn name(&self) -> &str {
+        match self {
+            Entry::Recent(line) => line,
+            Entry::Action(action) => action.name(),
+            Entry::Command(command) => command.name,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Palette {
+    pub input: String,
+    pub matches: Vec<Entry>,
+    pub selected: usize,
+}
+
+/// How well `pattern` matches `text` as a subsequence, ignoring case, or
+/// `None` if it does not. Runs of consecutive characters and matches at the
+/// start of a word score higher, so `sv` finds `split-vertical` before a
+/// name that merely contains an `s` and later a `v`.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut at = 0;
+    let mut previous: Option<usize> = None;
+    for p in pattern.to_lowercase().chars() {
+        let found = (at..text.len()).find(|&i| text[i] == p)?;
+        score += 1;
+        match previous {
+            Some(prev) if prev + 1 == found => score += 5,
+            // A match that starts late is a weaker one.
+            None => score -= found.min(10) as i32,
+            _ => {}
+        }
+        if found == 0 || matches!(text[found - 1], '-' | ' ' | '/' | '_') {
+            score += 8;
+        }
+        previous = Some(found);
+        at = found + 1;
+    }
+    Some(score)
+}
+
+impl Palette {
+    pub fn new(recent: &[String]) -> Self {
+        let mut palette = Palette {
+            input: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        palette.refresh(recent);
+        palette
+    }
+
+    /// Work out the list again for the current input.
+    ///
+    /// A bare word is matched against everything, recent lines first. Once
+    /// there is a space the command is being given its argument, so the
+    /// list narrows to that command and the recent lines that began the
+    /// same way — Enter then runs what was typed, not what is highlighted.
+    pub fn refresh(&mut self, recent: &[String]) {
+        let input = self.input.trim_start();
+        self.selected = 0;
+        self.matches = match input.split_once(' ') {
+            Some((name, _)) => recent
+                .iter()
+                .filter(|line| line.starts_with(input) && line.as_str() != input)
+                .map(|line| Entry::Recent(line.clone()))
+                .chain(
+                    COMMANDS
+                        .iter()
+                        .filter(|c| c.name == name)
+                        .map(Entry::Command),
+                )
+                .collect(),
+            None => {
+                let recent = recent.iter().filter_map(|line| {
+                    Some((fuzzy_score(input, line)?, Entry::Recent(line.clone())))
+                });
+                let mut rest: Vec<(i32, Entry)> = Action::ALL
+                    .iter()
+                    .filter(|a| **a != Action::Palette)
+                    .filter_map(|&a| {
+                        let score = fuzzy_score(input, a.name())
+                            .max(fuzzy_score(input, a.help()).map(|s| s - 10))?;
+                        Some((score, Entry::Action(a)))
+                    })
+                    .chain(COMMANDS.iter().filter_map(|c| {
+                        let score = fuzzy_score(input, c.name)
+                            .max(fuzzy_score(input, c.help).map(|s| s - 10))?;
+                        Some((score, Entry::Command(c)))
+                    }))
+                    .collect();
+                // Stable, so equal scores keep the registry's order — which
+                // is all of them when nothing has been typed yet.
+                rest.sort_by_key(|(score, _)| -score);
+                recent.chain(rest).map(|(_, entry)| entry).collect()
+            }
+        };
+    }
+
+    pub fn insert(&mut self, c: char, recent: &[String]) {
+        self.input.push(c);
+        self.refresh(recent);
+    }
+
+    pub fn backspace(&mut self, recent: &[String]) {
+        self.input.pop();
+        self.refresh(recent);
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let count = self.matches.len() as isize;
+        if count > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(count) as usize;
+        }
+    }
+
+    /// Put the highlighted entry in the input, ready for an argument.
+    pub fn complete(&mut self, recent: &[String]) {
+        let Some(entry) = self.matches.get(self.selected) else {
+            return;
+        };
+        self.input = match entry {
+            Entry::Command(command) => format!("{} ", command.name),
+            entry => entry.name().to_string(),
+        };
+        self.refresh(recent);
+    }
+}
+
+/// `line` as the latest of the recent commands, once.
+pub fn remember(recent: &mut Vec<String>, line: &str) {
+    recent.retain(|l| l != line);
+    recent.insert(0, line.to_string());
+    recent.truncate(RECENT_LIMIT);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_matching_prefers_word_starts_and_runs() {
+        assert!(fuzzy_score("xyz", "copy").is_none());
+        assert!(fuzzy_score("", "copy").is_some());
+        let split = fuzzy_score("sv", "split-vertical").unwrap();
+        let scattered = fuzzy_score("sv", "toggle-favorite-saved").unwrap_or(i32::MIN);
+        assert!(split > scattered);
+        assert!(fuzzy_score("cop", "copy") > fuzzy_score("cop", "compare-content"));
+    }
+
+    #[test]
+    fn recent_lines_come_first() {
+        let recent = vec!["sort size desc".to_string()];
+        let mut palette = Palette::new(&recent);
+        assert_eq!(palette.matches[0], Entry::Recent("sort size desc".into()));
+        for c in "so".chars() {
+            palette.insert(c, &recent);
+        }
+        assert_eq!(palette.matches[0], Entry::Recent("sort size desc".into()));
+        assert!(palette.matches.contains(&Entry::Action(Action::SortField)));
+    }
+
+    #[test]
+    fn an_argument_narrows_to_its_command() {
+        let recent = vec!["cd ~/dev".to_string(), "mkdir x".to_string()];
+        let mut palette = Palette::new(&recent);
+        for c in "cd ~".chars() {
+            palette.insert(c, &recent);
+        }
+        assert_eq!(palette.matches.len(), 2);
+        assert_eq!(palette.matches[0], Entry::Recent("cd ~/dev".into()));
+        assert!(matches!(palette.matches[1], Entry::Command(c) if c.name == "cd"));
+    }
+
+    #[test]
+    fn remembering_moves_a_line_to_the_front() {
+        let mut recent = vec!["a".to_string(), "b".to_string()];
+        remember(&mut recent, "b");
+        assert_eq!(recent, ["b", "a"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! The bulk rename dialog: three text fields, two toggles, and a preview that
+//! is worked out again on every keystroke. The renaming itself is
+//! panex-core's.
+
+use panex_core::rename::{preview_renames, RenamePreview, RenameRule};
+
+pub const FIELDS: [&str; 3] = ["Find", "Replace", "Extension"];
+
+#[derive(PartialEq, Debug)]
+pub struct BulkRename {
+    pub pane_id: String,
+    /// In the order the pane lists them, which is the order `{n}` counts in.
+    pub paths: Vec<String>,
+    pub rule: RenameRule,
+    /// Index into `FIELDS`.
+    pub field: usize,
+    /// In characters, not bytes, into the field being edited.
+    pub cursor: usize,
+    /// An invalid regex is the one failure that leaves nothing to preview.
+    pub preview: Result<Vec<RenamePreview>, String>,
+    pub offset: usize,
+}
+
+impl BulkRename {
+    pub fn new(pane_id: String, paths: Vec<String>) -> Self {
+        let rule = RenameRule::default();
+        let preview = preview_renames(&paths, &rule);
+        BulkRename {
+            pane_id,
+            paths,
+            rule,
+            field: 0,
+            cursor: 0,
+            preview,
+            offset: 0,
+        }
+    }
+
+    pub fn input(&self) -> &str {
+        match self.field {
+            0 => &self.rule.find,
+            1 => &self.rule.replace,
+            _ => &self.rule.extension,
+        }
+    }
+
+    fn input_mut(&mut self) -> &mut String {
+        match self.field {
+            0 => &mut self.rule.find,
+            1 => &mut self.rule.replace,
+            _ => &mut self.rule.extension,
+        }
+    }
+
+    fn byte_at(&self, chars: usize) -> usize {
+        let input = self.input();
+        input
+            .char_indices()
+            .nth(chars)
+            .map_or(input.len(), |(i, _)| i)
+    }
+
+    fn refresh(&mut self) {
+        self.preview = preview_renames(&self.paths, &self.rule);
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let at = self.byte_at(self.cursor);
+        self.input_mut().insert(at, c);
+        self.cursor += 1;
+        self.refresh();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        self.cursor -= 1;
+        let at = self.byte_at(self.cursor);
+        self.input_mut().remove(at);
+        self.refresh();
+    }
+
+    pub fn move_cursor(&mut self, delta: isize) {
+        let len = self.input().chars().count();
+        self.cursor = self.cursor.saturating_add_signed(delta).min(len);
+    }
+
+    pub fn cursor_to_end(&mut self) {
+        self.cursor = self.input().chars().count();
+    }
+
+    pub fn switch_field(&mut self, delta: isize) {
+        self.field = (self.field as isize + delta).rem_euclid(FIELDS.len() as isize) as usize;
+        self.cursor_to_end();
+    }
+
+    pub fn toggle_regex(&mut self) {
+        self.rule.regex = !self.rule.regex;
+        self.refresh();
+    }
+
+    pub fn cycle_case(&mut self) {
+        self.rule.case = self.rule.case.cycle();
+        self.refresh();
+    }
+
+    /// How many entries the rule changes, and how many of those cannot be done.
+    pub fn counts(&self) -> (usize, usize) {
+        match &self.preview {
+            Ok(previews) => (
+                previews.iter().filter(|p| p.changes()).count(),
+                previews.iter().filter(|p| p.problem.is_some()).count(),
+            ),
+            Err(_) => (0, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The cursor counts characters, so editing after a multi-byte one lands
+    /// where it is shown rather than inside it.
+    #[test]
+    fn editing_past_multibyte_characters() {
+        let mut state = BulkRename::new("p".into(), vec!["/x/café.txt".into()]);
+        for c in "é1".chars() {
+            state.insert(c);
+        }
+        state.move_cursor(-1);
+        state.insert('x');
+        assert_eq!(state.rule.find, "éx1");
+        state.backspace();
+        state.backspace();
+        assert_eq!(state.rule.find, "1");
+    }
+}
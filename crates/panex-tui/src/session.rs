@@ -0,0 +1,236 @@
+//! Sessions: the layout and where each pane was, saved on quit and put back
+//! on the next start.
+//!
+//! Stored as JSON beside the config — `~/.panex/session.json`, or
+//! `~/.panex/sessions/<name>.json` for a named one. It is state the app
+//! writes, not settings anybody edits, which is why it is not in the TOML.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::app::{App, PaneState};
+use crate::layout::{collect_leaf_ids, remove_pane, LayoutNode};
+use crate::sort::{SortDirection, SortField};
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    pub layout_root: LayoutNode,
+    pub panes: HashMap<String, PaneState>,
+    pub active_pane_id: String,
+    pub pane_counter: u32,
+    pub show_hidden: bool,
+    pub sort_field: SortField,
+    pub sort_direction: SortDirection,
+    /// The command palette's history. Absent from sessions saved before the
+    /// palette existed.
+    #[serde(default)]
+    pub recent_commands: Vec<String>,
+}
+
+/// A session name becomes a file name, so it is held to what is safe as one.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let ok = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        && !name.starts_with('.');
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid session name '{}': use letters, digits, '-', '_' and '.'",
+            name
+        ))
+    }
+}
+
+impl Session {
+    /// `~/.panex/session.json`, or `~/.panex/sessions/<name>.json`.
+    pub fn path(name: Option<&str>) -> Result<PathBuf, String> {
+        let home = dirs::home_dir().ok_or("Could not determine home directory")?;
+        let dir = home.join(".panex");
+        Ok(match name {
+            None => dir.join("session.json"),
+            Some(name) => dir.join("sessions").join(format!("{}.json", name)),
+        })
+    }
+
+    /// The app as it stands, less the card pane: oko is a view of this
+    /// moment's terminal window, and there is nothing of it to go back to.
+    pub fn capture(app: &App) -> Self {
+        let mut layout_root = app.layout_root.clone();
+        if let Some(oko) = &app.oko_pane_id {
+            if let Some(rest) = remove_pane(&layout_root, oko) {
+                layout_root = rest;
+            }
+        }
+        let leaves = collect_leaf_ids(&layout_root);
+        let panes = leaves
+            .iter()
+            .filter_map(|id| {
+                let pane = app.pane_map.get(id)?;
+                Some((
+                    id.clone(),
+                    PaneState {
+                        current_path: pane.current_path.clone(),
+                        focus_index: pane.focus_index,
+                        search_query: pane.search_query.clone(),
+                        width_level: pane.width_level,
+                        height_level: pane.height_level,
+                        columns: pane.columns.clone(),
+                        ..PaneState::new("")
+                    },
+                ))
+            })
+            .collect();
+        let active_pane_id = if leaves.contains(&app.active_pane_id) {
+            app.active_pane_id.clone()
+        } else {
+            app.oko_return_to
+                .clone()
+                .filter(|id| leaves.contains(id))
+                .unwrap_or_else(|| leaves[0].clone())
+        };
+        Session {
+            layout_root,
+            panes,
+            active_pane_id,
+            pane_counter: app.pane_counter,
+            show_hidden: app.show_hidden,
+            sort_field: app.sort_field,
+            sort_direction: app.sort_direction,
+            recent_commands: app.recent_commands.clone(),
+        }
+    }
+
+    pub fn save(&self, name: Option<&str>) -> Result<(), String> {
+        let path = Self::path(name)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create session directory: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write session: {}", e))
+    }
+
+    /// The saved session, if there is one. A file that is missing is the
+    /// first run; one that does not parse is ignored the same way, rather
+    /// than standing between the user and a working start.
+    pub fn load(name: Option<&str>) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(name).ok()?).ok()?;
+        let session: Session = serde_json::from_str(&content).ok()?;
+        session.is_whole().then_some(session)
+    }
+
+    /// Every leaf has a pane and the active one is among them — a file that
+    /// says otherwise was not written by us.
+    fn is_whole(&self) -> bool {
+        let leaves = collect_leaf_ids(&self.layout_root);
+        leaves.iter().all(|id| self.panes.contains_key(id))
+            && leaves.contains(&self.active_pane_id)
+    }
+
+    /// Put the saved layout and panes in place of `app`'s. A folder that has
+    /// gone since is replaced by the nearest one above it that is still there.
+    pub fn restore(self, app: &mut App) {
+        app.show_hidden = self.show_hidden;
+        app.sort_field = self.sort_field;
+        app.sort_direction = self.sort_direction;
+        app.recent_commands = self.recent_commands;
+        app.layout_root = self.layout_root;
+        app.active_pane_id = self.active_pane_id;
+        app.pane_counter = self.pane_counter;
+        app.pane_map.clear();
+        app.raw_entries_map.clear();
+
+        for (id, mut pane) in self.panes {
+            pane.current_path = nearest_existing(&pane.current_path)
+                .unwrap_or_else(|| app.home_path.clone());
+            let focus = pane.focus_index;
+            app.pane_map.insert(id.clone(), pane);
+            app.refresh_pane(&id);
+            if let Some(pane) = app.pane_map.get_mut(&id) {
+                pane.focus_index = if pane.entries.is_empty() {
+                    -1
+                } else {
+                    focus.clamp(0, pane.entries.len() as i32 - 1)
+                };
+                pane.table_state
+                    .select(usize::try_from(pane.focus_index).ok());
+            }
+        }
+    }
+}
+
+/// The folder at `path`, or the nearest one above it that still exists.
+pub fn nearest_existing(path: &str) -> Option<String> {
+    Path::new(path)
+        .ancestors()
+        .find(|p| p.is_dir())
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{split_pane, SplitDirection};
+
+    /// Through JSON and back: the layout keeps its shape and ratios, and each
+    /// pane its folder, focus and size levels.
+    #[test]
+    fn a_session_survives_the_round_trip() {
+        let mut app = App::new().unwrap();
+        let first = app.active_pane_id.clone();
+        let second = app.next_pane_id();
+        app.layout_root =
+            split_pane(&app.layout_root, &first, &second, SplitDirection::Horizontal);
+        if let LayoutNode::Split { ratio, .. } = &mut app.layout_root {
+            *ratio = 0.625;
+        }
+        let root = std::env::temp_dir();
+        app.pane_map.insert(second.clone(), PaneState::new("/"));
+        app.navigate_to(&second, &root.to_string_lossy());
+        app.pane_map.get_mut(&first).unwrap().height_level = 1;
+        app.active_pane_id = second.clone();
+
+        let json = serde_json::to_string(&Session::capture(&app)).unwrap();
+        let mut restored = App::new().unwrap();
+        serde_json::from_str::<Session>(&json)
+            .unwrap()
+            .restore(&mut restored);
+
+        assert_eq!(collect_leaf_ids(&restored.layout_root), [first.clone(), second.clone()]);
+        assert!(matches!(
+            restored.layout_root,
+            LayoutNode::Split { ratio, .. } if ratio == 0.625
+        ));
+        assert_eq!(restored.active_pane_id, second);
+        assert_eq!(restored.pane_map[&first].height_level, 1);
+        assert_eq!(
+            restored.pane_map[&second].current_path,
+            root.to_string_lossy()
+        );
+        // Reading on: the next split must not reuse an id already in the tree.
+        assert_ne!(restored.next_pane_id(), second);
+    }
+
+    #[test]
+    fn a_folder_that_has_gone_falls_back_to_its_parent() {
+        let gone = std::env::temp_dir().join("panex-session-gone").join("deeper");
+        assert_eq!(
+            nearest_existing(&gone.to_string_lossy()),
+            Some(std::env::temp_dir().to_string_lossy().to_string())
+        );
+    }
+
+    #[test]
+    fn session_names_stay_file_names() {
+        assert!(check_name("work").is_ok());
+        assert!(check_name("client-a_2").is_ok());
+        assert!(check_name("../escape").is_err());
+        assert!(check_name("").is_err());
+    }
+}
@@ -1,32 +1,55 @@
 use panex_core::FileEntry;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
-#[derive(Clone, Copy, PartialEq)]
+use crate::columns::owner_name;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub enum SortField {
     Name,
     Extension,
     Size,
     Modified,
+    Created,
+    Permissions,
+    Owner,
+    Items,
+    Git,
 }
 
 impl SortField {
-    pub fn cycle(self) -> Self {
-        match self {
-            SortField::Name => SortField::Extension,
-            SortField::Extension => SortField::Size,
-            SortField::Size => SortField::Modified,
-            SortField::Modified => SortField::Name,
+    /// As given to `:sort`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "name" => Some(SortField::Name),
+            "ext" | "extension" => Some(SortField::Extension),
+            "size" => Some(SortField::Size),
+            "modified" | "date" | "mtime" => Some(SortField::Modified),
+            "created" | "ctime" => Some(SortField::Created),
+            "permissions" | "perms" | "mode" => Some(SortField::Permissions),
+            "owner" => Some(SortField::Owner),
+            "items" | "count" => Some(SortField::Items),
+            "git" | "status" => Some(SortField::Git),
+            _ => None,
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SortDirection {
     Asc,
     Desc,
 }
 
 impl SortDirection {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "asc" => Some(SortDirection::Asc),
+            "desc" => Some(SortDirection::Desc),
+            _ => None,
+        }
+    }
+
     pub fn toggle(self) -> Self {
         match self {
             SortDirection::Asc => SortDirection::Desc,
@@ -46,6 +69,16 @@ fn extension_of(name: &str) -> &str {
     name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("")
 }
 
+/// A file's size, or a folder's once measured. An unmeasured folder sorts as
+/// empty rather than by the few blocks its own listing takes.
+fn size_of(entry: &FileEntry) -> u64 {
+    if entry.is_dir {
+        entry.contents_size.unwrap_or(0)
+    } else {
+        entry.size
+    }
+}
+
 pub fn sort_entries(entries: &mut [FileEntry], field: SortField, direction: SortDirection) {
     entries.sort_by(|a, b| {
         // Dirs always before files
@@ -57,15 +90,25 @@ pub fn sort_entries(entries: &mut [FileEntry], field: SortField, direction: Sort
             };
         }
 
+        let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
         let cmp = match field {
-            SortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortField::Name => by_name(),
             SortField::Extension => {
                 let ea = extension_of(&a.name).to_lowercase();
                 let eb = extension_of(&b.name).to_lowercase();
-                ea.cmp(&eb).then(a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+                ea.cmp(&eb).then_with(by_name)
             }
-            SortField::Size => a.size.cmp(&b.size),
+            SortField::Size => size_of(a).cmp(&size_of(b)),
             SortField::Modified => a.modified.cmp(&b.modified),
+            SortField::Created => a.created.cmp(&b.created),
+            // These are shared by many entries, so the name keeps the order
+            // within each the same every time.
+            SortField::Permissions => (a.mode & 0o7777).cmp(&(b.mode & 0o7777)).then_with(by_name),
+            SortField::Owner => owner_name(a.uid).cmp(&owner_name(b.uid)).then_with(by_name),
+            SortField::Items => a.items.cmp(&b.items).then_with(by_name),
+            // Clean first, then in rising order of how much attention the
+            // status wants, as a folder's own is worked out.
+            SortField::Git => a.git.cmp(&b.git).then_with(by_name),
         };
 
         match direction {
@@ -0,0 +1,475 @@
+//! Colours. Every colour the TUI draws with is a role in a `Theme` — one of
+//! the built-ins (`dark`, `light`, `retro`, `high-contrast`) with any roles
+//! from `[theme]` in the config laid over it:
+//!
+//! ```toml
+//! [theme]
+//! name = "light"
+//! accent = "#b8860b"
+//! border_active = "green"
+//! colors = "16"      # or "256", "truecolor"; "auto" by default
+//! ```
+//!
+//! The result is then fitted to the terminal: `NO_COLOR` turns every colour
+//! off, and a terminal that cannot draw 24-bit colour gets the nearest each
+//! one of its 256 or 16.
+
+use panex_core::config::ThemeConfig;
+use ratatui::style::{Color, Modifier, Style};
+
+/// Defines `Theme` from its list of roles, so that the config's names for
+/// them and the code that recolours them all can never miss one.
+macro_rules! roles {
+    ($($(#[$doc:meta])* $role:ident,)*) => {
+        #[derive(Clone, Copy, PartialEq, Debug)]
+        pub struct Theme {
+            $($(#[$doc])* pub $role: Color,)*
+            /// `NO_COLOR` is set: every role is `Reset`, and what colour
+            /// would have marked is marked by reversing instead.
+            pub no_color: bool,
+        }
+
+        impl Theme {
+            fn role_mut(&mut self, name: &str) -> Option<&mut Color> {
+                match name {
+                    $(stringify!($role) => Some(&mut self.$role),)*
+                    _ => None,
+                }
+            }
+
+            fn map_colors(mut self, f: impl Fn(Color) -> Color) -> Self {
+                $(self.$role = f(self.$role);)*
+                self
+            }
+        }
+    };
+}
+
+roles! {
+    /// Folder icons, the brand, a card waiting on you.
+    accent,
+    /// Borders of the panes that are not active.
+    border,
+    border_active,
+    /// Text in dialogs and lists.
+    text,
+    /// Hints, separators, what is there but not the point.
+    muted,
+    /// Between `text` and `muted`: old names, unchanged diff lines.
+    soft,
+    /// The sorted column's header.
+    header,
+    /// Folder names.
+    directory,
+    /// Behind the row under the cursor, and its text.
+    focus_bg,
+    focus_fg,
+    /// Behind selected rows.
+    selection_bg,
+    /// Borders of dialogs you type into, and the help.
+    prompt,
+    /// Borders of dialogs you choose from, and their highlighted row.
+    choice,
+    /// Keys, as the help and the palette show them.
+    key,
+    /// Section titles in the help.
+    heading,
+    /// The status message.
+    status,
+    /// Added, ready, yes.
+    ok,
+    /// Removed, failed, no.
+    error,
+    /// A card whose tab is working.
+    working,
+    /// Claude's mark on a card.
+    claude,
+    /// Text on a highlighted row or button.
+    highlight_fg,
+}
+
+/// The theme the TUI had before there were themes.
+fn dark() -> Theme {
+    Theme {
+        accent: Color::Rgb(255, 191, 0),
+        border: Color::DarkGray,
+        border_active: Color::Green,
+        text: Color::White,
+        muted: Color::DarkGray,
+        soft: Color::Gray,
+        header: Color::Cyan,
+        directory: Color::Blue,
+        focus_bg: Color::DarkGray,
+        focus_fg: Color::White,
+        selection_bg: Color::Blue,
+        prompt: Color::Cyan,
+        choice: Color::Yellow,
+        key: Color::Cyan,
+        heading: Color::Yellow,
+        status: Color::Yellow,
+        ok: Color::Green,
+        error: Color::Red,
+        // Not `Cyan`: what matters is telling it from `ok`'s green, and
+        // terminal palettes draw their cyan anywhere from blue to mint.
+        working: Color::Rgb(0, 178, 172),
+        // From the icon rather than the palette, whose orange is anybody's.
+        claude: Color::Rgb(217, 119, 87),
+        highlight_fg: Color::Black,
+        no_color: false,
+    }
+}
+
+/// For a light terminal background, where white text and yellow borders
+/// all but disappear.
+fn light() -> Theme {
+    Theme {
+        accent: Color::Rgb(184, 120, 0),
+        border: Color::Gray,
+        border_active: Color::Rgb(0, 140, 60),
+        text: Color::Black,
+        muted: Color::Rgb(120, 120, 120),
+        soft: Color::Rgb(80, 80, 80),
+        header: Color::Rgb(0, 110, 160),
+        directory: Color::Rgb(20, 70, 200),
+        focus_bg: Color::Rgb(215, 215, 215),
+        focus_fg: Color::Black,
+        selection_bg: Color::Rgb(170, 200, 255),
+        prompt: Color::Rgb(0, 110, 160),
+        choice: Color::Rgb(184, 120, 0),
+        key: Color::Rgb(0, 110, 160),
+        heading: Color::Rgb(150, 60, 150),
+        status: Color::Rgb(150, 60, 150),
+        ok: Color::Rgb(0, 140, 60),
+        error: Color::Rgb(200, 30, 30),
+        working: Color::Rgb(0, 130, 130),
+        claude: Color::Rgb(190, 90, 60),
+        highlight_fg: Color::White,
+        no_color: false,
+    }
+}
+
+/// Green phosphor.
+fn retro() -> Theme {
+    let bright = Color::Rgb(120, 255, 120);
+    let mid = Color::Rgb(50, 200, 50);
+    let dim = Color::Rgb(20, 110, 20);
+    Theme {
+        accent: bright,
+        border: dim,
+        border_active: bright,
+        text: bright,
+        muted: dim,
+        soft: mid,
+        header: bright,
+        directory: bright,
+        focus_bg: Color::Rgb(20, 80, 20),
+        focus_fg: bright,
+        selection_bg: mid,
+        prompt: mid,
+        choice: bright,
+        key: bright,
+        heading: bright,
+        status: bright,
+        ok: bright,
+        error: Color::Rgb(255, 176, 0),
+        working: mid,
+        claude: bright,
+        highlight_fg: Color::Black,
+        no_color: false,
+    }
+}
+
+/// Nothing dim: every colour one of the bright ones, every highlight white
+/// on black or black on white.
+fn high_contrast() -> Theme {
+    Theme {
+        accent: Color::LightYellow,
+        border: Color::Gray,
+        border_active: Color::White,
+        text: Color::White,
+        muted: Color::Gray,
+        soft: Color::White,
+        header: Color::LightCyan,
+        directory: Color::LightCyan,
+        focus_bg: Color::White,
+        focus_fg: Color::Black,
+        selection_bg: Color::LightBlue,
+        prompt: Color::White,
+        choice: Color::LightYellow,
+        key: Color::LightCyan,
+        heading: Color::LightYellow,
+        status: Color::LightYellow,
+        ok: Color::LightGreen,
+        error: Color::LightRed,
+        working: Color::LightCyan,
+        claude: Color::LightRed,
+        highlight_fg: Color::Black,
+        no_color: false,
+    }
+}
+
+pub const BUILT_IN: [&str; 4] = ["dark", "light", "retro", "high-contrast"];
+
+impl Default for Theme {
+    fn default() -> Self {
+        dark()
+    }
+}
+
+/// How many colours the terminal can draw.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Depth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl Depth {
+    /// From what terminals say about themselves. `COLORTERM` is the only
+    /// real promise of 24-bit colour; a `TERM` ending in `256color` is not
+    /// one, and Terminal.app is the standing example.
+    pub fn detect() -> Depth {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Depth::TrueColor;
+        }
+        match std::env::var("TERM") {
+            Ok(term) if term.contains("256color") => Depth::Ansi256,
+            // The Windows console sets no TERM and has drawn 24-bit colour
+            // since Windows 10.
+            Err(_) if cfg!(windows) => Depth::TrueColor,
+            _ => Depth::Ansi16,
+        }
+    }
+}
+
+/// `NO_COLOR` set to anything but the empty string, per no-color.org.
+pub fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
+impl Theme {
+    /// The theme `config` describes, fitted to this terminal, and what was
+    /// wrong with the config, for reporting at startup.
+    pub fn load(config: &ThemeConfig) -> (Theme, Vec<String>) {
+        Theme::build(config, no_color_requested(), Depth::detect())
+    }
+
+    fn build(config: &ThemeConfig, no_color: bool, detected: Depth) -> (Theme, Vec<String>) {
+        let mut problems = Vec::new();
+        let mut theme = match config.name.as_deref().unwrap_or("dark") {
+            "dark" => dark(),
+            "light" => light(),
+            "retro" => retro(),
+            "high-contrast" => high_contrast(),
+            other => {
+                problems.push(format!(
+                    "[theme] unknown theme '{}' (have: {})",
+                    other,
+                    BUILT_IN.join(", ")
+                ));
+                dark()
+            }
+        };
+        for (role, value) in &config.roles {
+            let Some(slot) = theme.role_mut(&role.replace('-', "_")) else {
+                problems.push(format!("[theme] unknown colour role '{}'", role));
+                continue;
+            };
+            match value.parse::<Color>() {
+                Ok(color) => *slot = color,
+                Err(_) => problems.push(format!("[theme] {}: '{}' is not a colour", role, value)),
+            }
+        }
+        let depth = match config.colors.as_deref().unwrap_or("auto") {
+            "auto" => detected,
+            "truecolor" | "24bit" => Depth::TrueColor,
+            "256" => Depth::Ansi256,
+            "16" => Depth::Ansi16,
+            other => {
+                problems.push(format!(
+                    "[theme] colors = '{}': use auto, truecolor, 256 or 16",
+                    other
+                ));
+                detected
+            }
+        };
+
+        let theme = if no_color {
+            Theme {
+                no_color: true,
+                ..theme.map_colors(|_| Color::Reset)
+            }
+        } else {
+            match depth {
+                Depth::TrueColor => theme,
+                Depth::Ansi256 => theme.map_colors(to_256),
+                Depth::Ansi16 => theme.map_colors(to_16),
+            }
+        };
+        (theme, problems)
+    }
+
+    /// `highlight_fg` on `bg`: a highlighted row, a chosen button. Without
+    /// colour, reversed — the one highlight a terminal always has.
+    pub fn highlight(&self, bg: Color) -> Style {
+        if self.no_color {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default().fg(self.highlight_fg).bg(bg)
+        }
+    }
+
+    /// The cell under a text cursor.
+    pub fn cursor(&self) -> Style {
+        self.highlight(self.text)
+    }
+}
+
+/// The sixteen, as xterm draws them by default — the nearest thing to what
+/// a terminal will actually show for each.
+const ANSI: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// What an indexed colour is in RGB, for the ones past the sixteen.
+fn indexed_rgb(i: u8) -> Option<(u8, u8, u8)> {
+    match i {
+        0..=15 => None,
+        16..=231 => {
+            let i = i - 16;
+            Some((CUBE[(i / 36) as usize], CUBE[(i / 6 % 6) as usize], CUBE[(i % 6) as usize]))
+        }
+        _ => {
+            let level = 8 + 10 * (i - 232);
+            Some((level, level, level))
+        }
+    }
+}
+
+fn to_256(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    let nearest = |v: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE[i] as i32 - v as i32).abs())
+            .unwrap_or(0)
+    };
+    let (ri, gi, bi) = (nearest(r), nearest(g), nearest(b));
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray = 232 + ((average.saturating_sub(8) + 5) / 10).min(23) as usize;
+    // The gray ramp is finer than the cube's diagonal, so it wins for the
+    // near-grays it can get closer to.
+    let rgb = (r, g, b);
+    let pick = [cube, gray]
+        .into_iter()
+        .min_by_key(|&i| distance(rgb, indexed_rgb(i as u8).unwrap_or_default()))
+        .unwrap_or(cube);
+    Color::Indexed(pick as u8)
+}
+
+fn to_16(color: Color) -> Color {
+    let rgb = match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(i) if i < 16 => return ANSI[i as usize].0,
+        Color::Indexed(i) => indexed_rgb(i).unwrap_or_default(),
+        other => return other,
+    };
+    ANSI.iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map_or(color, |(named, _)| *named)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn config(name: Option<&str>, roles: &[(&str, &str)]) -> ThemeConfig {
+        ThemeConfig {
+            name: name.map(str::to_string),
+            colors: None,
+            roles: roles
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn roles_override_the_named_theme() {
+        let (theme, problems) = Theme::build(
+            &config(Some("light"), &[("accent", "#102030"), ("border-active", "red")]),
+            false,
+            Depth::TrueColor,
+        );
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(theme.accent, Color::Rgb(0x10, 0x20, 0x30));
+        assert_eq!(theme.border_active, Color::Red);
+        assert_eq!(theme.text, light().text);
+    }
+
+    #[test]
+    fn every_built_in_loads() {
+        for name in BUILT_IN {
+            let (_, problems) = Theme::build(&config(Some(name), &[]), false, Depth::TrueColor);
+            assert!(problems.is_empty(), "{}: {:?}", name, problems);
+        }
+    }
+
+    #[test]
+    fn mistakes_are_reported_and_skipped() {
+        let (theme, problems) = Theme::build(
+            &config(Some("solarized"), &[("acent", "red"), ("accent", "not-a-colour")]),
+            false,
+            Depth::TrueColor,
+        );
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert_eq!(theme, dark());
+    }
+
+    #[test]
+    fn no_color_leaves_only_modifiers() {
+        let (theme, _) = Theme::build(&config(None, &[]), true, Depth::TrueColor);
+        assert_eq!(theme.accent, Color::Reset);
+        assert!(theme.highlight(theme.choice).add_modifier.contains(Modifier::REVERSED));
+    }
+
+    #[test]
+    fn limited_terminals_get_the_nearest_colour() {
+        let (theme, _) = Theme::build(&config(None, &[]), false, Depth::Ansi16);
+        // Amber is near enough yellow, teal near enough cyan.
+        assert_eq!(theme.accent, Color::Yellow);
+        assert_eq!(theme.working, Color::Cyan);
+        // Named colours are already within the sixteen.
+        assert_eq!(theme.directory, Color::Blue);
+
+        assert_eq!(to_256(Color::Rgb(255, 175, 0)), Color::Indexed(214));
+        assert_eq!(to_256(Color::Rgb(128, 128, 128)), Color::Indexed(244));
+        assert_eq!(to_16(Color::Indexed(196)), Color::LightRed);
+    }
+}
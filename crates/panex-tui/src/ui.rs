@@ -4,16 +4,23 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table};
 use ratatui::Frame;
 
-use crate::app::{App, AppMode, PaneView};
+use crate::app::{App, AppMode, PaneView, PromptAction};
+use crate::compare::RowMark;
+use crate::diff::DiffView;
+use crate::keys::{Action, Section};
+use crate::palette::{Entry, Palette, COMMANDS};
+use crate::rename::{BulkRename, FIELDS};
 use crate::layout::{LayoutNode, SplitDirection};
-use crate::sort::SortField;
-
-/// Amber accent — folder icons and the footer brand.
-const ACCENT: Color = Color::Rgb(255, 191, 0);
-/// `working` in the card view. Named rather than `Color::Cyan` because the
-/// distinction that matters is from `ready`'s green, and terminal palettes
-/// render their own cyan anywhere from blue to mint.
-const TEAL: Color = Color::Rgb(0, 178, 172);
+use crate::columns::{format_relative, format_size, Column};
+use crate::filetype::Type;
+use crate::theme::Theme;
+use crate::usage::Usage;
+use panex_core::compare::{SyncDirection, SyncPlan};
+use panex_core::config::{Opener, RunIn};
+use panex_core::diff::{DiffLine, LineChange};
+use panex_core::git::{Commit, GitStatus};
+use panex_core::rename::CaseChange;
+
 /// Padded so a status line clipped at the column edge can't butt up against it.
 const BRAND: &str = "  panex ";
 /// Scroll thumb glyph. Half-width so it reads lighter than the full block
@@ -33,13 +40,20 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
     render_layout_node(frame, app, &app.layout_root.clone(), chunks[0]);
     render_status_bar(frame, app, chunks[1]);
 
+    // Drawn before the dialogs rather than as one: deleting from it asks
+    // first, and the question goes on top.
+    let theme = app.theme;
+    if let Some(usage) = &mut app.usage {
+        render_usage_view(frame, area, &theme, usage);
+    }
+
     // Render dialog overlays
     match &app.mode {
         AppMode::Help => {
-            render_help_dialog(frame, area, app.oko_available);
+            render_help_dialog(frame, area, app);
         }
         AppMode::Confirm { title, message, selected, .. } => {
-            render_confirm_dialog(frame, area, title, message, *selected);
+            render_confirm_dialog(frame, area, &app.theme, title, message, *selected);
         }
         AppMode::Prompt {
             title,
@@ -47,22 +61,67 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
             cursor,
             ..
         } => {
-            render_prompt_dialog(frame, area, title, input, *cursor);
+            render_prompt_dialog(frame, area, &app.theme, title, input, *cursor);
         }
         AppMode::Rename { input, cursor, .. } => {
-            render_prompt_dialog(frame, area, "Rename", input, *cursor);
+            render_prompt_dialog(frame, area, &app.theme, "Rename", input, *cursor);
         }
-        AppMode::PathEdit { input, cursor, completions, completion_index, .. } => {
+        AppMode::PathEdit {
+            input,
+            cursor,
+            completions,
+            completion_index,
+            partial,
+            ..
+        } => {
             let title = if let Some(idx) = completion_index {
                 format!("Go to path ({}/{})", idx + 1, completions.len())
             } else {
                 "Go to path".to_string()
             };
-            render_prompt_dialog(frame, area, &title, input, *cursor);
+            render_prompt_dialog(frame, area, &app.theme, &title, input, *cursor);
+            render_completions(frame, area, app, completions, *completion_index, *partial);
         }
-        AppMode::FavoritesList { selected, .. } => {
-            render_favorites_dialog(frame, area, &app.config.favorites.paths, *selected);
+        AppMode::FavoritesList { selected, .. } => render_favorites(frame, area, app, *selected),
+        AppMode::RecentList { selected, .. } => {
+            let title = " ↺ Recent folders ";
+            render_path_list(frame, area, &app.theme, title, &app.recent.paths, *selected);
         }
+        AppMode::LayoutPicker { selected } => {
+            let names: Vec<&String> = app.config.layouts.keys().collect();
+            render_layout_picker(frame, area, &app.theme, &names, *selected);
+        }
+        AppMode::Palette { state } => render_palette(frame, area, app, state),
+        AppMode::OpenWith {
+            paths,
+            choices,
+            selected,
+        } => render_open_with(frame, area, &app.theme, paths, choices, *selected),
+        AppMode::Jump {
+            input,
+            matches,
+            selected,
+            ..
+        } => render_jump(frame, area, app, input, matches, *selected),
+        AppMode::SyncPlan {
+            direction,
+            plan,
+            offset,
+        } => {
+            render_sync_dialog(frame, area, &app.theme, *direction, plan, *offset);
+        }
+        AppMode::GitLog {
+            path,
+            commits,
+            offset,
+        } => render_git_log(frame, area, &app.theme, path, commits, *offset),
+        _ => {}
+    }
+    // Apart from the rest, as the overlays that write back: how many rows fit
+    // is only known here.
+    match &mut app.mode {
+        AppMode::Diff { view } => render_diff_view(frame, area, &theme, view),
+        AppMode::BulkRename { state } => render_bulk_rename_dialog(frame, area, &theme, state),
         _ => {}
     }
 }
@@ -99,9 +158,9 @@ fn render_layout_node(frame: &mut Frame, app: &mut App, node: &LayoutNode, area:
 fn render_pane(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect) {
     let is_active = app.active_pane_id == pane_id;
     let border_style = if is_active {
-        Style::default().fg(Color::Green)
+        Style::default().fg(app.theme.border_active)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(app.theme.border)
     };
 
     if app.oko_pane_id.as_deref() == Some(pane_id) {
@@ -126,10 +185,18 @@ fn render_pane(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect) {
         })
         .unwrap_or_default();
 
+    // The branch goes after the path, and is kept whole: it is the shorter
+    // of the two, and a branch cut short could read as another branch.
+    let branch = app
+        .git
+        .repo(&current_path)
+        .map(|repo| format!("⎇ {} ", repo.describe()));
+    let branch_len = branch.as_ref().map_or(0, |b| b.chars().count());
+
     // Build title with path (truncated from left if too long).
     // Truncate by chars, not bytes — byte slicing panics on multi-byte
     // characters and when the pane is narrower than the path suffix.
-    let max_title_len = area.width.saturating_sub(4) as usize;
+    let max_title_len = area.width.saturating_sub(4 + branch_len as u16) as usize;
     let path_chars: Vec<char> = current_path.chars().collect();
     let display_path = if path_chars.len() > max_title_len {
         let keep = max_title_len.saturating_sub(1);
@@ -140,11 +207,14 @@ fn render_pane(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect) {
     };
 
     let fav_indicator = if app.config.is_favorite(&current_path) { "★ " } else { "" };
-    let title = if let Some(ref query) = search_mode_query {
+    let mut title = Line::from(if let Some(ref query) = search_mode_query {
         format!(" 🔍 {} ", query)
     } else {
         format!(" {}{} ", fav_indicator, display_path)
-    };
+    });
+    if let (Some(branch), None) = (branch, &search_mode_query) {
+        title.push_span(Span::styled(branch, Style::default().fg(app.theme.accent)));
+    }
 
     let block = Block::default()
         .borders(Borders::ALL)
@@ -178,7 +248,7 @@ fn render_pane(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect) {
 
     app.pane_views.insert(pane_id.to_string(), PaneView { area, list_area });
 
-    render_column_header(frame, app, header_area);
+    render_column_header(frame, app, pane_id, header_area);
     render_file_list(frame, app, pane_id, list_area);
 }
 
@@ -214,7 +284,7 @@ fn render_oko_pane(
         .border_style(border_style)
         .title(Span::styled(
             " Oko ",
-            Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
+            Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD),
         ));
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -233,7 +303,7 @@ fn render_oko_pane(
 
     let header = Rect { height: 1, ..inner };
     frame.render_widget(
-        Paragraph::new(Span::styled("Tabs", Style::default().fg(Color::DarkGray))),
+        Paragraph::new(Span::styled("Tabs", Style::default().fg(app.theme.muted))),
         header,
     );
     let body = Rect {
@@ -262,21 +332,22 @@ fn render_oko_pane(
     app.oko_offset = offset;
     app.oko_capacity = capacity;
 
+    let theme = &app.theme;
     let cards = match &app.oko_view {
         crate::oko::View::Connecting => {
-            render_oko_note(frame, body, "connecting to oko…", Color::DarkGray);
+            render_oko_note(frame, body, "connecting to oko…", theme.muted);
             Vec::new()
         }
         crate::oko::View::Lost(message) => {
-            render_oko_note(frame, body, message, Color::Red);
+            render_oko_note(frame, body, message, theme.error);
             Vec::new()
         }
         crate::oko::View::Rows(rows) if rows.is_empty() => {
-            render_oko_note(frame, body, "no tabs to show", Color::DarkGray);
+            render_oko_note(frame, body, "no tabs to show", theme.muted);
             Vec::new()
         }
         crate::oko::View::Rows(rows) => {
-            render_oko_cards(frame, body, rows, &app.home_path, selected, offset)
+            render_oko_cards(frame, body, theme, rows, &app.home_path, selected, offset)
         }
     };
     // Where the mouse has to land to hit each card. Only what was actually
@@ -293,6 +364,7 @@ fn render_oko_pane(
             width: 1,
             height: body.height,
         },
+        &app.theme,
         count,
         capacity,
         offset,
@@ -330,6 +402,7 @@ fn oko_capacity(area: Rect) -> usize {
 fn render_oko_cards(
     frame: &mut Frame,
     area: Rect,
+    theme: &Theme,
     rows: &[crate::oko::Row],
     home: &str,
     selected: Option<&str>,
@@ -360,6 +433,7 @@ fn render_oko_cards(
             render_oko_card_stacked(
                 frame,
                 card,
+                theme,
                 row,
                 home,
                 selected == Some(row.session_id.as_str()),
@@ -390,6 +464,7 @@ fn render_oko_cards(
         render_oko_card_boxed(
             frame,
             card,
+            theme,
             row,
             home,
             selected == Some(row.session_id.as_str()),
@@ -405,6 +480,7 @@ fn render_oko_cards(
 fn render_oko_card_stacked(
     frame: &mut Frame,
     area: Rect,
+    theme: &Theme,
     row: &crate::oko::Row,
     home: &str,
     selected: bool,
@@ -419,7 +495,7 @@ fn render_oko_card_stacked(
         frame.render_widget(
             Paragraph::new(Span::styled(
                 CARD_RULE.repeat(area.width as usize),
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(theme.muted),
             )),
             Rect { height: 1, ..area },
         );
@@ -439,13 +515,16 @@ fn render_oko_card_stacked(
     // under the cursor — same colours, so moving through cards and moving
     // through files are visibly the same gesture.
     let (fg, dim) = if selected {
-        (Color::White, Color::Gray)
+        (theme.focus_fg, theme.soft)
     } else {
-        (Color::Reset, Color::DarkGray)
+        (Color::Reset, theme.muted)
     };
     let mut line_style = Style::default().fg(fg);
     if selected {
-        line_style = line_style.bg(Color::DarkGray);
+        line_style = line_style.bg(theme.focus_bg);
+        if theme.no_color {
+            line_style = line_style.add_modifier(Modifier::REVERSED);
+        }
     }
 
     let tab = format!("⌘ {}", row.tab);
@@ -481,7 +560,7 @@ fn render_oko_card_stacked(
     frame.render_widget(
         Paragraph::new(Span::styled(
             truncate_left(&path, inner_width),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         )),
         indent,
     );
@@ -490,7 +569,7 @@ fn render_oko_card_stacked(
         return;
     }
     frame.render_widget(
-        Paragraph::new(activity_line(row, inner_width, Color::DarkGray)),
+        Paragraph::new(activity_line(row, inner_width, theme)),
         Rect { y: indent.y + 1, ..indent },
     );
 }
@@ -498,6 +577,7 @@ fn render_oko_card_stacked(
 fn render_oko_card_boxed(
     frame: &mut Frame,
     area: Rect,
+    theme: &Theme,
     row: &crate::oko::Row,
     home: &str,
     selected: bool,
@@ -507,13 +587,13 @@ fn render_oko_card_boxed(
     // reading as a filled box, so the selection lands on the border instead.
     let (border, title) = if selected {
         (
-            Style::default().fg(ACCENT),
-            Style::default().fg(ACCENT).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.accent),
+            Style::default().fg(theme.accent).add_modifier(Modifier::BOLD),
         )
     } else {
         (
-            Style::default().fg(Color::DarkGray),
-            Style::default().fg(Color::White),
+            Style::default().fg(theme.muted),
+            Style::default().fg(theme.text),
         )
     };
     let block = Block::default()
@@ -531,7 +611,7 @@ fn render_oko_card_boxed(
     frame.render_widget(
         Paragraph::new(Span::styled(
             truncate_left(&path, width),
-            Style::default().fg(Color::DarkGray),
+            Style::default().fg(theme.muted),
         )),
         Rect { height: 1, ..inner },
     );
@@ -540,7 +620,7 @@ fn render_oko_card_boxed(
         return;
     }
     frame.render_widget(
-        Paragraph::new(activity_line(row, width, Color::DarkGray)),
+        Paragraph::new(activity_line(row, width, theme)),
         Rect { y: inner.y + 1, height: 1, ..inner },
     );
 }
@@ -554,9 +634,6 @@ fn render_oko_card_boxed(
 /// in the right orange is what makes it recognisable one character wide.
 const CLAUDE_MARK: &str = "✻ ";
 const CLAUDE_WORD: &str = "claude ";
-/// Claude's orange, taken from the icon rather than from the terminal palette —
-/// a named colour would come out whatever orange the theme happens to hold.
-const CLAUDE_ORANGE: Color = Color::Rgb(217, 119, 87);
 
 /// What is happening in that directory, and how long it has said so.
 ///
@@ -567,13 +644,14 @@ const CLAUDE_ORANGE: Color = Color::Rgb(217, 119, 87);
 /// foreground job stays the agent throughout — measured over 38 minutes and
 /// four status transitions on 2026-08-17, which produced no `jobName` event at
 /// all. Invariant, not unstable. See Ivapo/PanEx#4.
-fn activity_line(row: &crate::oko::Row, width: usize, dim: Color) -> Line<'static> {
+fn activity_line(row: &crate::oko::Row, width: usize, theme: &Theme) -> Line<'static> {
+    let dim = theme.muted;
     // Who it is, then how it is: `✻ claude ◐ working`. The name leads because
     // it is the same on every Claude card and the eye can skip it; the
     // indicator sits against the status it belongs to.
     let (claude, glyph, text, color) = match (row.status.as_deref(), row.job.as_deref()) {
         (Some(status), _) => {
-            let (glyph, color) = status_style(status);
+            let (glyph, color) = status_style(status, theme);
             (true, format!("{} ", glyph), status.to_string(), color)
         }
         (None, Some(job)) => (false, String::new(), truncate_left(job, width), dim),
@@ -596,7 +674,7 @@ fn activity_line(row: &crate::oko::Row, width: usize, dim: Color) -> Line<'stati
 
     let gap = width.saturating_sub(bare + len(mark) + len(word));
     Line::from(vec![
-        Span::styled(mark, Style::default().fg(CLAUDE_ORANGE)),
+        Span::styled(mark, Style::default().fg(theme.claude)),
         // The name dim and the status in its own colour: the name is the
         // constant on this line and the status is the part that changes.
         Span::styled(word, Style::default().fg(dim)),
@@ -607,15 +685,15 @@ fn activity_line(row: &crate::oko::Row, width: usize, dim: Color) -> Line<'stati
     ])
 }
 
-fn status_style(status: &str) -> (&'static str, Color) {
+fn status_style(status: &str, theme: &Theme) -> (&'static str, Color) {
     match status {
-        "ready" => ("●", Color::Green),
-        "working" => ("◐", TEAL),
-        "waiting" => ("▲", ACCENT),
-        "stale" => ("○", Color::DarkGray),
+        "ready" => ("●", theme.ok),
+        "working" => ("◐", theme.working),
+        "waiting" => ("▲", theme.accent),
+        "stale" => ("○", theme.muted),
         // A status this build does not know is still worth showing as text —
         // the schema check upstream is what guards against drawing nonsense.
-        _ => ("·", Color::DarkGray),
+        _ => ("·", theme.muted),
     }
 }
 
@@ -649,46 +727,49 @@ fn truncate_left(s: &str, max: usize) -> String {
     format!("…{}", chars[chars.len() - keep..].iter().collect::<String>())
 }
 
-fn render_column_header(frame: &mut Frame, app: &App, area: Rect) {
-    let fields = [
-        (SortField::Name, "Name"),
-        (SortField::Extension, "Ext"),
-        (SortField::Size, "Size"),
-        (SortField::Modified, "Modified"),
-    ];
-
-    let spans: Vec<Span> = fields
-        .iter()
-        .enumerate()
-        .flat_map(|(i, (field, label))| {
-            let mut parts = Vec::new();
-            if i > 0 {
-                parts.push(Span::styled(" │ ", Style::default().fg(Color::DarkGray)));
-            }
-            if *field == app.sort_field {
-                parts.push(Span::styled(
-                    format!("{} {}", label, app.sort_direction.indicator()),
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
-                ));
-            } else {
-                parts.push(Span::styled(
-                    label.to_string(),
-                    Style::default().fg(Color::DarkGray),
-                ));
-            }
-            parts
-        })
-        .collect();
-
-    let header_line = Line::from(spans);
-    let header = Paragraph::new(header_line);
-    frame.render_widget(header, area);
+/// Each column's label over its cells, the sorted one marked with its
+/// direction. Laid out by `columns::areas`, as the clicks on it are.
+fn render_column_header(frame: &mut Frame, app: &App, pane_id: &str, area: Rect) {
+    let columns = app.pane_columns(pane_id);
+    let areas = crate::columns::areas(columns, area);
+    for (spec, cell) in columns.iter().zip(areas.iter()) {
+        let column = spec.column;
+        let label = if column.sort_field() == app.sort_field {
+            Span::styled(
+                format!("{} {}", column.label(), app.sort_direction.indicator()),
+                Style::default()
+                    .fg(app.theme.header)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Span::styled(column.label(), Style::default().fg(app.theme.muted))
+        };
+        frame.render_widget(Paragraph::new(Line::from(label)), *cell);
+    }
 }
 
 fn render_file_list(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect) {
     let is_active = app.active_pane_id == pane_id;
+    let theme = app.theme;
+    let file_styles = &app.file_styles;
+    let columns = app.pane_columns(pane_id).to_vec();
+    let formats = &app.columns;
+    let dir_sizes = &app.dir_sizes;
+    let now = crate::columns::now();
+    // Present only while this pane is one of a compared pair. Every row then
+    // gets the mark's column, blank or not, so the names stay in line.
+    let marks = app
+        .comparison
+        .as_ref()
+        .filter(|c| c.involves(pane_id))
+        .map(|c| &c.marks);
+    // Likewise git's: a column of them while the pane is in a repository,
+    // unless the list has a git column of its own.
+    let in_repo = !columns.iter().any(|s| s.column == Column::Git)
+        && app
+            .pane_map
+            .get(pane_id)
+            .is_some_and(|p| app.git.repo(&p.current_path).is_some());
 
     let pane = match app.pane_map.get_mut(pane_id) {
         Some(p) => p,
@@ -697,7 +778,7 @@ fn render_file_list(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect)
 
     if pane.entries.is_empty() {
         let empty = Paragraph::new("  (empty)")
-            .style(Style::default().fg(Color::DarkGray));
+            .style(Style::default().fg(theme.muted));
         frame.render_widget(empty, area);
         return;
     }
@@ -707,46 +788,33 @@ fn render_file_list(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect)
         .iter()
         .enumerate()
         .map(|(i, entry)| {
-            let icon = if entry.is_dir { "\u{f07b}" } else { "\u{f016}" };
-            let icon_color = if entry.is_dir { ACCENT } else { Color::DarkGray };
-
-            let ext = if entry.is_dir {
-                String::new()
+            let ty = Type::of(entry);
+            let type_style = file_styles.style(entry, ty);
+            // Folders keep the accent their icon always had; anything else
+            // takes its name's colour, if it has one.
+            let icon_color = if entry.is_dir {
+                theme.accent
             } else {
-                entry
-                    .name
-                    .rsplit_once('.')
-                    .map(|(_, e)| e.to_string())
-                    .unwrap_or_default()
+                type_style.fg.unwrap_or(theme.muted)
             };
 
-            let size = if entry.is_dir {
-                String::from("—")
-            } else {
-                format_size(entry.size)
-            };
-
-            let modified = format_date(entry.modified);
-
             let is_selected = pane.selected_paths.contains(&entry.path);
             let is_focused = is_active && pane.focus_index == i as i32;
 
             let (row_bg, name_fg) = if is_focused && is_selected {
-                (Some(Color::Blue), Color::White)
+                (Some(theme.selection_bg), theme.focus_fg)
             } else if is_focused {
-                (Some(Color::DarkGray), Color::White)
+                (Some(theme.focus_bg), theme.focus_fg)
             } else if is_selected {
-                (Some(Color::Blue), Color::White)
-            } else if entry.is_dir {
-                (None, Color::Blue)
+                (Some(theme.selection_bg), theme.focus_fg)
             } else {
                 (None, Color::Reset)
             };
 
             let mut icon_style = Style::default().fg(icon_color);
             let mut name_style = Style::default().fg(name_fg);
-            if entry.is_dir && !is_focused && !is_selected {
-                name_style = name_style.add_modifier(Modifier::BOLD);
+            if !is_focused && !is_selected {
+                name_style = type_style;
             }
             if is_focused && is_selected {
                 name_style = name_style.add_modifier(Modifier::BOLD);
@@ -756,34 +824,80 @@ fn render_file_list(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect)
                 name_style = name_style.bg(bg);
             }
 
-            let name_cell = Cell::from(Line::from(vec![
-                Span::styled(format!("{} ", icon), icon_style),
-                Span::styled(entry.name.clone(), name_style),
-            ]));
+            let mut name_spans = Vec::with_capacity(3);
+            if let Some(marks) = marks {
+                let span = match marks.get(&entry.path) {
+                    Some(mark) => {
+                        let mut style = Style::default()
+                            .fg(mark_color(*mark, &theme))
+                            .add_modifier(Modifier::BOLD);
+                        if let Some(bg) = row_bg {
+                            style = style.bg(bg);
+                        }
+                        Span::styled(format!("{} ", mark.glyph()), style)
+                    }
+                    None => Span::raw("  "),
+                };
+                name_spans.push(span);
+            }
+            if in_repo {
+                let span = match entry.git {
+                    Some(status) => {
+                        let (glyph, color) = git_mark(status, &theme);
+                        let mut style = Style::default().fg(color);
+                        if let Some(bg) = row_bg {
+                            style = style.bg(bg);
+                        }
+                        Span::styled(format!("{} ", glyph), style)
+                    }
+                    None => Span::raw("  "),
+                };
+                name_spans.push(span);
+            }
+            if file_styles.icons {
+                name_spans.push(Span::styled(format!("{} ", ty.icon()), icon_style));
+            }
+            name_spans.push(Span::styled(entry.name.clone(), name_style));
+            let name_cell = Cell::from(Line::from(name_spans));
 
-            let row_style = match row_bg {
+            let mut row_style = match row_bg {
                 Some(bg) => Style::default().bg(bg).fg(name_fg),
                 None => Style::default().fg(name_fg),
             };
+            // Without colour the cursor is told by reversing, as everywhere
+            // else, and the selection by underlining.
+            if theme.no_color {
+                if is_focused {
+                    row_style = row_style.add_modifier(Modifier::REVERSED);
+                }
+                if is_selected {
+                    row_style = row_style.add_modifier(Modifier::UNDERLINED);
+                }
+            }
 
-            Row::new(vec![
-                name_cell,
-                Cell::from(ext),
-                Cell::from(size),
-                Cell::from(modified),
-            ])
-            .style(row_style)
+            let mut name_cell = Some(name_cell);
+            let cells: Vec<Cell> = columns
+                .iter()
+                .map(|spec| match spec.column {
+                    Column::Name => name_cell.take().unwrap_or_default(),
+                    Column::Size if entry.is_dir && dir_sizes.is_pending(&entry.path) => {
+                        Cell::from("…")
+                    }
+                    Column::Git => match entry.git {
+                        Some(status) => {
+                            let (glyph, color) = git_mark(status, &theme);
+                            Cell::from(Span::styled(glyph, Style::default().fg(color)))
+                        }
+                        None => Cell::default(),
+                    },
+                    column => Cell::from(formats.cell(column, entry, now)),
+                })
+                .collect();
+            Row::new(cells).style(row_style)
         })
         .collect();
 
-    let widths = [
-        Constraint::Min(10),
-        Constraint::Length(6),
-        Constraint::Length(8),
-        Constraint::Length(12),
-    ];
-
-    let table = Table::new(rows, widths).column_spacing(1);
+    let table = Table::new(rows, crate::columns::constraints(&columns)).column_spacing(1);
 
     let len = pane.entries.len();
     let viewport = area.height as usize;
@@ -799,12 +913,33 @@ fn render_file_list(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect)
             width: 1,
             height: area.height,
         },
+        &theme,
         len,
         viewport,
         pane.table_state.offset(),
     );
 }
 
+fn git_mark(status: GitStatus, theme: &Theme) -> (&'static str, Color) {
+    let color = match status {
+        GitStatus::Conflicted => theme.error,
+        GitStatus::Modified => theme.accent,
+        GitStatus::Staged => theme.ok,
+        GitStatus::Untracked => theme.soft,
+        GitStatus::Ignored => theme.muted,
+    };
+    (crate::columns::git_letter(status), color)
+}
+
+fn mark_color(mark: RowMark, theme: &Theme) -> Color {
+    match mark {
+        RowMark::Unique => theme.ok,
+        RowMark::Newer => theme.accent,
+        RowMark::Older => theme.muted,
+        RowMark::Different => theme.error,
+    }
+}
+
 /// Draws a scroll thumb over `area` (one column, spanning the list rows).
 ///
 /// Hand-rolled rather than `ratatui::Scrollbar`, which rounds the thumb's
@@ -814,6 +949,7 @@ fn render_file_list(frame: &mut Frame, app: &mut App, pane_id: &str, area: Rect)
 fn render_scroll_thumb(
     frame: &mut Frame,
     area: Rect,
+    theme: &Theme,
     len: usize,
     viewport: usize,
     offset: usize,
@@ -831,7 +967,7 @@ fn render_scroll_thumb(
     // Round to nearest so the thumb lands flush at the top and the bottom.
     let start = (offset.min(max_offset) * travel + max_offset / 2) / max_offset;
 
-    let style = Style::default().fg(Color::DarkGray);
+    let style = Style::default().fg(theme.muted);
     let buf = frame.buffer_mut();
     for i in start..start + thumb {
         let y = area.y + i as u16;
@@ -860,30 +996,98 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
             .get(&app.active_pane_id)
             .map(|p| p.entries.len())
             .unwrap_or(0);
-        format!(" {} items", item_count)
+        // A compared pane says how much is left to settle, which is what the
+        // marks down its rows add up to.
+        let differing = app
+            .comparison
+            .as_ref()
+            .filter(|c| c.involves(&app.active_pane_id))
+            .map(|c| {
+                if c.is_busy() {
+                    " · comparing…".to_string()
+                } else {
+                    format!(" · {} differ", c.diffs.len())
+                }
+            })
+            .unwrap_or_default();
+        format!(" {} items{}", item_count, differing)
     };
 
+    // The normal-mode hint names whatever help and quit are bound to.
+    let normal_hint;
     let mode_hint = match &app.mode {
-        AppMode::Normal => "?:help  q:quit",
+        AppMode::Normal => {
+            let first = |action| app.keymap.keys_for(action).into_iter().next();
+            let pick = app.pick.map(|_| "Enter:pick".to_string());
+            normal_hint = pick
+                .into_iter()
+                .chain(
+                    [(Action::Help, "help"), (Action::Quit, "quit")]
+                        .into_iter()
+                        .filter_map(|(action, label)| Some(format!("{}:{}", first(action)?, label))),
+                )
+                .collect::<Vec<_>>()
+                .join("  ");
+            normal_hint.as_str()
+        }
         AppMode::Help => "Esc/q/?:close",
         AppMode::Search { .. } => "Esc:cancel  Enter:confirm",
         AppMode::Rename { .. } => "Esc:cancel  Enter:rename",
         AppMode::Confirm { .. } => "←→:select  Enter:confirm  y/n  Esc:cancel",
-        AppMode::Prompt { .. } => "Esc:cancel  Enter:create",
-        AppMode::PathEdit { .. } => "Tab:complete  Bksp:up dir  Enter:go  Esc:cancel",
-        AppMode::FavoritesList { .. } => "↑↓:select  Enter:go  e:edit path  d:remove  Esc:cancel",
+        AppMode::Prompt {
+            action: PromptAction::NewFile(_) | PromptAction::NewFolder(_),
+            ..
+        } => "Esc:cancel  Enter:create",
+        AppMode::Prompt {
+            action: PromptAction::Shell(_),
+            ..
+        } => "%f:focused  %s:selection  %d:folder  %%:a percent sign  Enter:run  Esc:cancel",
+        AppMode::Prompt { .. } => "Esc:cancel  Enter:set",
+        AppMode::PathEdit { .. } => "Tab/↓:next  ↑:previous  Bksp:up dir  Enter:go  Esc:cancel",
+        AppMode::FavoritesList { .. } => {
+            "↑↓:select  J/K:move  Enter:go  n:label  g:group  b:key  d:remove  e:edit path  Tab:recent"
+        }
+        AppMode::Mark { set: true, .. } => "press a key to put this folder under  Esc:cancel",
+        // Going, the keys there are to go to are the hint.
+        AppMode::Mark { set: false, .. } => {
+            normal_hint = app
+                .config
+                .favorites
+                .entries
+                .iter()
+                .filter_map(|f| Some(format!("'{}:{}", f.key?, f.name())))
+                .collect::<Vec<_>>()
+                .join("  ");
+            if normal_hint.is_empty() {
+                "no folder is under a key yet — m and a key puts one there"
+            } else {
+                normal_hint.as_str()
+            }
+        }
+        AppMode::Jump { .. } => "type words of a folder's path  ↑↓:select  Enter:go  Esc:cancel",
+        AppMode::OpenWith { .. } => "↑↓:select  Enter/1-9:open  Tab:where it runs  Esc:cancel",
+        AppMode::RecentList { .. } => "↑↓:select  Enter:go  Tab:favorites  d:forget  Esc:cancel",
+        AppMode::LayoutPicker { .. } => "↑↓:select  Enter:open  Esc:cancel",
+        AppMode::Palette { .. } => "↑↓:select  Tab:complete  Enter:run  Esc:cancel",
+        AppMode::SyncPlan { .. } => "Tab:direction  ↑↓:scroll  Enter:sync  Esc:cancel",
+        AppMode::Diff { .. } => "↑↓:scroll  n/N:next/prev change  Tab:unified  Esc:close",
+        AppMode::GitLog { .. } => "↑↓:scroll  g/G:newest/oldest  Esc:close",
+        AppMode::Usage => "↑↓:select  Enter/→:open  ←:up  d:delete  Esc:stop/close",
+        AppMode::BulkRename { .. } => {
+            "Tab:field  ^R:regex  ^T:case  ↑↓:scroll  Enter:rename  Esc:cancel"
+        }
     };
 
     let line = match &app.status_message {
         Some(msg) => Line::from(vec![
-            Span::styled(msg.clone(), Style::default().fg(Color::Yellow)),
+            Span::styled(msg.clone(), Style::default().fg(app.theme.status)),
             Span::raw("  "),
-            Span::styled(mode_hint, Style::default().fg(Color::DarkGray)),
+            Span::styled(mode_hint, Style::default().fg(app.theme.muted)),
         ]),
         None => Line::from(vec![
-            Span::styled(left, Style::default().fg(Color::DarkGray)),
+            Span::styled(left, Style::default().fg(app.theme.muted)),
             Span::raw("  "),
-            Span::styled(mode_hint, Style::default().fg(Color::DarkGray)),
+            Span::styled(mode_hint, Style::default().fg(app.theme.muted)),
         ]),
     };
 
@@ -899,30 +1103,37 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
 
     frame.render_widget(Paragraph::new(line), cols[0]);
     frame.render_widget(
-        Paragraph::new(BRAND).style(Style::default().fg(ACCENT).add_modifier(Modifier::BOLD)),
+        Paragraph::new(BRAND).style(Style::default().fg(app.theme.accent).add_modifier(Modifier::BOLD)),
         cols[1],
     );
 }
 
-fn render_confirm_dialog(frame: &mut Frame, area: Rect, title: &str, message: &str, selected: usize) {
+fn render_confirm_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    title: &str,
+    message: &str,
+    selected: usize,
+) {
     let dialog = centered_rect(50, 7, area);
     frame.render_widget(Clear, dialog);
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow))
+        .border_style(Style::default().fg(theme.choice))
         .title(format!(" {} ", title));
     let inner = block.inner(dialog);
     frame.render_widget(block, dialog);
 
     let yes_style = if selected == 0 {
-        Style::default().fg(Color::Black).bg(Color::Green).add_modifier(Modifier::BOLD)
+        theme.highlight(theme.ok).add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
     let no_style = if selected == 1 {
-        Style::default().fg(Color::Black).bg(Color::Red).add_modifier(Modifier::BOLD)
+        theme.highlight(theme.error).add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(theme.muted)
     };
 
     let text = Paragraph::new(vec![
@@ -939,12 +1150,19 @@ fn render_confirm_dialog(frame: &mut Frame, area: Rect, title: &str, message: &s
     frame.render_widget(text, inner);
 }
 
-fn render_prompt_dialog(frame: &mut Frame, area: Rect, title: &str, input: &str, cursor: usize) {
+fn render_prompt_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    title: &str,
+    input: &str,
+    cursor: usize,
+) {
     let dialog = centered_rect(60, 7, area);
     frame.render_widget(Clear, dialog);
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(Style::default().fg(theme.prompt))
         .title(format!(" {} ", title));
     let inner = block.inner(dialog);
     frame.render_widget(block, dialog);
@@ -956,7 +1174,7 @@ fn render_prompt_dialog(frame: &mut Frame, area: Rect, title: &str, input: &str,
         Span::raw(before),
         Span::styled(
             if after.is_empty() { " " } else { &after[..1] },
-            Style::default().bg(Color::White).fg(Color::Black),
+            theme.cursor(),
         ),
         Span::raw(if after.len() > 1 { &after[1..] } else { "" }),
     ]);
@@ -965,23 +1183,157 @@ fn render_prompt_dialog(frame: &mut Frame, area: Rect, title: &str, input: &str,
     frame.render_widget(text, inner);
 }
 
-fn render_favorites_dialog(frame: &mut Frame, area: Rect, favorites: &[String], selected: usize) {
-    let height = (favorites.len() as u16 + 4).min(area.height.saturating_sub(4));
+/// What the path prompt's input completes to, in a list hung below it:
+/// folders marked and coloured as in the panes, the one Tab is on
+/// highlighted.
+fn render_completions(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    completions: &[crate::complete::Candidate],
+    selected: Option<usize>,
+    partial: bool,
+) {
+    if completions.is_empty() && !partial {
+        return;
+    }
+    let theme = &app.theme;
+    let prompt = centered_rect(60, 7, area);
+    let below = prompt.y + prompt.height;
+    let room = (area.y + area.height).saturating_sub(below);
+    let height = (completions.len() as u16 + partial as u16 + 2).min(12).min(room);
+    if height < 3 {
+        return;
+    }
+    let dropdown = Rect::new(prompt.x, below, prompt.width, height);
+    frame.render_widget(Clear, dropdown);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.muted));
+    let inner = block.inner(dropdown);
+    frame.render_widget(block, dropdown);
+
+    let rows = inner.height.saturating_sub(partial as u16) as usize;
+    let skip = selected.map_or(0, |s| (s + 1).saturating_sub(rows));
+    let mut lines = Vec::new();
+    for (i, candidate) in completions.iter().enumerate().skip(skip).take(rows) {
+        let kind = if candidate.is_dir {
+            crate::filetype::Type::Dir
+        } else {
+            crate::filetype::Type::File
+        };
+        let marker = if app.file_styles.icons {
+            kind.icon()
+        } else if candidate.is_dir {
+            "▸"
+        } else {
+            " "
+        };
+        // The folder it is in is shown quieter: a loose segment may have
+        // led to more than one, but the name is what is being chosen.
+        let trimmed = candidate.path.trim_end_matches('/');
+        let (parent, name) = candidate.path.split_at(trimmed.rfind('/').map_or(0, |at| at + 1));
+        let mut style = if candidate.is_dir {
+            Style::default().fg(theme.directory).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        let mut quiet = Style::default().fg(theme.muted);
+        if selected == Some(i) {
+            style = theme.highlight(theme.prompt).add_modifier(Modifier::BOLD);
+            quiet = theme.highlight(theme.prompt);
+        }
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {} ", marker), style),
+            Span::styled(parent.to_string(), quiet),
+            Span::styled(name.to_string(), style),
+        ]));
+    }
+    if partial {
+        lines.push(Line::from(Span::styled(
+            " some folders were too slow to list",
+            Style::default().fg(theme.muted),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The favorites: each under its group's heading, with the key it is bound
+/// to and its label before its path.
+fn render_favorites(frame: &mut Frame, area: Rect, app: &App, selected: usize) {
+    let theme = &app.theme;
+    let favorites = &app.config.favorites.entries;
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    let mut group = None;
+    for (i, favorite) in favorites.iter().enumerate() {
+        if favorite.group.is_some() && favorite.group != group {
+            group = favorite.group.clone();
+            lines.push(Line::from(Span::styled(
+                format!(" {}", favorite.group.as_deref().unwrap_or_default()),
+                Style::default().fg(theme.heading).add_modifier(Modifier::BOLD),
+            )));
+        }
+        if i == selected {
+            selected_line = lines.len();
+        }
+        let (style, quiet) = if i == selected {
+            let style = theme.highlight(theme.choice);
+            (style.add_modifier(Modifier::BOLD), style)
+        } else {
+            (Style::default().fg(theme.text), Style::default().fg(theme.muted))
+        };
+        let key = favorite.key.map(|k| format!("'{}", k)).unwrap_or_default();
+        let mut spans = vec![
+            Span::styled(format!("  {:<3}", key), Style::default().fg(theme.key)),
+            Span::styled(format!("{}  ", favorite.name()), style),
+        ];
+        if favorite.label.is_some() {
+            spans.push(Span::styled(format!("{}  ", favorite.path), quiet));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let dialog = centered_rect(60, height, area);
+    frame.render_widget(Clear, dialog);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.choice))
+        .title(" ★ Favorites ");
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+    let skip = (selected_line + 1).saturating_sub(inner.height as usize);
+    frame.render_widget(Paragraph::new(lines).scroll((skip as u16, 0)), inner);
+}
+
+/// The recent folders: a list of paths to pick one from, scrolled to keep
+/// the pick in view.
+fn render_path_list(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    title: &str,
+    paths: &[String],
+    selected: usize,
+) {
+    let height = (paths.len() as u16 + 4).min(area.height.saturating_sub(4));
     let dialog = centered_rect(60, height, area);
     frame.render_widget(Clear, dialog);
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Yellow))
-        .title(" ★ Favorites (e:edit path) ");
+        .border_style(Style::default().fg(theme.choice))
+        .title(title);
     let inner = block.inner(dialog);
     frame.render_widget(block, dialog);
 
+    let skip = selected.saturating_sub((inner.height as usize).saturating_sub(1));
     let mut lines = Vec::new();
-    for (i, path) in favorites.iter().enumerate() {
+    for (i, path) in paths.iter().enumerate().skip(skip) {
         let style = if i == selected {
-            Style::default().fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD)
+            theme.highlight(theme.choice).add_modifier(Modifier::BOLD)
         } else {
-            Style::default().fg(Color::White)
+            Style::default().fg(theme.text)
         };
         lines.push(Line::from(Span::styled(format!("  {}  ", path), style)));
     }
@@ -990,7 +1342,735 @@ fn render_favorites_dialog(frame: &mut Frame, area: Rect, favorites: &[String],
     frame.render_widget(text, inner);
 }
 
-fn help_lines(sections: &[(&str, &[(&str, &str)])]) -> Vec<Line<'static>> {
+fn render_layout_picker(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    names: &[&String],
+    selected: usize,
+) {
+    let height = (names.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let dialog = centered_rect(40, height, area);
+    frame.render_widget(Clear, dialog);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.choice))
+        .title(" Layouts ");
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    // Kept in view as it moves: the list is not scrolled otherwise.
+    let skip = selected.saturating_sub(inner.height.saturating_sub(1) as usize);
+    let lines: Vec<Line> = names
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(i, name)| {
+            let style = if i == selected {
+                theme.highlight(theme.choice).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text)
+            };
+            Line::from(Span::styled(format!("  {}  ", name), style))
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// "Open with": each program with where it runs, numbered for the keys
+/// that pick it.
+fn render_open_with(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    paths: &[String],
+    choices: &[Option<Opener>],
+    selected: usize,
+) {
+    let title = match paths {
+        [one] => format!(
+            " Open {} with ",
+            std::path::Path::new(one)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        ),
+        many => format!(" Open {} items with ", many.len()),
+    };
+    let height = (choices.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let dialog = centered_rect(60, height, area);
+    frame.render_widget(Clear, dialog);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.choice))
+        .title(title);
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    let skip = selected.saturating_sub(inner.height.saturating_sub(1) as usize);
+    let lines: Vec<Line> = choices
+        .iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(i, choice)| {
+            let style = if i == selected {
+                theme.highlight(theme.choice).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.text)
+            };
+            let number = if i < 9 { format!(" {} ", i + 1) } else { "   ".to_string() };
+            let mut spans = vec![Span::styled(number, Style::default().fg(theme.key))];
+            match choice {
+                Some(opener) => {
+                    spans.push(Span::styled(format!(" {} ", opener.label()), style));
+                    if opener.name.is_some() {
+                        spans.push(Span::styled(
+                            format!(" {}", opener.command),
+                            Style::default().fg(theme.muted),
+                        ));
+                    }
+                    let place = match opener.run.unwrap_or(RunIn::Terminal) {
+                        RunIn::Terminal => "new terminal",
+                        RunIn::Current => "this terminal",
+                        RunIn::Detached => "detached",
+                    };
+                    spans.push(Span::styled(format!("  [{}]", place), Style::default().fg(theme.muted)));
+                }
+                None => spans.push(Span::styled(" system default ".to_string(), style)),
+            }
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The `z` prompt, laid out like the palette: what is typed, then the
+/// folders it finds.
+fn render_jump(frame: &mut Frame, area: Rect, app: &App, input: &str, matches: &[String], selected: usize) {
+    let theme = &app.theme;
+    let height = (matches.len() as u16 + 4).clamp(5, 18).min(area.height.saturating_sub(2));
+    let dialog = Rect::new(
+        area.x + area.width.saturating_sub(area.width * 3 / 5) / 2,
+        area.y + area.height / 6,
+        area.width * 3 / 5,
+        height,
+    );
+    frame.render_widget(Clear, dialog);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.prompt))
+        .title(" Jump ");
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(" z ", Style::default().fg(theme.key)),
+            Span::raw(input.to_string()),
+            Span::styled(" ", theme.cursor()),
+        ]),
+        Line::from(""),
+    ];
+    let rows = inner.height.saturating_sub(2) as usize;
+    let skip = (selected + 1).saturating_sub(rows);
+    for (i, path) in matches.iter().enumerate().skip(skip).take(rows) {
+        let shown = match path.strip_prefix(&app.home_path) {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("~{}", rest),
+            _ => path.clone(),
+        };
+        let style = if i == selected {
+            theme.highlight(theme.prompt).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        lines.push(Line::from(Span::styled(format!("  {}", shown), style)));
+    }
+    if matches.is_empty() {
+        let note = if app.frecency.dirs.is_empty() {
+            "  no folders yet — they are added as you visit them"
+        } else {
+            "  no folder matches"
+        };
+        lines.push(Line::from(Span::styled(note, Style::default().fg(theme.muted))));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The palette: what has been typed, then what it matches — each entry with
+/// its key, so the palette teaches the shortcut it saves you looking up.
+fn render_palette(frame: &mut Frame, area: Rect, app: &App, state: &Palette) {
+    let theme = &app.theme;
+    let height = (state.matches.len() as u16 + 4).clamp(5, 18).min(area.height.saturating_sub(2));
+    let dialog = Rect::new(
+        area.x + area.width.saturating_sub(area.width * 3 / 5) / 2,
+        area.y + area.height / 6,
+        area.width * 3 / 5,
+        height,
+    );
+    frame.render_widget(Clear, dialog);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.prompt))
+        .title(" Commands ");
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(" : ", Style::default().fg(theme.key)),
+            Span::raw(state.input.clone()),
+            Span::styled(" ", theme.cursor()),
+        ]),
+        Line::from(""),
+    ];
+    let rows = inner.height.saturating_sub(2) as usize;
+    let skip = (state.selected + 1).saturating_sub(rows);
+    let width = inner.width as usize;
+    for (i, entry) in state.matches.iter().enumerate().skip(skip).take(rows) {
+        let (name, help, key) = match entry {
+            Entry::Recent(line) => (line.clone(), "recent", String::new()),
+            Entry::Action(action) => (
+                action.name().to_string(),
+                action.help(),
+                app.keymap.keys_for(*action).join(" / "),
+            ),
+            Entry::Command(command) => (command.usage.to_string(), command.help, String::new()),
+        };
+        let selected = i == state.selected;
+        let (name_style, dim) = if selected {
+            let style = theme.highlight(theme.prompt);
+            (style.add_modifier(Modifier::BOLD), style)
+        } else {
+            (Style::default().fg(theme.text), Style::default().fg(theme.muted))
+        };
+        let left = format!("  {:<22} {}", name, help);
+        let pad = width.saturating_sub(left.chars().count() + key.chars().count() + 1);
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<22} ", name), name_style),
+            Span::styled(format!("{}{}", help, " ".repeat(pad)), dim),
+            Span::styled(format!("{} ", key), if selected { dim } else { Style::default().fg(theme.key) }),
+        ]));
+    }
+    if state.matches.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  no match — Enter runs it as typed",
+            Style::default().fg(theme.muted),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+/// The dry run: every copy the sync would make and every name it would leave
+/// alone, listed before anything is written.
+fn render_sync_dialog(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    direction: SyncDirection,
+    plan: &SyncPlan,
+    offset: usize,
+) {
+    let mut lines: Vec<Line> = plan
+        .steps
+        .iter()
+        .map(|step| {
+            let arrow = if step.rightward { "→" } else { "←" };
+            Line::from(vec![
+                Span::styled(format!("  {} ", arrow), Style::default().fg(theme.accent)),
+                Span::styled(step.name.clone(), Style::default().fg(theme.text)),
+            ])
+        })
+        .collect();
+    lines.extend(plan.skipped.iter().map(|(name, reason)| {
+        Line::from(vec![
+            Span::styled("  · ", Style::default().fg(theme.muted)),
+            Span::styled(name.clone(), Style::default().fg(theme.soft)),
+            Span::styled(format!(" — {}", reason), Style::default().fg(theme.muted)),
+        ])
+    }));
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  nothing to copy",
+            Style::default().fg(theme.muted),
+        )));
+    }
+
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4)).max(3);
+    let dialog = centered_rect(70, height, area);
+    frame.render_widget(Clear, dialog);
+    let how = match direction {
+        SyncDirection::LeftToRight => "left → right",
+        SyncDirection::RightToLeft => "right → left",
+        SyncDirection::Both => "both ways",
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.choice))
+        .title(format!(" Sync {}: {} to copy ", how, plan.steps.len()));
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    let offset = offset.min(lines.len().saturating_sub(inner.height as usize));
+    let len = lines.len();
+    let visible: Vec<Line> = lines.into_iter().skip(offset).collect();
+    frame.render_widget(Paragraph::new(visible), inner);
+    render_scroll_thumb(
+        frame,
+        Rect {
+            x: dialog.x + dialog.width.saturating_sub(1),
+            y: inner.y,
+            width: 1,
+            height: inner.height,
+        },
+        theme,
+        len,
+        inner.height as usize,
+        offset,
+    );
+}
+
+/// A path's commits, newest first: id, age, author and subject.
+fn render_git_log(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    path: &str,
+    commits: &[Commit],
+    offset: usize,
+) {
+    let now = crate::columns::now();
+    let author_width = commits
+        .iter()
+        .map(|c| c.author.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(18);
+    let lines: Vec<Line> = commits
+        .iter()
+        .map(|commit| {
+            let author: String = commit.author.chars().take(author_width).collect();
+            Line::from(vec![
+                Span::styled(format!("  {} ", commit.id), Style::default().fg(theme.accent)),
+                Span::styled(
+                    format!("{:>8} ", format_relative(commit.time.max(0) as u64, now)),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(
+                    format!("{:<width$}  ", author, width = author_width),
+                    Style::default().fg(theme.soft),
+                ),
+                Span::styled(commit.subject.clone(), Style::default().fg(theme.text)),
+            ])
+        })
+        .collect();
+
+    let height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4)).max(3);
+    let dialog = centered_rect(80, height, area);
+    frame.render_widget(Clear, dialog);
+    let name = std::path::Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(format!(" History of {}: {} commit(s) ", name, commits.len()));
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    let offset = offset.min(lines.len().saturating_sub(inner.height as usize));
+    let len = lines.len();
+    let visible: Vec<Line> = lines.into_iter().skip(offset).collect();
+    frame.render_widget(Paragraph::new(visible), inner);
+    render_scroll_thumb(
+        frame,
+        Rect {
+            x: dialog.x + dialog.width.saturating_sub(1),
+            y: inner.y,
+            width: 1,
+            height: inner.height,
+        },
+        theme,
+        len,
+        inner.height as usize,
+        offset,
+    );
+}
+
+/// Cells in a usage bar: enough to tell a third from a half at a glance.
+const USAGE_BAR: usize = 12;
+
+/// The disk usage view, over the panes like the diff viewer: the current
+/// folder's entries, biggest first, each with its share of the folder.
+fn render_usage_view(frame: &mut Frame, area: Rect, theme: &Theme, usage: &mut Usage) {
+    let height = area.height.saturating_sub(2).max(3);
+    let dialog = centered_rect(96, height, area);
+    frame.render_widget(Clear, dialog);
+
+    let current = &usage.nodes[usage.current];
+    let state = if usage.is_scanning() {
+        " · scanning…".to_string()
+    } else if usage.cancelled {
+        " · stopped, sizes are partial".to_string()
+    } else {
+        String::new()
+    };
+    let unreadable = match usage.unreadable {
+        0 => String::new(),
+        n => format!(" · {} unreadable", n),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(format!(
+            " {} · {} in {} item(s){}{} ",
+            usage.path(usage.current),
+            format_size(current.size),
+            current.items,
+            state,
+            unreadable
+        ));
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    let rows = usage.rows();
+    if rows.is_empty() {
+        let text = if usage.is_scanning() { "  reading…" } else { "  empty" };
+        frame.render_widget(
+            Paragraph::new(Span::styled(text, Style::default().fg(theme.muted))),
+            inner,
+        );
+        return;
+    }
+
+    // Kept in view as the cursor moves, and full when scrolled to the end.
+    let cursor = usage.cursor(&rows);
+    usage.page = inner.height as usize;
+    if cursor < usage.offset {
+        usage.offset = cursor;
+    } else if cursor >= usage.offset + usage.page {
+        usage.offset = cursor + 1 - usage.page;
+    }
+    usage.offset = usage.offset.min(rows.len().saturating_sub(usage.page));
+
+    let total = current.size.max(1);
+    let lines: Vec<Line> = rows
+        .iter()
+        .enumerate()
+        .skip(usage.offset)
+        .take(usage.page)
+        .map(|(row, &id)| {
+            let node = &usage.nodes[id];
+            let share = node.size as f64 / total as f64;
+            let filled = ((share * USAGE_BAR as f64).round() as usize).min(USAGE_BAR);
+            let items = if node.is_dir {
+                format!("{:>8}", node.items)
+            } else {
+                " ".repeat(8)
+            };
+            let name = if node.is_dir {
+                format!("{}/", node.name)
+            } else {
+                node.name.clone()
+            };
+            // Until its listing is in, a folder may be much bigger than it
+            // shows; muted says so without a column to say it.
+            let size_style = Style::default().fg(if node.read { theme.text } else { theme.muted });
+            let name_style = if node.unreadable {
+                Style::default().fg(theme.error)
+            } else if node.is_dir {
+                Style::default().fg(theme.directory)
+            } else {
+                Style::default().fg(theme.text)
+            };
+            let mut spans = vec![
+                Span::styled(format!(" {:>10} ", format_size(node.size)), size_style),
+                Span::styled(format!("{:>5.1}% ", share * 100.0), Style::default().fg(theme.soft)),
+                Span::styled("█".repeat(filled), Style::default().fg(theme.accent)),
+                Span::styled(
+                    "░".repeat(USAGE_BAR - filled),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::styled(items, Style::default().fg(theme.muted)),
+                Span::raw("  "),
+                Span::styled(name, name_style),
+            ];
+            if row == cursor {
+                let style = theme.highlight(theme.focus_bg);
+                spans = spans
+                    .into_iter()
+                    .map(|span| span.style(style))
+                    .collect();
+            }
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), inner);
+    render_scroll_thumb(
+        frame,
+        Rect {
+            x: dialog.x + dialog.width.saturating_sub(1),
+            y: inner.y,
+            width: 1,
+            height: inner.height,
+        },
+        theme,
+        rows.len(),
+        inner.height as usize,
+        usage.offset,
+    );
+}
+
+/// The diff viewer takes nearly the whole screen: it is read, not glanced at,
+/// and side by side each file gets only half the width as it is.
+fn render_diff_view(frame: &mut Frame, area: Rect, theme: &Theme, view: &mut DiffView) {
+    let height = area.height.saturating_sub(2).max(3);
+    let dialog = centered_rect(96, height, area);
+    frame.render_widget(Clear, dialog);
+
+    let name = |path: &str| {
+        std::path::Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string())
+    };
+    let (left, right) = (name(&view.left_path), name(&view.right_path));
+    // Same-named files are the usual case (one config, two deploys), where the
+    // names tell nothing apart — the folders they sit in do.
+    let (left, right) = if left == right {
+        let parent = |path: &str| {
+            std::path::Path::new(path)
+                .parent()
+                .map(|p| name(&p.to_string_lossy()))
+                .unwrap_or_default()
+        };
+        (
+            format!("{}/{}", parent(&view.left_path), left),
+            format!("{}/{}", parent(&view.right_path), right),
+        )
+    } else {
+        (left, right)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title(format!(
+            " {} ↔ {} · {} change(s) ",
+            left,
+            right,
+            view.change_count()
+        ));
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    // Clamped here rather than in the scroll keys, which do not know how much
+    // fits: scrolled to the end, the last page stays full.
+    let len = view.row_count();
+    view.page = inner.height as usize;
+    view.offset = view.offset.min(len.saturating_sub(view.page));
+
+    let width = view
+        .lines
+        .iter()
+        .filter_map(|l| l.left.max(l.right))
+        .max()
+        .unwrap_or(0)
+        .to_string()
+        .len();
+    let number = |n: Option<usize>| match n {
+        Some(n) => format!("{:>w$} ", n, w = width),
+        None => " ".repeat(width + 1),
+    };
+    let style = |change: LineChange| match change {
+        LineChange::Same => Style::default().fg(theme.soft),
+        LineChange::Removed => Style::default().fg(theme.error),
+        LineChange::Added => Style::default().fg(theme.ok),
+    };
+    let gutter = Style::default().fg(theme.muted);
+    // Tabs are drawn as spaces: a terminal cell holds one character, and a
+    // raw tab would throw every column after it out of line.
+    let text = |line: &DiffLine| line.text.replace('\t', "    ");
+
+    if view.unified {
+        let rows: Vec<Line> = view
+            .lines
+            .iter()
+            .skip(view.offset)
+            .take(view.page)
+            .map(|line| {
+                let sign = match line.change {
+                    LineChange::Same => " ",
+                    LineChange::Removed => "-",
+                    LineChange::Added => "+",
+                };
+                Line::from(vec![
+                    Span::styled(number(line.left), gutter),
+                    Span::styled(number(line.right), gutter),
+                    Span::styled(format!("{} {}", sign, text(line)), style(line.change)),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(rows), inner);
+    } else {
+        let halves = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(50),
+                Constraint::Length(1),
+                Constraint::Percentage(50),
+            ])
+            .split(inner);
+        let side = |left: bool| {
+            view.rows
+                .iter()
+                .skip(view.offset)
+                .take(view.page)
+                .map(|&(l, r)| match (if left { l } else { r }).map(|i| &view.lines[i]) {
+                    Some(line) => Line::from(vec![
+                        Span::styled(number(if left { line.left } else { line.right }), gutter),
+                        Span::styled(text(line), style(line.change)),
+                    ]),
+                    None => Line::from(""),
+                })
+                .collect::<Vec<Line>>()
+        };
+        frame.render_widget(Paragraph::new(side(true)), halves[0]);
+        let rule: Vec<Line> = (0..halves[1].height)
+            .map(|_| Line::from(Span::styled("│", gutter)))
+            .collect();
+        frame.render_widget(Paragraph::new(rule), halves[1]);
+        frame.render_widget(Paragraph::new(side(false)), halves[2]);
+    }
+
+    render_scroll_thumb(
+        frame,
+        Rect {
+            x: dialog.x + dialog.width.saturating_sub(1),
+            y: inner.y,
+            width: 1,
+            height: inner.height,
+        },
+        theme,
+        len,
+        view.page,
+        view.offset,
+    );
+}
+
+fn render_bulk_rename_dialog(frame: &mut Frame, area: Rect, theme: &Theme, state: &mut BulkRename) {
+    let height = area.height.saturating_sub(4).max(8);
+    let dialog = centered_rect(80, height, area);
+    frame.render_widget(Clear, dialog);
+    let (changed, problems) = state.counts();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.prompt))
+        .title(format!(
+            " Rename {} item(s): {} change(s) ",
+            state.paths.len(),
+            changed
+        ));
+    let inner = block.inner(dialog);
+    frame.render_widget(block, dialog);
+
+    let label = Style::default().fg(theme.muted);
+    let mut lines: Vec<Line> = FIELDS
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let value = match i {
+                0 => &state.rule.find,
+                1 => &state.rule.replace,
+                _ => &state.rule.extension,
+            };
+            let mut spans = vec![Span::styled(format!("  {:<10}", name), label)];
+            if i == state.field {
+                let at = value
+                    .char_indices()
+                    .nth(state.cursor)
+                    .map_or(value.len(), |(b, _)| b);
+                let (before, after) = value.split_at(at);
+                let mut rest = after.chars();
+                let under = rest.next().map_or(" ".to_string(), String::from);
+                spans.push(Span::raw(before.to_string()));
+                spans.push(Span::styled(under, theme.cursor()));
+                spans.push(Span::raw(rest.as_str().to_string()));
+            } else {
+                spans.push(Span::styled(value.clone(), Style::default().fg(theme.soft)));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    let toggle = |on: bool| if on { theme.key } else { theme.muted };
+    lines.push(Line::from(vec![
+        Span::styled("  ", label),
+        Span::styled("regex", Style::default().fg(toggle(state.rule.regex))),
+        Span::styled("  ·  ", label),
+        Span::styled(
+            state.rule.case.label(),
+            Style::default().fg(toggle(state.rule.case != CaseChange::Keep)),
+        ),
+        Span::styled("  ·  {n} {n:03} count, $1 group", label),
+    ]));
+    lines.push(Line::from(""));
+    let header = lines.len();
+
+    match &state.preview {
+        Err(e) => lines.push(Line::from(Span::styled(
+            format!("  {}", e),
+            Style::default().fg(theme.error),
+        ))),
+        Ok(previews) => {
+            let rows = inner.height.saturating_sub(header as u16) as usize;
+            state.offset = state.offset.min(previews.len().saturating_sub(rows));
+            for p in previews.iter().skip(state.offset).take(rows) {
+                let (marker, style) = match (&p.problem, p.changes()) {
+                    (Some(_), _) => ("✗", Style::default().fg(theme.error)),
+                    (None, false) => (" ", Style::default().fg(theme.muted)),
+                    (None, true) if p.chained => ("↻", Style::default().fg(theme.accent)),
+                    (None, true) => ("→", Style::default().fg(theme.ok)),
+                };
+                let mut spans = vec![
+                    Span::styled(format!("  {} ", marker), style),
+                    Span::styled(p.old_name.clone(), Style::default().fg(theme.soft)),
+                ];
+                if p.changes() {
+                    spans.push(Span::styled("  →  ", label));
+                    spans.push(Span::styled(p.new_name.clone(), Style::default().fg(theme.text)));
+                }
+                if let Some(problem) = &p.problem {
+                    spans.push(Span::styled(
+                        format!("  ({})", problem),
+                        Style::default().fg(theme.error),
+                    ));
+                }
+                lines.push(Line::from(spans));
+            }
+            if problems > 0 {
+                // Where the scrolled list may not show them, the count does.
+                let last = inner.y + inner.height.saturating_sub(1);
+                frame.render_widget(
+                    Paragraph::new(Span::styled(
+                        format!(" {} problem(s) ", problems),
+                        Style::default().fg(theme.error),
+                    )),
+                    Rect::new(dialog.x + 2, last + 1, 16.min(dialog.width.saturating_sub(4)), 1),
+                );
+            }
+        }
+    }
+    frame.render_widget(Paragraph::new(lines), inner);
+}
+
+fn help_lines(theme: &Theme, sections: &[(&str, Vec<(String, &str)>)]) -> Vec<Line<'static>> {
+    // Wide enough for the longest chord, so a rebinding to something long
+    // pushes the descriptions over rather than running into them.
+    let width = sections
+        .iter()
+        .flat_map(|(_, items)| items.iter().map(|(key, _)| key.chars().count()))
+        .max()
+        .unwrap_or(0)
+        .max(10);
     let mut lines = Vec::new();
     for (i, (title, items)) in sections.iter().enumerate() {
         if i > 0 {
@@ -998,94 +2078,69 @@ fn help_lines(sections: &[(&str, &[(&str, &str)])]) -> Vec<Line<'static>> {
         }
         lines.push(Line::from(Span::styled(
             format!(" {}", title),
-            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            Style::default().fg(theme.heading).add_modifier(Modifier::BOLD),
         )));
         for (key, desc) in items.iter() {
             lines.push(Line::from(vec![
-                Span::styled(format!("  {:<12}", key), Style::default().fg(Color::Cyan)),
-                Span::styled((*desc).to_string(), Style::default().fg(Color::White)),
+                Span::styled(format!("  {:<width$}  ", key), Style::default().fg(theme.key)),
+                Span::styled((*desc).to_string(), Style::default().fg(theme.text)),
             ]));
         }
     }
     lines
 }
 
-fn render_help_dialog(frame: &mut Frame, area: Rect, oko_available: bool) {
-    // Listed only when there is a usable oko to open. An entry for a key that
-    // is not bound is worse than no entry at all.
-    let mut panes: Vec<(&str, &str)> = vec![
-        ("|", "split vertical"),
-        ("_", "split horizontal"),
-        ("+ / =", "grow pane 25%"),
-        ("-", "shrink pane 25%"),
-        ("W", "close pane"),
-    ];
-    if oko_available {
-        panes.push(("O", "tab cards (oko)"));
-    }
+/// One section of the help, from the active keymap. An action with no key
+/// is left out — an entry for a key that is not bound is worse than no entry
+/// at all — and so is the card view when there is no usable oko to open.
+fn help_section(app: &App, section: Section) -> (&'static str, Vec<(String, &'static str)>) {
+    let items = Action::ALL
+        .iter()
+        .filter(|a| a.section() == section && (**a != Action::Oko || app.oko_available))
+        .filter_map(|&action| {
+            let keys = app.keymap.keys_for(action);
+            (!keys.is_empty()).then(|| (keys.join(" / "), action.help()))
+        })
+        .collect();
+    (section.title(), items)
+}
 
-    let left = help_lines(&[
-        (
-            "Navigation",
-            &[
-                ("j/k ↑/↓", "move focus"),
-                ("Enter", "open / enter folder"),
-                ("Bksp", "up one directory"),
-                ("~ / Home", "go to home"),
-                ("g / G", "jump to top / bottom"),
-                ("PgUp/PgDn", "page up / down"),
-                ("Tab", "next pane"),
-                ("e", "edit path / favorites"),
-                ("f", "toggle favorite"),
-            ],
-        ),
-        ("Panes", &panes),
-        (
-            "Selection",
-            &[
-                ("Shift+j/k", "extend selection"),
-                ("Ctrl+a", "select all"),
-                ("Esc", "clear selection"),
-            ],
-        ),
-    ]);
+fn render_help_dialog(frame: &mut Frame, area: Rect, app: &App) {
+    let [navigation, panes, selection, files, git, view, other] =
+        Section::ALL.map(|section| help_section(app, section));
+    let left = help_lines(&app.theme, &[navigation, panes, selection, git]);
 
-    let right = help_lines(&[
-        (
-            "Files",
-            &[
-                ("y / x", "copy / cut"),
-                ("p, Ctrl+v", "paste"),
-                ("r / F2", "rename"),
-                ("d / Del", "delete (trash)"),
-                ("n / N", "new file / folder"),
-                ("o", "open in default app"),
-                ("t", "open in terminal"),
-            ],
-        ),
-        (
-            "View",
-            &[
-                ("/, Ctrl+f", "search"),
-                ("s / S", "sort field / direction"),
-                (".", "show hidden files"),
-                ("F5", "refresh"),
-            ],
-        ),
-        (
-            "Mouse",
-            &[
-                ("wheel", "scroll pane at cursor"),
-                ("click", "focus pane / select row"),
-                ("double-click", "open (same as Enter)"),
-            ],
-        ),
-        ("Other", &[("?", "toggle this help"), ("q", "quit")]),
-    ]);
+    let mouse = vec![
+        ("wheel".to_string(), "scroll pane at cursor"),
+        ("click".to_string(), "focus pane / select row"),
+        ("double-click".to_string(), "open (same as Enter)"),
+    ];
+    // The palette's commands that take an argument, and so have no key.
+    let commands = COMMANDS
+        .iter()
+        .map(|c| (format!(":{}", c.name), c.help))
+        .collect();
+    let mut right = help_lines(&app.theme, &[files, view, other, ("Commands", commands), ("Mouse", mouse)]);
+    // Settings from the config that could not be used, so that a key which
+    // does nothing, or a colour that did not take, can be traced back to why.
+    let problems = app.config_problems();
+    if !problems.is_empty() {
+        right.push(Line::from(""));
+        right.push(Line::from(Span::styled(
+            " Config problems",
+            Style::default().fg(app.theme.error).add_modifier(Modifier::BOLD),
+        )));
+        for problem in problems {
+            right.push(Line::from(Span::styled(
+                format!("  {}", problem),
+                Style::default().fg(app.theme.error),
+            )));
+        }
+    }
 
     let content_height = left.len().max(right.len()) as u16 + 2;
     let height = content_height.min(area.height.saturating_sub(2));
-    let width = 74u16.min(area.width.saturating_sub(4));
+    let width = 80u16.min(area.width.saturating_sub(4));
     let dialog = Rect::new(
         area.x + area.width.saturating_sub(width) / 2,
         area.y + area.height.saturating_sub(height) / 2,
@@ -1095,7 +2150,7 @@ fn render_help_dialog(frame: &mut Frame, area: Rect, oko_available: bool) {
     frame.render_widget(Clear, dialog);
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan))
+        .border_style(Style::default().fg(app.theme.prompt))
         .title(" Keyboard Shortcuts ");
     let inner = block.inner(dialog);
     frame.render_widget(block, dialog);
@@ -1115,64 +2170,6 @@ fn centered_rect(percent_x: u16, height: u16, area: Rect) -> Rect {
     Rect::new(popup_x + area.x, popup_y + area.y, popup_width, height)
 }
 
-fn format_size(bytes: u64) -> String {
-    if bytes == 0 {
-        return "0 B".to_string();
-    }
-    let units = ["B", "KB", "MB", "GB", "TB"];
-    let mut size = bytes as f64;
-    let mut unit_idx = 0;
-    while size >= 1024.0 && unit_idx < units.len() - 1 {
-        size /= 1024.0;
-        unit_idx += 1;
-    }
-    if unit_idx == 0 {
-        format!("{} B", bytes)
-    } else {
-        format!("{:.1} {}", size, units[unit_idx])
-    }
-}
-
-fn format_date(timestamp: u64) -> String {
-    if timestamp == 0 {
-        return "—".to_string();
-    }
-    // Simple date formatting: DD/MM/YY
-    let secs = timestamp as i64;
-    let days = secs / 86400;
-    // Approximate: days since epoch to date
-    // Using a simple algorithm
-    let mut y = 1970i32;
-    let mut remaining = days;
-    loop {
-        let days_in_year = if is_leap(y) { 366 } else { 365 };
-        if remaining < days_in_year {
-            break;
-        }
-        remaining -= days_in_year;
-        y += 1;
-    }
-    let month_days = if is_leap(y) {
-        [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-    } else {
-        [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31]
-    };
-    let mut m = 0usize;
-    for (i, &md) in month_days.iter().enumerate() {
-        if remaining < md as i64 {
-            m = i;
-            break;
-        }
-        remaining -= md as i64;
-    }
-    let d = remaining + 1;
-    format!("{:02}/{:02}/{:02}", d, m + 1, y % 100)
-}
-
-fn is_leap(y: i32) -> bool {
-    (y % 4 == 0 && y % 100 != 0) || y % 400 == 0
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1262,6 +2259,33 @@ mod tests {
         assert_eq!(before, 40);
         assert_eq!(after, 50, "0.625 of 80 columns");
     }
+
+    /// Side by side, a changed line and its replacement share a row; scrolled
+    /// past the end, the view settles with its last page full.
+    #[test]
+    fn diff_view_faces_each_change_with_its_replacement() {
+        let mut app = App::new().unwrap();
+        let lines = panex_core::diff::diff_lines("keep\nold line\n", "keep\nnew line\n");
+        app.mode = AppMode::Diff {
+            view: crate::diff::DiffView::new("/a/x.toml".into(), "/b/x.toml".into(), lines),
+        };
+        let screen = render(&mut app, 80, 20);
+        let row = screen
+            .lines()
+            .find(|l| l.contains("old line"))
+            .expect("old line not shown");
+        assert!(row.contains("new line"), "not on one row: {row:?}");
+        assert!(screen.contains("a/x.toml ↔ b/x.toml"), "{screen}");
+
+        if let AppMode::Diff { view } = &mut app.mode {
+            view.offset = 99;
+        }
+        render(&mut app, 80, 20);
+        let AppMode::Diff { view } = &app.mode else {
+            unreachable!()
+        };
+        assert_eq!(view.offset, 0, "both rows fit, so nothing is scrolled off");
+    }
 }
 
 #[cfg(test)]
@@ -1280,6 +2304,13 @@ mod scrollbar_tests {
                 is_dir: false,
                 size: 1024,
                 modified: 1_700_000_000,
+                kind: panex_core::FileKind::File,
+                created: 0,
+                mode: 0,
+                uid: 0,
+                items: None,
+                contents_size: None,
+                git: None,
             })
             .collect();
         app.pane_map.get_mut(&id).unwrap().entries = entries;
@@ -1414,6 +2445,13 @@ mod panic_probe {
                         is_dir: false,
                         size: 1,
                         modified: 1_700_000_000,
+                        kind: panex_core::FileKind::File,
+                        created: 0,
+                        mode: 0,
+                        uid: 0,
+                        items: None,
+                        contents_size: None,
+                        git: None,
                     })
                     .collect();
                 app.pane_map.get_mut(&id).unwrap().entries = entries.clone();
@@ -1606,7 +2644,7 @@ mod oko_tests {
                 .map(|x| buffer[(x, y)].symbol())
                 .collect();
             if line.contains(needle) {
-                return (0..buffer.area.width).any(|x| buffer[(x, y)].fg == ACCENT);
+                return (0..buffer.area.width).any(|x| buffer[(x, y)].fg == app.theme.accent);
             }
         }
         panic!("no line containing {needle:?}");
@@ -1700,7 +2738,7 @@ mod oko_layout_tests {
                 let text = (0..buffer.area.width)
                     .map(|x| buffer[(x, y)].symbol())
                     .collect::<String>();
-                let lit = (0..buffer.area.width).any(|x| buffer[(x, y)].bg == Color::DarkGray);
+                let lit = (0..buffer.area.width).any(|x| buffer[(x, y)].bg == app.theme.focus_bg);
                 (text, lit)
             })
             .collect()
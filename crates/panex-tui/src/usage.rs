@@ -0,0 +1,292 @@
+//! The disk usage view (`U`): the folder tree below where you were, biggest
+//! first, filling in as panex-core's scan reads it. Sizes are added up the
+//! tree listing by listing, so a folder's size is what has been found in it
+//! so far until the scan is done.
+
+use panex_core::usage::{Listing, Scan};
+use panex_core::SizeOptions;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+pub struct Node {
+    pub name: String,
+    /// `None` for the root, and for a node taken out of the tree.
+    pub parent: Option<usize>,
+    pub is_dir: bool,
+    /// On disk, of the entry and everything found below it.
+    pub size: u64,
+    /// Entries found below it, at any depth.
+    pub items: u64,
+    pub children: Vec<usize>,
+    /// Whether its listing has come in. A folder that is not read yet may
+    /// be bigger than it looks.
+    pub read: bool,
+    pub unreadable: bool,
+}
+
+pub struct Usage {
+    pub root: String,
+    /// Every entry found, the root first. Indices are ids: a node taken out
+    /// stays where it is, unlinked.
+    pub nodes: Vec<Node>,
+    /// Folder paths to their node, for placing listings as they come in.
+    folders: HashMap<String, usize>,
+    scan: Option<Scan>,
+    pub cancelled: bool,
+    pub unreadable: usize,
+    /// The folder being shown.
+    pub current: usize,
+    /// The entry under the cursor, by id rather than row: rows reorder as
+    /// sizes come in, and the cursor should stay on what it was on.
+    pub selected: Option<usize>,
+    /// First row on screen, and how many fit, as last drawn.
+    pub offset: usize,
+    pub page: usize,
+}
+
+impl Usage {
+    pub fn start(root: &str, options: SizeOptions) -> Result<Usage, String> {
+        let scan = Scan::start(root, options)?;
+        let name = std::path::Path::new(root)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| root.to_string());
+        Ok(Usage {
+            root: root.to_string(),
+            nodes: vec![Node {
+                name,
+                parent: None,
+                is_dir: true,
+                size: 0,
+                items: 0,
+                children: Vec::new(),
+                read: false,
+                unreadable: false,
+            }],
+            folders: HashMap::from([(root.to_string(), 0)]),
+            scan: Some(scan),
+            cancelled: false,
+            unreadable: 0,
+            current: 0,
+            selected: None,
+            offset: 0,
+            page: 10,
+        })
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scan.is_some()
+    }
+
+    /// Stop the scan, keeping what it found.
+    pub fn cancel(&mut self) {
+        if self.scan.take().is_some() {
+            self.cancelled = true;
+        }
+    }
+
+    /// Take in whatever the scan has read since the last call. Returns true
+    /// if anything changed.
+    pub fn pump(&mut self) -> bool {
+        let Some(scan) = &mut self.scan else {
+            return false;
+        };
+        let listings = scan.drain();
+        let finished = scan.is_done();
+        if finished {
+            self.scan = None;
+        }
+        let changed = finished || !listings.is_empty();
+        for listing in listings {
+            self.add(listing);
+        }
+        if self.selected.is_none() {
+            self.selected = self.rows().first().copied();
+        }
+        changed
+    }
+
+    fn add(&mut self, listing: Listing) {
+        let Some(&id) = self.folders.get(&listing.path) else {
+            return;
+        };
+        self.nodes[id].read = true;
+        if listing.error.is_some() {
+            self.nodes[id].unreadable = true;
+            self.unreadable += 1;
+        }
+        let (mut size, items) = (0, listing.entries.len() as u64);
+        for entry in listing.entries {
+            let child = self.nodes.len();
+            if entry.is_dir {
+                let path = PathBuf::from(&listing.path).join(&entry.name);
+                self.folders.insert(path.to_string_lossy().to_string(), child);
+            }
+            size += entry.size;
+            self.nodes.push(Node {
+                name: entry.name,
+                parent: Some(id),
+                is_dir: entry.is_dir,
+                size: entry.size,
+                items: 0,
+                children: Vec::new(),
+                read: !entry.is_dir,
+                unreadable: false,
+            });
+            self.nodes[id].children.push(child);
+        }
+        self.grow(id, size as i64, items as i64);
+    }
+
+    /// Add to the size and item count of `id` and every folder above it.
+    fn grow(&mut self, id: usize, size: i64, items: i64) {
+        let mut at = Some(id);
+        while let Some(node) = at {
+            let node = &mut self.nodes[node];
+            node.size = node.size.saturating_add_signed(size);
+            node.items = node.items.saturating_add_signed(items);
+            at = node.parent;
+        }
+    }
+
+    /// Take an entry out of the tree, once it is gone from disk.
+    pub fn remove(&mut self, id: usize) {
+        let Some(parent) = self.nodes[id].parent else {
+            return;
+        };
+        let rows = self.rows();
+        let (size, items) = (self.nodes[id].size, self.nodes[id].items + 1);
+        self.grow(parent, -(size as i64), -(items as i64));
+        self.nodes[parent].children.retain(|&c| c != id);
+        // Unlinked, so that listings still arriving from below it add to
+        // nothing that is shown.
+        self.nodes[id].parent = None;
+        if self.selected == Some(id) {
+            let row = rows.iter().position(|&r| r == id).unwrap_or(0);
+            let rows = self.rows();
+            self.selected = rows.get(row).or(rows.last()).copied();
+        }
+    }
+
+    /// The entries of the current folder, biggest first.
+    pub fn rows(&self) -> Vec<usize> {
+        let mut rows = self.nodes[self.current].children.clone();
+        rows.sort_by(|&a, &b| {
+            let (a, b) = (&self.nodes[a], &self.nodes[b]);
+            b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name))
+        });
+        rows
+    }
+
+    pub fn path(&self, id: usize) -> String {
+        let mut names = Vec::new();
+        let mut at = id;
+        while let Some(parent) = self.nodes[at].parent {
+            names.push(self.nodes[at].name.as_str());
+            at = parent;
+        }
+        let mut path = PathBuf::from(&self.root);
+        path.extend(names.iter().rev());
+        path.to_string_lossy().to_string()
+    }
+
+    /// The cursor's row in `rows`.
+    pub fn cursor(&self, rows: &[usize]) -> usize {
+        self.selected
+            .and_then(|id| rows.iter().position(|&r| r == id))
+            .unwrap_or(0)
+    }
+
+    pub fn move_by(&mut self, delta: isize) {
+        let rows = self.rows();
+        if rows.is_empty() {
+            return;
+        }
+        let row = self.cursor(&rows).saturating_add_signed(delta).min(rows.len() - 1);
+        self.selected = Some(rows[row]);
+    }
+
+    pub fn move_page(&mut self, pages: isize) {
+        self.move_by(pages * self.page.max(1) as isize);
+    }
+
+    /// Show the folder under the cursor. Returns false if it is not one.
+    pub fn enter(&mut self) -> bool {
+        match self.selected {
+            Some(id) if self.nodes[id].is_dir => {
+                self.current = id;
+                self.offset = 0;
+                self.selected = self.rows().first().copied();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Show the folder above, with the cursor on the one just left.
+    pub fn leave(&mut self) -> bool {
+        match self.nodes[self.current].parent {
+            Some(parent) => {
+                self.selected = Some(self.current);
+                self.current = parent;
+                self.offset = 0;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::click_tests::TempDir;
+    use std::time::{Duration, Instant};
+
+    fn scanned(root: &std::path::Path) -> Usage {
+        let mut usage = Usage::start(&root.to_string_lossy(), SizeOptions::default()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while usage.is_scanning() && Instant::now() < deadline {
+            usage.pump();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        usage
+    }
+
+    #[test]
+    fn sizes_add_up_the_tree_and_the_biggest_comes_first() {
+        let tmp = TempDir::new("usage-view");
+        let root = &tmp.0;
+        std::fs::create_dir_all(root.join("big/inner")).unwrap();
+        std::fs::create_dir_all(root.join("small")).unwrap();
+        std::fs::write(root.join("big/inner/data"), vec![1u8; 256 * 1024]).unwrap();
+        std::fs::write(root.join("small/data"), vec![1u8; 4 * 1024]).unwrap();
+
+        let mut usage = scanned(root);
+        assert!(!usage.is_scanning() && !usage.cancelled);
+        let names = |usage: &Usage| -> Vec<String> {
+            usage.rows().iter().map(|&id| usage.nodes[id].name.clone()).collect()
+        };
+        assert_eq!(names(&usage), ["big", "small"]);
+        let big = usage.rows()[0];
+        assert!(usage.nodes[big].size >= 256 * 1024);
+        assert_eq!(usage.nodes[big].items, 2);
+        assert_eq!(usage.nodes[0].items, 5);
+        assert_eq!(usage.path(big), root.join("big").to_string_lossy());
+
+        assert!(usage.enter());
+        assert_eq!(names(&usage), ["inner"]);
+        assert!(usage.leave());
+        assert_eq!(usage.selected, Some(big), "the cursor comes back to the folder left");
+
+        let total = usage.nodes[0].size;
+        let small = usage.rows()[1];
+        let small_size = usage.nodes[small].size;
+        usage.selected = Some(small);
+        usage.remove(small);
+        assert_eq!(names(&usage), ["big"]);
+        assert_eq!(usage.nodes[0].size, total - small_size);
+        assert_eq!(usage.nodes[0].items, 3);
+        assert_eq!(usage.selected, Some(big));
+    }
+}
@@ -1,5 +1,8 @@
-use panex_core::FileEntry;
-use panex_core::config::PanexConfig;
+use panex_core::{DirSize, FileEntry};
+use panex_core::apps;
+use panex_core::config::{Favorite, Opener, PanexConfig, RunIn};
+use panex_core::diff::DiffLine;
+use panex_core::git::Commit;
 
 #[tauri::command]
 pub fn read_dir(path: String) -> Result<Vec<FileEntry>, String> {
@@ -22,12 +25,39 @@ pub fn get_parent_dir(path: String) -> Result<String, String> {
 #[tauri::command]
 pub fn open_entry(path: String) -> Result<(), String> {
     let config = PanexConfig::load();
-    if let Some(ext) = panex_core::get_extension(&path) {
-        if let Some(app) = config.get_gui_app(&ext) {
-            return panex_core::open_entry_with_app(&path, Some(app));
+    let paths = [path];
+    match config.gui_openers(&paths).first() {
+        // An opener that does not say where it runs is an application, as
+        // [open.gui] entries always were: `open -a` takes a name on macOS.
+        Some(opener) if opener.run.is_none() => {
+            panex_core::open_entry_with_app(&paths[0], Some(&opener.command))
         }
+        Some(opener) => apps::launch(opener, &paths, RunIn::Detached),
+        None => panex_core::open_entry(&paths[0]),
     }
-    panex_core::open_entry(&path)
+}
+
+/// The configured openers for all of `paths`, then the desktop's.
+#[tauri::command]
+pub fn open_with_choices(paths: Vec<String>) -> Vec<Opener> {
+    let configured = PanexConfig::load().gui_openers(&paths);
+    apps::open_with_choices(configured, &paths)
+}
+
+/// Open `paths` with the `choice`th of `open_with_choices`, all in one run
+/// unless it takes one file; none is the system default, a file at a time.
+/// The list is built again here rather than taken from the webview, so only
+/// what the config or the desktop registered can be run.
+#[tauri::command]
+pub fn open_with(paths: Vec<String>, choice: Option<usize>) -> Result<(), String> {
+    let Some(choice) = choice else {
+        return paths.iter().try_for_each(|p| panex_core::open_entry(p));
+    };
+    let choices = open_with_choices(paths.clone());
+    let opener = choices
+        .get(choice)
+        .ok_or_else(|| "That program is no longer offered".to_string())?;
+    apps::launch(opener, &paths, RunIn::Detached)
 }
 
 #[tauri::command]
@@ -51,7 +81,7 @@ pub fn move_entry(source: String, dest_dir: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub fn calculate_dir_size(path: String) -> Result<u64, String> {
+pub fn calculate_dir_size(path: String) -> Result<DirSize, String> {
     panex_core::calculate_directory_size(&path)
 }
 
@@ -71,8 +101,8 @@ pub fn open_in_terminal(path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn get_favorites() -> Vec<String> {
-    PanexConfig::load().favorites.paths
+pub fn get_favorites() -> Vec<Favorite> {
+    PanexConfig::load().favorites.entries
 }
 
 #[tauri::command]
@@ -80,8 +110,47 @@ pub fn is_favorite(path: String) -> bool {
     PanexConfig::load().is_favorite(&path)
 }
 
+/// The favorite as it now is, or `None` when it was taken away.
 #[tauri::command]
-pub fn toggle_favorite(path: String) -> Result<bool, String> {
+pub fn toggle_favorite(path: String) -> Result<Option<Favorite>, String> {
     let mut config = PanexConfig::load();
-    config.toggle_favorite(&path)
+    config.toggle_favorite(&path)?;
+    Ok(config.favorites.get(&path).cloned())
+}
+
+/// The folders `query` finds in the TUI's visit ranking, best first.
+#[tauri::command]
+pub fn frecent_dirs(query: String, limit: Option<usize>) -> Vec<String> {
+    let frecency = panex_core::frecency::Frecency::load();
+    frecency
+        .query(&query, panex_core::frecency::now())
+        .into_iter()
+        .take(limit.unwrap_or(10))
+        .map(|d| d.path.clone())
+        .collect()
+}
+
+#[tauri::command]
+pub fn git_stage(paths: Vec<String>) -> Result<(), String> {
+    panex_core::git::stage(&paths)
+}
+
+#[tauri::command]
+pub fn git_unstage(paths: Vec<String>) -> Result<(), String> {
+    panex_core::git::unstage(&paths)
+}
+
+#[tauri::command]
+pub fn git_discard(paths: Vec<String>, permanent: Option<bool>) -> Result<(), String> {
+    panex_core::git::discard(&paths, permanent.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn git_diff(path: String) -> Result<Vec<DiffLine>, String> {
+    panex_core::git::diff(&path)
+}
+
+#[tauri::command]
+pub fn git_log(path: String, limit: Option<usize>) -> Result<Vec<Commit>, String> {
+    panex_core::git::log(&path, limit.unwrap_or(200))
 }
@@ -18,6 +18,8 @@ pub fn run() {
             commands::get_home_dir,
             commands::get_parent_dir,
             commands::open_entry,
+            commands::open_with_choices,
+            commands::open_with,
             commands::rename_entry,
             commands::delete_entry,
             commands::copy_entry,
@@ -29,6 +31,12 @@ pub fn run() {
             commands::get_favorites,
             commands::is_favorite,
             commands::toggle_favorite,
+            commands::frecent_dirs,
+            commands::git_stage,
+            commands::git_unstage,
+            commands::git_discard,
+            commands::git_diff,
+            commands::git_log,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
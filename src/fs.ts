@@ -1,10 +1,14 @@
-import type { FileEntry } from "./types.ts";
+import type { DiffLine, DirSize, Favorite, FileEntry, GitCommit, Opener } from "./types.ts";
 
 export interface FsBackend {
   readDir(path: string): Promise<FileEntry[]>;
   getHomeDir(): Promise<string>;
   getParentDir(path: string): Promise<string>;
   openEntry(path: string): Promise<void>;
+  openWithChoices(paths: string[]): Promise<Opener[]>;
+  // An index into what openWithChoices gave for the same paths; null is
+  // the system default.
+  openWith(paths: string[], choice: number | null): Promise<void>;
   renameEntry(path: string, newName: string): Promise<void>;
   deleteEntry(path: string, permanent?: boolean): Promise<void>;
   copyEntry(source: string, destDir: string): Promise<string>;
@@ -13,9 +17,16 @@ export interface FsBackend {
   createFile(dir: string, name: string): Promise<void>;
   createFolder(dir: string, name: string): Promise<void>;
   openInTerminal(path: string): Promise<void>;
-  getFavorites(): Promise<string[]>;
+  getFavorites(): Promise<Favorite[]>;
   isFavorite(path: string): Promise<boolean>;
-  toggleFavorite(path: string): Promise<boolean>;
+  // The favorite as it now is, or null when it was taken away.
+  toggleFavorite(path: string): Promise<Favorite | null>;
+  frecentDirs(query: string): Promise<string[]>;
+  gitStage(paths: string[]): Promise<void>;
+  gitUnstage(paths: string[]): Promise<void>;
+  gitDiscard(paths: string[]): Promise<void>;
+  gitDiff(path: string): Promise<DiffLine[]>;
+  gitLog(path: string): Promise<GitCommit[]>;
 }
 
 function createTauriFs(): FsBackend {
@@ -47,6 +58,14 @@ function createTauriFs(): FsBackend {
       const invoke = await getInvoke();
       await invoke("open_entry", { path });
     },
+    async openWithChoices(paths: string[]): Promise<Opener[]> {
+      const invoke = await getInvoke();
+      return invoke<Opener[]>("open_with_choices", { paths });
+    },
+    async openWith(paths: string[], choice: number | null): Promise<void> {
+      const invoke = await getInvoke();
+      await invoke("open_with", { paths, choice });
+    },
     async renameEntry(path: string, newName: string): Promise<void> {
       const invoke = await getInvoke();
       await invoke("rename_entry", { path, newName });
@@ -65,7 +84,8 @@ function createTauriFs(): FsBackend {
     },
     async getDirSize(path: string): Promise<number> {
       const invoke = await getInvoke();
-      return invoke<number>("calculate_dir_size", { path });
+      const size = await invoke<DirSize>("calculate_dir_size", { path });
+      return size.on_disk;
     },
     async createFile(dir: string, name: string): Promise<void> {
       const invoke = await getInvoke();
@@ -79,17 +99,41 @@ function createTauriFs(): FsBackend {
       const invoke = await getInvoke();
       await invoke("open_in_terminal", { path });
     },
-    async getFavorites(): Promise<string[]> {
+    async getFavorites(): Promise<Favorite[]> {
       const invoke = await getInvoke();
-      return invoke<string[]>("get_favorites");
+      return invoke<Favorite[]>("get_favorites");
     },
     async isFavorite(path: string): Promise<boolean> {
       const invoke = await getInvoke();
       return invoke<boolean>("is_favorite", { path });
     },
-    async toggleFavorite(path: string): Promise<boolean> {
+    async toggleFavorite(path: string): Promise<Favorite | null> {
+      const invoke = await getInvoke();
+      return invoke<Favorite | null>("toggle_favorite", { path });
+    },
+    async frecentDirs(query: string): Promise<string[]> {
+      const invoke = await getInvoke();
+      return invoke<string[]>("frecent_dirs", { query });
+    },
+    async gitStage(paths: string[]): Promise<void> {
       const invoke = await getInvoke();
-      return invoke<boolean>("toggle_favorite", { path });
+      await invoke("git_stage", { paths });
+    },
+    async gitUnstage(paths: string[]): Promise<void> {
+      const invoke = await getInvoke();
+      await invoke("git_unstage", { paths });
+    },
+    async gitDiscard(paths: string[]): Promise<void> {
+      const invoke = await getInvoke();
+      await invoke("git_discard", { paths });
+    },
+    async gitDiff(path: string): Promise<DiffLine[]> {
+      const invoke = await getInvoke();
+      return invoke<DiffLine[]>("git_diff", { path });
+    },
+    async gitLog(path: string): Promise<GitCommit[]> {
+      const invoke = await getInvoke();
+      return invoke<GitCommit[]>("git_log", { path });
     },
   };
 }
@@ -234,6 +278,16 @@ function createBrowserFs(): FsBackend {
       }
     },
 
+    async openWithChoices(): Promise<Opener[]> {
+      return [];
+    },
+
+    async openWith(paths: string[]): Promise<void> {
+      for (const path of paths) {
+        await this.openEntry(path);
+      }
+    },
+
     async renameEntry(path: string, newName: string): Promise<void> {
       const { parentPath, name } = splitPath(path);
       const parentHandle = await resolveDir(parentPath);
@@ -331,30 +385,37 @@ function createBrowserFs(): FsBackend {
       alert("Open in Terminal is not available in browser mode.");
     },
 
-    async getFavorites(): Promise<string[]> {
+    async getFavorites(): Promise<Favorite[]> {
       const stored = localStorage.getItem("panex_favorites");
-      return stored ? JSON.parse(stored) : [];
+      // Stored before favorites had labels, as bare paths.
+      const favs: (string | Favorite)[] = stored ? JSON.parse(stored) : [];
+      return favs.map((f) => (typeof f === "string" ? { path: f } : f));
     },
 
     async isFavorite(path: string): Promise<boolean> {
       const favs = await this.getFavorites();
-      return favs.includes(path);
+      return favs.some((f) => f.path === path);
     },
 
-    async toggleFavorite(path: string): Promise<boolean> {
+    async toggleFavorite(path: string): Promise<Favorite | null> {
       const favs = await this.getFavorites();
-      const idx = favs.indexOf(path);
+      const idx = favs.findIndex((f) => f.path === path);
       if (idx >= 0) {
         favs.splice(idx, 1);
         localStorage.setItem("panex_favorites", JSON.stringify(favs));
-        return false;
+        return null;
       } else {
-        favs.push(path);
+        const fav = { path };
+        favs.push(fav);
         localStorage.setItem("panex_favorites", JSON.stringify(favs));
-        return true;
+        return fav;
       }
     },
 
+    async frecentDirs(): Promise<string[]> {
+      return [];
+    },
+
     async getDirSize(path: string): Promise<number> {
       async function walkHandle(handle: FileSystemDirectoryHandle): Promise<number> {
         let total = 0;
@@ -373,6 +434,23 @@ function createBrowserFs(): FsBackend {
       const dirHandle = await resolveDir(path);
       return walkHandle(dirHandle);
     },
+
+    // There is no git to run from a browser tab.
+    async gitStage(): Promise<void> {
+      throw new Error("Git is not available in the browser");
+    },
+    async gitUnstage(): Promise<void> {
+      throw new Error("Git is not available in the browser");
+    },
+    async gitDiscard(): Promise<void> {
+      throw new Error("Git is not available in the browser");
+    },
+    async gitDiff(): Promise<DiffLine[]> {
+      throw new Error("Git is not available in the browser");
+    },
+    async gitLog(): Promise<GitCommit[]> {
+      throw new Error("Git is not available in the browser");
+    },
   };
 }
 
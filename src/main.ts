@@ -1,6 +1,7 @@
 import { fs, isBrowser } from "./fs.ts";
-import type { FileEntry, PaneState, LayoutNode, LayoutSplit, SplitDirection, SortField, SortDirection } from "./types.ts";
+import type { Favorite, FileEntry, GitAction, PaneState, LayoutNode, LayoutSplit, SplitDirection, SortField, SortDirection } from "./types.ts";
 import { createPane, loadDirectory, navigateInto, navigateUp, renderPane, buildDisplayList } from "./pane.ts";
+import { showContextMenu } from "./context-menu.ts";
 import { countLeaves, splitPane, removePane, collectLeafIds } from "./layout.ts";
 import { shouldShowSupportPrompt, dismissSupportPrompt } from "./licensing.ts";
 import { initTheme, cycleTheme, getTheme } from "./theme.ts";
@@ -19,7 +20,7 @@ const dirSizeCache = new Map<string, number>();
 // Stores the full unfiltered entries per pane (for search re-filtering without disk reload)
 const rawEntriesMap = new Map<string, FileEntry[]>();
 // Cached favorites list and per-path state
-let cachedFavorites: string[] = [];
+let cachedFavorites: Favorite[] = [];
 const favoriteStateCache = new Map<string, boolean>();
 
 function nextPaneId(): string {
@@ -526,12 +527,12 @@ async function reloadFavorites() {
   cachedFavorites = await fs.getFavorites();
   favoriteStateCache.clear();
   for (const fav of cachedFavorites) {
-    favoriteStateCache.set(fav, true);
+    favoriteStateCache.set(fav.path, true);
   }
 }
 
 function isPathFavorite(path: string): boolean {
-  return favoriteStateCache.has(path) || cachedFavorites.includes(path);
+  return favoriteStateCache.has(path) || cachedFavorites.some((f) => f.path === path);
 }
 
 async function handleToggleFavorite(paneId: string) {
@@ -709,6 +710,9 @@ function renderNode(node: LayoutNode, totalPanes: number): HTMLElement {
       onNavigateUp: () => handleNavigateUp(paneId),
       onHome: () => handleHome(paneId),
       onOpen: (entry: FileEntry) => handleOpen(entry),
+      onOpenWith: isBrowser()
+        ? undefined
+        : (entry: FileEntry, x: number, y: number) => handleOpenWith(paneId, entry, x, y),
       onRename: (entry: FileEntry, newName: string) => handleRename(paneId, entry, newName),
       onDelete: (entry: FileEntry) => handleDelete(paneId, entry),
       onDrop: (entries: FileEntry[], sourcePaneId: string, copy: boolean) =>
@@ -739,11 +743,13 @@ function renderNode(node: LayoutNode, totalPanes: number): HTMLElement {
       onCreateFile: () => handleCreateFile(paneId),
       onCreateFolder: () => handleCreateFolder(paneId),
       onOpenInTerminal: () => handleOpenInTerminal(paneId),
+      onGit: isBrowser() ? undefined : (entry: FileEntry, action: GitAction) => handleGit(paneId, entry, action),
       onDropOnFolder: (entries: FileEntry[], targetFolderPath: string, sourcePaneId: string, isCopy: boolean) =>
         handleDropOnFolder(paneId, entries, targetFolderPath, sourcePaneId, isCopy),
       isFavorite: isPathFavorite(pane.currentPath),
       onToggleFavorite: () => handleToggleFavorite(paneId),
       favorites: cachedFavorites,
+      getFrecent: (query) => fs.frecentDirs(query),
       onSearchChange: (query: string) => handleSearchChange(paneId, query),
       onSearchExit: () => {
         const p = paneMap.get(paneId);
@@ -1101,6 +1107,30 @@ async function handleOpen(entry: FileEntry) {
   }
 }
 
+// The selection when the clicked row is in it, all in one run of the
+// program chosen from a second menu where the first one was.
+async function handleOpenWith(paneId: string, entry: FileEntry, x: number, y: number) {
+  const pane = paneMap.get(paneId);
+  if (!pane) return;
+  const paths = pane.selectedPaths.has(entry.path) ? [...pane.selectedPaths] : [entry.path];
+  const open = async (choice: number | null) => {
+    try {
+      await fs.openWith(paths, choice);
+    } catch (e) {
+      alert(`Failed to open: ${e}`);
+    }
+  };
+  const choices = await fs.openWithChoices(paths);
+  showContextMenu(x, y, [
+    ...choices.map((opener, i) => ({
+      label: opener.name ?? opener.command,
+      shortcut: opener.name ? opener.command : undefined,
+      action: () => open(i),
+    })),
+    { label: "System Default", action: () => open(null), divider: choices.length > 0 },
+  ]);
+}
+
 async function handleRename(paneId: string, entry: FileEntry, newName: string) {
   try {
     await fs.renameEntry(entry.path, newName);
@@ -1294,6 +1324,52 @@ async function handleOpenInTerminal(paneId: string) {
   }
 }
 
+// Stage, unstage and discard act on the selection when the clicked row is in
+// it, as the other context-menu actions do; diff and history are one path's.
+async function handleGit(paneId: string, entry: FileEntry, action: GitAction) {
+  const pane = paneMap.get(paneId);
+  if (!pane) return;
+  const paths = pane.selectedPaths.has(entry.path) ? [...pane.selectedPaths] : [entry.path];
+  try {
+    switch (action) {
+      case "stage":
+        await fs.gitStage(paths);
+        break;
+      case "unstage":
+        await fs.gitUnstage(paths);
+        break;
+      case "discard": {
+        const what = paths.length === 1 ? `"${entry.name}"` : `${paths.length} items`;
+        const confirmed = await showConfirmDialog(
+          `Discard changes to ${what}?`,
+          "They will be put back as the last commit has them. Changed files go to the Trash.",
+          "Discard Changes"
+        );
+        if (!confirmed) return;
+        await fs.gitDiscard(paths);
+        break;
+      }
+      case "diff": {
+        const lines = await fs.gitDiff(entry.path);
+        const marks = { Same: " ", Removed: "-", Added: "+" };
+        showTextDialog(`${entry.name} against HEAD`, lines.map((l) => `${marks[l.change]} ${l.text}`));
+        return;
+      }
+      case "log": {
+        const commits = await fs.gitLog(entry.path);
+        showTextDialog(
+          `History of ${entry.name}`,
+          commits.map((c) => `${c.id}  ${new Date(c.time * 1000).toLocaleDateString()}  ${c.author}  ${c.subject}`)
+        );
+        return;
+      }
+    }
+  } catch (e) {
+    alert(`Git failed: ${e}`);
+  }
+  await refreshPanesShowingPaths(pane.currentPath);
+}
+
 async function handleDropOnFolder(
   paneId: string,
   entries: FileEntry[],
@@ -1651,6 +1727,55 @@ function showConflictDialog(name: string): Promise<"replace" | "keep-both" | "ca
   });
 }
 
+// A read-only, scrollable block of text: a diff or a history.
+function showTextDialog(title: string, lines: string[]): void {
+  const overlay = document.createElement("div");
+  overlay.className = "dialog-overlay";
+
+  const dialog = document.createElement("div");
+  dialog.className = "dialog dialog-wide";
+
+  const titleEl = document.createElement("div");
+  titleEl.className = "dialog-title";
+  titleEl.textContent = title;
+
+  const text = document.createElement("pre");
+  text.className = "dialog-text";
+  text.textContent = lines.length > 0 ? lines.join("\n") : "(nothing)";
+
+  const actions = document.createElement("div");
+  actions.className = "dialog-actions";
+
+  const closeBtn = document.createElement("button");
+  closeBtn.className = "dialog-btn dialog-btn-primary";
+  closeBtn.textContent = "Close";
+  actions.appendChild(closeBtn);
+
+  dialog.appendChild(titleEl);
+  dialog.appendChild(text);
+  dialog.appendChild(actions);
+  overlay.appendChild(dialog);
+  document.body.appendChild(overlay);
+  makeDialogDraggable(dialog, titleEl);
+
+  closeBtn.focus();
+
+  function cleanup() {
+    overlay.remove();
+    document.removeEventListener("keydown", onKeyDown);
+  }
+
+  function onKeyDown(e: KeyboardEvent) {
+    if (e.key === "Escape") cleanup();
+  }
+
+  closeBtn.addEventListener("click", cleanup);
+  overlay.addEventListener("click", (e) => {
+    if (e.target === overlay) cleanup();
+  });
+  document.addEventListener("keydown", onKeyDown);
+}
+
 function showConfirmDialog(title: string, message: string, actionLabel = "Move to Trash"): Promise<boolean> {
   return new Promise((resolve) => {
     const overlay = document.createElement("div");
@@ -1,5 +1,5 @@
 import { fs } from "./fs.ts";
-import type { FileEntry, PaneState, SortField, SortDirection } from "./types.ts";
+import type { Favorite, FileEntry, GitAction, PaneState, SortField, SortDirection } from "./types.ts";
 import { showContextMenu } from "./context-menu.ts";
 
 export function createPane(id: string, initialPath: string): PaneState {
@@ -42,6 +42,7 @@ export interface PaneCallbacks {
   onNavigateUp: () => void;
   onHome: () => void;
   onOpen: (entry: FileEntry) => void;
+  onOpenWith?: (entry: FileEntry, x: number, y: number) => void;
   onRename: (entry: FileEntry, newName: string) => void;
   onDelete: (entry: FileEntry) => void;
   onDrop: (entries: FileEntry[], sourcePaneId: string, copy: boolean) => void;
@@ -68,7 +69,9 @@ export interface PaneCallbacks {
   onDropOnFolder?: (entries: FileEntry[], targetFolderPath: string, sourcePaneId: string, isCopy: boolean) => void;
   isFavorite?: boolean;
   onToggleFavorite?: () => void;
-  favorites?: string[];
+  favorites?: Favorite[];
+  getFrecent?: (query: string) => Promise<string[]>;
+  onGit?: (entry: FileEntry, action: GitAction) => void;
 }
 
 export function renderPane(
@@ -104,9 +107,7 @@ export function renderPane(
     pathInput.value = pane.currentPath;
     pathInput.focus();
     pathInput.select();
-    if (callbacks.favorites && callbacks.favorites.length > 0) {
-      favDropdown.style.display = "";
-    }
+    void fillDropdown("");
   }
 
   function hidePathInput() {
@@ -134,6 +135,10 @@ export function renderPane(
     }
   });
 
+  pathInput.addEventListener("input", () => {
+    void fillDropdown(pathInput.value.trim());
+  });
+
   pathInput.addEventListener("blur", () => {
     hidePathInput();
   });
@@ -188,19 +193,55 @@ export function renderPane(
   favDropdown.className = "favorites-dropdown";
   favDropdown.style.display = "none";
 
-  if (callbacks.favorites && callbacks.favorites.length > 0) {
-    for (const favPath of callbacks.favorites) {
-      const item = document.createElement("button");
-      item.className = "favorites-dropdown-item";
-      item.textContent = favPath;
-      item.addEventListener("mousedown", (e) => {
-        e.preventDefault(); // prevent blur on pathInput
-        callbacks.onNavigateTo(favPath);
-        favDropdown.style.display = "none";
-        hidePathInput();
-      });
-      favDropdown.appendChild(item);
+  // Favorites first, then the folders most visited that match what is
+  // typed — a path being typed out in full matches nothing, which is fine.
+  let dropdownQuery: string | null = null;
+  async function fillDropdown(query: string) {
+    dropdownQuery = query;
+    const frecent = callbacks.getFrecent ? await callbacks.getFrecent(query) : [];
+    if (dropdownQuery !== query || pathInput.style.display === "none") return;
+    const favorites = callbacks.favorites ?? [];
+    const others = frecent
+      .filter((p) => !favorites.some((f) => f.path === p))
+      .map((path): Favorite => ({ path }));
+    favDropdown.replaceChildren();
+    let group: string | undefined;
+    for (const fav of favorites) {
+      // Favorites come grouped; a heading starts each group.
+      if (fav.group && fav.group !== group) {
+        const heading = document.createElement("div");
+        heading.className = "favorites-dropdown-group";
+        heading.textContent = fav.group;
+        favDropdown.appendChild(heading);
+      }
+      group = fav.group;
+      favDropdown.appendChild(dropdownItem(fav, false));
     }
+    for (const fav of others) {
+      favDropdown.appendChild(dropdownItem(fav, true));
+    }
+    favDropdown.style.display = favorites.length + others.length > 0 ? "" : "none";
+  }
+
+  function dropdownItem(fav: Favorite, frecent: boolean): HTMLElement {
+    const item = document.createElement("button");
+    item.className = "favorites-dropdown-item";
+    if (frecent) item.classList.add("frecent");
+    item.textContent = fav.label ?? fav.path;
+    item.title = fav.path;
+    if (fav.key) {
+      const key = document.createElement("span");
+      key.className = "favorites-dropdown-key";
+      key.textContent = `'${fav.key}`;
+      item.prepend(key);
+    }
+    item.addEventListener("mousedown", (e) => {
+      e.preventDefault(); // prevent blur on pathInput
+      callbacks.onNavigateTo(fav.path);
+      favDropdown.style.display = "none";
+      hidePathInput();
+    });
+    return item;
   }
 
   const homeBtn = document.createElement("button");
@@ -595,6 +636,12 @@ export function renderPane(
             }
           },
         },
+      ];
+      if (callbacks.onOpenWith) {
+        const onOpenWith = callbacks.onOpenWith;
+        items.push({ label: "Open With\u2026", action: () => onOpenWith(entry, e.clientX, e.clientY) });
+      }
+      items.push(
         {
           label: "Copy",
           shortcut: `${mod}C`,
@@ -620,7 +667,19 @@ export function renderPane(
           shortcut: isMac ? "\u2318\u232B" : "Del",
           action: () => callbacks.onDelete(entry),
         },
-      ];
+      );
+      if (callbacks.onGit) {
+        const onGit = callbacks.onGit;
+        items.push(
+          { label: "Stage", action: () => onGit(entry, "stage"), divider: true },
+          { label: "Unstage", action: () => onGit(entry, "unstage") },
+          { label: "Discard Changes", action: () => onGit(entry, "discard") },
+        );
+        if (!entry.is_dir) {
+          items.push({ label: "Show Diff", action: () => onGit(entry, "diff") });
+        }
+        items.push({ label: "Show History", action: () => onGit(entry, "log") });
+      }
       if (entry.is_dir && callbacks.onGetDirSize) {
         const cached = callbacks.getDirSize?.(entry.path) ?? null;
         items.push({
@@ -4,8 +4,70 @@ export interface FileEntry {
   is_dir: boolean;
   size: number;
   modified: number;
+  kind?: FileKind;
+  created?: number;
+  mode?: number;
+  uid?: number;
+  items?: number;
+  contents_size?: number;
+  git?: GitStatus;
 }
 
+// What `calculate_dir_size` found below a folder; hard links count once.
+export interface DirSize {
+  apparent: number;
+  on_disk: number;
+  files: number;
+  dirs: number;
+  errors: number;
+}
+
+export type GitStatus = "ignored" | "untracked" | "staged" | "modified" | "conflicted";
+
+export type GitAction = "stage" | "unstage" | "discard" | "diff" | "log";
+
+// One line of `git_diff`'s whole-file diff against HEAD; a side is null where
+// the line is not on it.
+export interface DiffLine {
+  change: "Same" | "Removed" | "Added";
+  left: number | null;
+  right: number | null;
+  text: string;
+}
+
+// A favorite folder; `key` is the TUI's `'` mark for it.
+export interface Favorite {
+  path: string;
+  label?: string;
+  group?: string;
+  key?: string;
+}
+
+// What a file can be opened with: a command the paths are appended to.
+export interface Opener {
+  command: string;
+  name?: string;
+  run?: "terminal" | "current" | "detached";
+  single?: boolean;
+}
+
+export interface GitCommit {
+  id: string;
+  author: string;
+  time: number;
+  subject: string;
+}
+
+export type FileKind =
+  | "file"
+  | "dir"
+  | "symlink"
+  | "broken_symlink"
+  | "executable"
+  | "socket"
+  | "fifo"
+  | "device";
+
 export interface PaneState {
   id: string;
   currentPath: string;